@@ -1,2 +1,5 @@
 pub mod controller;
+pub mod file_presenter;
+pub mod multi_presenter;
 pub mod presenter;
+pub mod run_store_presenter;
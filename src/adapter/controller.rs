@@ -1,36 +1,61 @@
+use crate::domain::diagnostics::Diagnostic;
+use crate::domain::formula_validation::FormulaValidationPolicy;
 use crate::domain::repositories::*;
+use crate::domain::services::{CostedProduction, RoundingPolicy, SafetyStockPolicy, YieldPolicy};
+use crate::domain::sheet_schema::{ProductionSheetSchema, SchemaMapping};
+use crate::domain::value_objects::TransactionDate;
 use crate::usecase::interactor::{
     CalculateMaterialCostInteractor, CreateInventoryHistoryInteractor,
+    CreateMonthlyInventoryReportInteractor, ReportDiagnosticsInteractor, ValidateFormulaInteractor,
 };
 use crate::usecase::ports::*;
 use color_eyre::Result;
 
 /// Excelコントローラ
-pub struct ExcelController<'a, F, P, FR, R, T, O>
+pub struct ExcelController<'a, F, P, FR, R, T, OB, O>
 where
     F: FormulaRepository,
     P: PurchaseRepository,
     FR: FreightMasterRepository,
     R: ProductionRepository,
     T: InventoryTransactionRepository,
-    O: CalculateMaterialCostOutputPort + CreateInventoryHistoryOutputPort,
+    OB: OpeningBalanceRepository,
+    O: ReportDiagnosticsOutputPort
+        + ValidateFormulaOutputPort
+        + CalculateMaterialCostOutputPort
+        + CreateMonthlyInventoryReportOutputPort
+        + CreateInventoryHistoryOutputPort,
 {
     formula_repo: &'a F,
     purchase_repo: &'a P,
     freight_repo: &'a FR,
     production_repo: &'a R,
     transaction_repo: &'a T,
+    opening_balance_repo: &'a OB,
     output_port: &'a mut O,
+    safety_stock: SafetyStockPolicy,
+    period_start: Option<TransactionDate>,
+    formula_validation: FormulaValidationPolicy,
+    rounding: RoundingPolicy,
+    yield_policy: YieldPolicy,
+    production_sheet: String,
+    accumulate_errors: bool,
+    costed_productions: Vec<CostedProduction>, // 材料費計算の結果（入出庫履歴・月次受払表で再利用）
 }
 
-impl<'a, F, P, FR, R, T, O> ExcelController<'a, F, P, FR, R, T, O>
+impl<'a, F, P, FR, R, T, OB, O> ExcelController<'a, F, P, FR, R, T, OB, O>
 where
     F: FormulaRepository,
     P: PurchaseRepository,
     FR: FreightMasterRepository,
     R: ProductionRepository,
     T: InventoryTransactionRepository,
-    O: CalculateMaterialCostOutputPort + CreateInventoryHistoryOutputPort,
+    OB: OpeningBalanceRepository,
+    O: ReportDiagnosticsOutputPort
+        + ValidateFormulaOutputPort
+        + CalculateMaterialCostOutputPort
+        + CreateMonthlyInventoryReportOutputPort
+        + CreateInventoryHistoryOutputPort,
 {
     pub fn new(
         formula_repo: &'a F,
@@ -38,6 +63,7 @@ where
         freight_repo: &'a FR,
         production_repo: &'a R,
         transaction_repo: &'a T,
+        opening_balance_repo: &'a OB,
         output_port: &'a mut O,
     ) -> Self {
         Self {
@@ -46,10 +72,74 @@ where
             freight_repo,
             production_repo,
             transaction_repo,
+            opening_balance_repo,
             output_port,
+            safety_stock: SafetyStockPolicy::default(),
+            period_start: None,
+            formula_validation: FormulaValidationPolicy::default(),
+            rounding: RoundingPolicy::default(),
+            yield_policy: YieldPolicy::default(),
+            production_sheet: ProductionSheetSchema::SHEET.to_string(),
+            accumulate_errors: false,
+            costed_productions: Vec::new(),
         }
     }
 
+    /// 診断モードの設定（計算できない行を記録して処理を続ける）
+    pub fn with_error_accumulation(mut self, accumulate_errors: bool) -> Self {
+        self.accumulate_errors = accumulate_errors;
+        self
+    }
+
+    /// 配合マスタ検証の設定
+    pub fn with_formula_validation(mut self, formula_validation: FormulaValidationPolicy) -> Self {
+        self.formula_validation = formula_validation;
+        self
+    }
+
+    /// 材料費の構成要素ごとの端数処理を設定
+    pub fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// 歩留率の計算方法（乗算・除算）を設定
+    pub fn with_yield_policy(mut self, yield_policy: YieldPolicy) -> Self {
+        self.yield_policy = yield_policy;
+        self
+    }
+
+    /// シート名・列名の対応表を設定（計算エラーの行をテンプレートのシート名で示す）
+    pub fn with_schema(mut self, mapping: &SchemaMapping) -> Self {
+        self.production_sheet = mapping.sheet_name(ProductionSheetSchema::SHEET);
+        self
+    }
+
+    /// 入出庫履歴の安全在庫を設定
+    pub fn with_safety_stock(mut self, safety_stock: SafetyStockPolicy) -> Self {
+        self.safety_stock = safety_stock;
+        self
+    }
+
+    /// 期首日付を設定（入出庫履歴の繰越行の日付・月次受払表の最初の月）
+    pub fn with_period_start(mut self, period_start: Option<TransactionDate>) -> Self {
+        self.period_start = period_start;
+        self
+    }
+
+    /// 入力データ読み取り時に見つかった問題を出力
+    pub fn execute_diagnostics_report(&mut self, diagnostics: &[Diagnostic]) -> Result<()> {
+        let mut interactor = ReportDiagnosticsInteractor::new(diagnostics, self.output_port);
+        interactor.execute()
+    }
+
+    /// 配合マスタ検証を実行（材料費計算の前に実行する）
+    pub fn execute_formula_validation(&mut self) -> Result<()> {
+        let mut interactor = ValidateFormulaInteractor::new(self.formula_repo, self.output_port)
+            .with_policy(self.formula_validation.clone());
+        interactor.execute()
+    }
+
     /// 材料費計算を実行
     pub fn execute_material_cost_calculation(&mut self) -> Result<()> {
         let mut interactor = CalculateMaterialCostInteractor::new(
@@ -57,15 +147,42 @@ where
             self.purchase_repo,
             self.freight_repo,
             self.production_repo,
+            self.opening_balance_repo,
+            self.output_port,
+        )
+        .with_error_accumulation(self.accumulate_errors)
+        .with_rounding(self.rounding.clone())
+        .with_yield_policy(self.yield_policy.clone())
+        .with_production_sheet(self.production_sheet.clone());
+        interactor.execute()?;
+        self.costed_productions = interactor.into_costed_productions();
+        Ok(())
+    }
+
+    /// 月次受払表作成を実行（材料費計算の後に実行する）
+    pub fn execute_monthly_inventory_report(&mut self) -> Result<()> {
+        let mut interactor = CreateMonthlyInventoryReportInteractor::new(
+            self.transaction_repo,
+            self.opening_balance_repo,
+            self.purchase_repo,
+            &self.costed_productions,
             self.output_port,
-        );
+        )
+        .with_period_start(self.period_start.clone())
+        .with_rounding(self.rounding.clone());
         interactor.execute()
     }
 
-    /// 入出庫履歴作成を実行
+    /// 入出庫履歴作成を実行（材料費計算の後に実行する）
     pub fn execute_inventory_history_creation(&mut self) -> Result<()> {
-        let mut interactor =
-            CreateInventoryHistoryInteractor::new(self.transaction_repo, self.output_port);
+        let mut interactor = CreateInventoryHistoryInteractor::new(
+            self.transaction_repo,
+            self.opening_balance_repo,
+            &self.costed_productions,
+            self.output_port,
+        )
+        .with_safety_stock(self.safety_stock.clone())
+        .with_period_start(self.period_start.clone());
         interactor.execute()
     }
 }
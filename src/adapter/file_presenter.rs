@@ -0,0 +1,382 @@
+use crate::usecase::dtos::*;
+use crate::usecase::ports::*;
+use color_eyre::{Result, eyre};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// ファイル出力の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Csv,  // 結果ごとのCSVファイル（UTF-8）
+    Json, // 1つのJSONドキュメント
+}
+
+impl FileFormat {
+    pub fn as_str(&self) -> &str {
+        match self {
+            FileFormat::Csv => "CSV",
+            FileFormat::Json => "JSON",
+        }
+    }
+}
+
+/// 材料費計算結果（計算した行の材料消費明細を含む）
+#[derive(Debug, Clone, Serialize)]
+struct CostResultRecord {
+    #[serde(flatten)]
+    result: MaterialCostResultDto,
+    consumptions: Vec<MaterialConsumptionDto>,
+}
+
+/// JSON出力のドキュメント
+#[derive(Serialize)]
+struct ResultDocument<'a> {
+    results: &'a [CostResultRecord],
+    inventory_history: &'a [InventoryHistoryRecordDto],
+    diagnostics: &'a [DiagnosticDto],
+}
+
+/// CSV・JSONファイルプレゼンター
+///
+/// 材料費計算結果・材料消費明細・入出庫履歴・入力データの問題を集め、終了時に出力フォルダへ書き込む。
+/// 進捗の表示は行わない（Excelプレゼンターと同時に使う場合も表示が重複しない）。
+pub struct FilePresenter {
+    format: FileFormat,
+    output_dir: PathBuf,
+    pending_consumptions: Vec<MaterialConsumptionDto>,
+    results: Vec<CostResultRecord>,
+    history_records: Vec<InventoryHistoryRecordDto>,
+    diagnostics: Vec<DiagnosticDto>,
+    written: bool,
+}
+
+impl FilePresenter {
+    pub fn new(format: FileFormat, output_dir: String) -> Self {
+        Self {
+            format,
+            output_dir: PathBuf::from(output_dir),
+            pending_consumptions: Vec::new(),
+            results: Vec::new(),
+            history_records: Vec::new(),
+            diagnostics: Vec::new(),
+            written: false,
+        }
+    }
+
+    /// 出力フォルダに結果を書き込む（2回目以降は何もしない）
+    pub fn finalize(&mut self) -> Result<()> {
+        if self.written {
+            return Ok(());
+        }
+        self.written = true;
+
+        std::fs::create_dir_all(&self.output_dir).map_err(|e| {
+            eyre::eyre!(
+                "出力フォルダを作成できませんでした: {}\n原因: {}",
+                self.output_dir.display(),
+                e
+            )
+        })?;
+
+        let paths = match self.format {
+            FileFormat::Csv => self.write_csv()?,
+            FileFormat::Json => self.write_json()?,
+        };
+        for path in paths {
+            println!("  ✓ {}出力: {}", self.format.as_str(), path.display());
+        }
+        Ok(())
+    }
+
+    fn write_json(&self) -> Result<Vec<PathBuf>> {
+        let path = self.output_dir.join("材料費計算結果.json");
+        let document = ResultDocument {
+            results: &self.results,
+            inventory_history: &self.history_records,
+            diagnostics: &self.diagnostics,
+        };
+        std::fs::write(&path, serde_json::to_string_pretty(&document)?)?;
+        Ok(vec![path])
+    }
+
+    fn write_csv(&self) -> Result<Vec<PathBuf>> {
+        let results = self.output_dir.join("材料費計算結果.csv");
+        write_csv_file(
+            &results,
+            &[
+                "行",
+                "生産日",
+                "商品コード",
+                "原砂金額",
+                "原砂歩留金額",
+                "歩留率",
+                "歩留計算式",
+                "凝集剤",
+                "粘土処理",
+                "材料運賃",
+                "材料費",
+            ],
+            self.results.iter().map(|r| {
+                vec![
+                    r.result.row_number.to_string(),
+                    r.result.production_date.clone(),
+                    r.result.product_code.clone(),
+                    r.result.raw_material_cost.to_string(),
+                    r.result.yield_cost.to_string(),
+                    r.result.yield_rate.to_string(),
+                    r.result.yield_formula.clone(),
+                    r.result.coagulant_cost.to_string(),
+                    r.result.clay_treatment_cost.to_string(),
+                    r.result.freight_cost.to_string(),
+                    r.result.total_material_cost.to_string(),
+                ]
+            }),
+        )?;
+
+        let consumptions = self.output_dir.join("材料消費明細.csv");
+        write_csv_file(
+            &consumptions,
+            &[
+                "行",
+                "生産日",
+                "商品コード",
+                "材料商品コード",
+                "材料名",
+                "消費数量",
+                "単価",
+                "仕入日",
+                "金額",
+                "運賃コード",
+                "運賃Kg単価",
+                "運賃",
+                "ロット不足数量",
+            ],
+            self.results.iter().flat_map(|r| {
+                r.consumptions.iter().map(|c| {
+                    vec![
+                        r.result.row_number.to_string(),
+                        r.result.production_date.clone(),
+                        r.result.product_code.clone(),
+                        c.material_code.clone(),
+                        c.material_name.clone(),
+                        c.quantity.to_string(),
+                        c.unit_price.to_string(),
+                        c.purchase_date.clone(),
+                        c.total_cost.to_string(),
+                        c.freight_code_str.clone(),
+                        c.freight_kg_price.to_string(),
+                        c.freight_cost.to_string(),
+                        c.lot_shortage.to_string(),
+                    ]
+                })
+            }),
+        )?;
+
+        let history = self.output_dir.join("入出庫履歴.csv");
+        write_csv_file(
+            &history,
+            &[
+                "日付",
+                "在庫区分",
+                "商品コード",
+                "商品名",
+                "基準数量",
+                "増減数量",
+                "残高",
+            ],
+            self.history_records.iter().map(|h| {
+                vec![
+                    h.date.clone(),
+                    h.inventory_type.clone(),
+                    h.product_code.clone(),
+                    h.product_name.clone(),
+                    h.base_quantity.to_string(),
+                    h.change_quantity.to_string(),
+                    h.balance.to_string(),
+                ]
+            }),
+        )?;
+
+        let diagnostics = self.output_dir.join("エラー一覧.csv");
+        write_csv_file(
+            &diagnostics,
+            &["シート", "行", "列", "内容"],
+            self.diagnostics.iter().map(|d| {
+                vec![
+                    d.sheet.clone(),
+                    d.row.map(|row| row.to_string()).unwrap_or_default(),
+                    d.column.clone(),
+                    d.message.clone(),
+                ]
+            }),
+        )?;
+
+        Ok(vec![results, consumptions, history, diagnostics])
+    }
+}
+
+/// 見出し行とレコードをCSVファイルに書き込む
+fn write_csv_file(
+    path: &Path,
+    headers: &[&str],
+    records: impl Iterator<Item = Vec<String>>,
+) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(headers)?;
+    for record in records {
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+impl ReportDiagnosticsOutputPort for FilePresenter {
+    fn present_diagnostics(&mut self, diagnostics: &[DiagnosticDto]) {
+        self.diagnostics.extend_from_slice(diagnostics);
+    }
+}
+
+impl ValidateFormulaOutputPort for FilePresenter {
+    fn present_validation_start(&mut self, _total_versions: usize) {}
+
+    fn present_formula_issues(&mut self, _issues: &[FormulaIssueDto], _strict: bool) {}
+
+    fn present_validation_error(&mut self, _error: &ErrorDto) {}
+}
+
+impl CalculateMaterialCostOutputPort for FilePresenter {
+    fn present_no_data(&mut self) {}
+
+    fn present_calculation_start(&mut self, _total_rows: usize) {}
+
+    fn present_processing_row(
+        &mut self,
+        _row_number: usize,
+        _production_date: &str,
+        _product_code: &str,
+    ) {
+        // 前の行が計算できなかった場合の材料消費は捨てる
+        self.pending_consumptions.clear();
+    }
+
+    fn present_material_consumptions(&mut self, consumptions: &[MaterialConsumptionDto]) {
+        self.pending_consumptions = consumptions.to_vec();
+    }
+
+    fn present_calculation_result(&mut self, result: &MaterialCostResultDto) {
+        self.results.push(CostResultRecord {
+            result: result.clone(),
+            consumptions: std::mem::take(&mut self.pending_consumptions),
+        });
+    }
+
+    fn present_lot_balances(&mut self, _balances: &[LotBalanceDto]) {}
+
+    fn present_row_error(&mut self, diagnostic: &DiagnosticDto) {
+        self.diagnostics.push(diagnostic.clone());
+    }
+
+    fn present_completion(&mut self) {}
+
+    fn present_error(&mut self, _error: &ErrorDto) {}
+}
+
+impl CreateMonthlyInventoryReportOutputPort for FilePresenter {
+    fn present_report_start(&mut self) {}
+
+    fn present_monthly_report(&mut self, _reports: &[MonthlyInventoryReportDto]) {}
+
+    fn present_missing_prices(&mut self, _missing: &[MissingPriceDto]) {}
+
+    fn present_report_error(&mut self, _error: &ErrorDto) {}
+}
+
+impl CreateInventoryHistoryOutputPort for FilePresenter {
+    fn present_history_start(&mut self) {}
+
+    fn present_history_record(&mut self, record: &InventoryHistoryRecordDto) {
+        self.history_records.push(record.clone());
+    }
+
+    fn present_inventory_warnings(&mut self, _warnings: &[InventoryWarningDto]) {}
+
+    fn present_history_completion(&mut self, _total_records: usize) {}
+
+    fn present_history_error(&mut self, _error: &ErrorDto) {}
+
+    fn finalize(&mut self) -> Result<()> {
+        self.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(row_number: usize, product_code: &str) -> MaterialCostResultDto {
+        MaterialCostResultDto {
+            row_number,
+            production_date: "2026-01-10".to_string(),
+            product_code: product_code.to_string(),
+            raw_material_cost: 10000.0,
+            yield_cost: 9000.0,
+            yield_rate: 0.9,
+            yield_formula: "原砂金額 × 歩留率".to_string(),
+            coagulant_cost: 0.0,
+            clay_treatment_cost: 0.0,
+            freight_cost: 500.0,
+            total_material_cost: 9500.0,
+            intermediate_costs: Vec::new(),
+            explosion: Vec::new(),
+        }
+    }
+
+    fn consumption(material_code: &str) -> MaterialConsumptionDto {
+        MaterialConsumptionDto {
+            material_code: material_code.to_string(),
+            material_name: format!("材料{}", material_code),
+            quantity: 100.0,
+            unit_price: 100.0,
+            purchase_date: "2026-01-05".to_string(),
+            total_cost: 10000.0,
+            freight_cost: 500.0,
+            purchase_quantity: 500.0,
+            freight_code_str: "T01".to_string(),
+            freight_kg_price: 5.0,
+            lot_allocations: Vec::new(),
+            lot_shortage: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_consumptions_attached_to_calculated_row() {
+        let mut presenter = FilePresenter::new(FileFormat::Json, String::new());
+
+        // 行2は材料消費の後で計算できずスキップ、行3は計算済み
+        presenter.present_processing_row(2, "2026-01-10", "P001");
+        presenter.present_material_consumptions(&[consumption("M001")]);
+        presenter.present_processing_row(3, "2026-01-10", "P002");
+        presenter.present_material_consumptions(&[consumption("M002")]);
+        presenter.present_calculation_result(&result(3, "P002"));
+
+        assert_eq!(presenter.results.len(), 1);
+        assert_eq!(presenter.results[0].result.product_code, "P002");
+        assert_eq!(presenter.results[0].consumptions.len(), 1);
+        assert_eq!(presenter.results[0].consumptions[0].material_code, "M002");
+    }
+
+    #[test]
+    fn test_json_document_flattens_result() {
+        let record = CostResultRecord {
+            result: result(2, "P001"),
+            consumptions: vec![consumption("M001")],
+        };
+
+        let json = serde_json::to_value(&record).unwrap();
+
+        assert_eq!(json["product_code"], "P001");
+        assert_eq!(json["row_number"], 2);
+        assert_eq!(json["total_material_cost"], 9500.0);
+        assert_eq!(json["consumptions"][0]["material_code"], "M001");
+    }
+}
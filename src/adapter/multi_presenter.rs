@@ -0,0 +1,198 @@
+use crate::usecase::dtos::*;
+use crate::usecase::ports::*;
+use color_eyre::Result;
+
+/// すべてのアウトプットポートを実装するプレゼンター
+pub trait Presenter:
+    ReportDiagnosticsOutputPort
+    + ValidateFormulaOutputPort
+    + CalculateMaterialCostOutputPort
+    + CreateMonthlyInventoryReportOutputPort
+    + CreateInventoryHistoryOutputPort
+{
+}
+
+impl<T> Presenter for T where
+    T: ReportDiagnosticsOutputPort
+        + ValidateFormulaOutputPort
+        + CalculateMaterialCostOutputPort
+        + CreateMonthlyInventoryReportOutputPort
+        + CreateInventoryHistoryOutputPort
+{
+}
+
+/// 複数のプレゼンターに同じ結果を出力するプレゼンター（設定した出力形式の順）
+#[derive(Default)]
+pub struct MultiPresenter {
+    presenters: Vec<Box<dyn Presenter>>,
+}
+
+impl MultiPresenter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_presenter(mut self, presenter: Box<dyn Presenter>) -> Self {
+        self.presenters.push(presenter);
+        self
+    }
+
+    /// すべてのプレゼンターの結果を保存
+    pub fn finalize(&mut self) -> Result<()> {
+        for presenter in &mut self.presenters {
+            presenter.finalize()?;
+        }
+        Ok(())
+    }
+}
+
+impl ReportDiagnosticsOutputPort for MultiPresenter {
+    fn present_diagnostics(&mut self, diagnostics: &[DiagnosticDto]) {
+        for presenter in &mut self.presenters {
+            presenter.present_diagnostics(diagnostics);
+        }
+    }
+}
+
+impl ValidateFormulaOutputPort for MultiPresenter {
+    fn present_validation_start(&mut self, total_versions: usize) {
+        for presenter in &mut self.presenters {
+            presenter.present_validation_start(total_versions);
+        }
+    }
+
+    fn present_formula_issues(&mut self, issues: &[FormulaIssueDto], strict: bool) {
+        for presenter in &mut self.presenters {
+            presenter.present_formula_issues(issues, strict);
+        }
+    }
+
+    fn present_validation_error(&mut self, error: &ErrorDto) {
+        for presenter in &mut self.presenters {
+            presenter.present_validation_error(error);
+        }
+    }
+}
+
+impl CalculateMaterialCostOutputPort for MultiPresenter {
+    fn present_no_data(&mut self) {
+        for presenter in &mut self.presenters {
+            presenter.present_no_data();
+        }
+    }
+
+    fn present_calculation_start(&mut self, total_rows: usize) {
+        for presenter in &mut self.presenters {
+            presenter.present_calculation_start(total_rows);
+        }
+    }
+
+    fn present_processing_row(
+        &mut self,
+        row_number: usize,
+        production_date: &str,
+        product_code: &str,
+    ) {
+        for presenter in &mut self.presenters {
+            presenter.present_processing_row(row_number, production_date, product_code);
+        }
+    }
+
+    fn present_material_consumptions(&mut self, consumptions: &[MaterialConsumptionDto]) {
+        for presenter in &mut self.presenters {
+            presenter.present_material_consumptions(consumptions);
+        }
+    }
+
+    fn present_calculation_result(&mut self, result: &MaterialCostResultDto) {
+        for presenter in &mut self.presenters {
+            presenter.present_calculation_result(result);
+        }
+    }
+
+    fn present_lot_balances(&mut self, balances: &[LotBalanceDto]) {
+        for presenter in &mut self.presenters {
+            presenter.present_lot_balances(balances);
+        }
+    }
+
+    fn present_row_error(&mut self, diagnostic: &DiagnosticDto) {
+        for presenter in &mut self.presenters {
+            presenter.present_row_error(diagnostic);
+        }
+    }
+
+    fn present_completion(&mut self) {
+        for presenter in &mut self.presenters {
+            presenter.present_completion();
+        }
+    }
+
+    fn present_error(&mut self, error: &ErrorDto) {
+        for presenter in &mut self.presenters {
+            presenter.present_error(error);
+        }
+    }
+}
+
+impl CreateMonthlyInventoryReportOutputPort for MultiPresenter {
+    fn present_report_start(&mut self) {
+        for presenter in &mut self.presenters {
+            presenter.present_report_start();
+        }
+    }
+
+    fn present_monthly_report(&mut self, reports: &[MonthlyInventoryReportDto]) {
+        for presenter in &mut self.presenters {
+            presenter.present_monthly_report(reports);
+        }
+    }
+
+    fn present_missing_prices(&mut self, missing: &[MissingPriceDto]) {
+        for presenter in &mut self.presenters {
+            presenter.present_missing_prices(missing);
+        }
+    }
+
+    fn present_report_error(&mut self, error: &ErrorDto) {
+        for presenter in &mut self.presenters {
+            presenter.present_report_error(error);
+        }
+    }
+}
+
+impl CreateInventoryHistoryOutputPort for MultiPresenter {
+    fn present_history_start(&mut self) {
+        for presenter in &mut self.presenters {
+            presenter.present_history_start();
+        }
+    }
+
+    fn present_history_record(&mut self, record: &InventoryHistoryRecordDto) {
+        for presenter in &mut self.presenters {
+            presenter.present_history_record(record);
+        }
+    }
+
+    fn present_inventory_warnings(&mut self, warnings: &[InventoryWarningDto]) {
+        for presenter in &mut self.presenters {
+            presenter.present_inventory_warnings(warnings);
+        }
+    }
+
+    fn present_history_completion(&mut self, total_records: usize) {
+        for presenter in &mut self.presenters {
+            presenter.present_history_completion(total_records);
+        }
+    }
+
+    fn present_history_error(&mut self, error: &ErrorDto) {
+        for presenter in &mut self.presenters {
+            presenter.present_history_error(error);
+        }
+    }
+
+    fn finalize(&mut self) -> Result<()> {
+        self.finalize()
+    }
+}
@@ -1,41 +1,49 @@
+use crate::domain::sheet_schema::{
+    ColumnIndex, ProductionOutputSchema, ProductionSheetSchema, SchemaMapping,
+};
 use crate::usecase::dtos::*;
 use crate::usecase::ports::*;
 use calamine::{Reader, Xlsx, open_workbook};
 use color_eyre::Result;
-use rust_xlsxwriter::Workbook;
+use rust_xlsxwriter::{Workbook, Worksheet};
 
 /// Excelプレゼンター
 pub struct ExcelPresenter {
     input_file_path: String,
     output_file_path: String,
+    mapping: SchemaMapping,
     workbook: Option<Workbook>,
     results: Vec<MaterialCostResultDto>,
     history_records: Vec<InventoryHistoryRecordDto>,
+    lot_balances: Vec<LotBalanceDto>,
+    inventory_warnings: Vec<InventoryWarningDto>,
+    monthly_reports: Vec<MonthlyInventoryReportDto>,
+    diagnostics: Vec<DiagnosticDto>,
     logs: Vec<String>,
-    // 【入庫】生産シートの列インデックス
-    production_col_raw_material_cost: Option<usize>,
-    production_col_yield_cost: Option<usize>,
-    production_col_coagulant_cost: Option<usize>,
-    production_col_clay_treatment_cost: Option<usize>,
-    production_col_freight_cost: Option<usize>,
-    production_col_total_material_cost: Option<usize>,
+    // 【入庫】生産シートの書き込み列（シートがない場合はNone）
+    production_columns: Option<ProductionOutputSchema>,
 }
 
 impl ExcelPresenter {
-    pub fn new(input_file_path: String, output_file_path: String) -> Result<Self> {
+    /// シート名・列名は `mapping` の対応表で読み替える
+    pub fn new(
+        input_file_path: String,
+        output_file_path: String,
+        mapping: SchemaMapping,
+    ) -> Result<Self> {
         let mut presenter = Self {
             input_file_path: input_file_path.clone(),
             output_file_path,
+            mapping,
             workbook: None,
             results: Vec::new(),
             history_records: Vec::new(),
+            lot_balances: Vec::new(),
+            inventory_warnings: Vec::new(),
+            monthly_reports: Vec::new(),
+            diagnostics: Vec::new(),
             logs: Vec::new(),
-            production_col_raw_material_cost: None,
-            production_col_yield_cost: None,
-            production_col_coagulant_cost: None,
-            production_col_clay_treatment_cost: None,
-            production_col_freight_cost: None,
-            production_col_total_material_cost: None,
+            production_columns: None,
         };
 
         // Excelファイルを準備
@@ -123,44 +131,29 @@ impl ExcelPresenter {
         let mut source_workbook: Xlsx<_> = open_workbook(&self.input_file_path)?;
 
         // 【入庫】生産シートのヘッダー行を読み込む
-        let sheet_name = "【入庫】生産";
-        if let Ok(range) = source_workbook.worksheet_range(sheet_name)
-            && let Some(header_row) = range.rows().next() {
-                // 各列のインデックスを取得
-                self.production_col_raw_material_cost = header_row
-                    .iter()
-                    .position(|cell| cell.to_string().trim() == "原砂金額");
-
-                self.production_col_yield_cost = header_row
-                    .iter()
-                    .position(|cell| cell.to_string().trim() == "原砂歩留金額");
-
-                self.production_col_coagulant_cost = header_row
-                    .iter()
-                    .position(|cell| cell.to_string().trim() == "凝集剤");
-
-                self.production_col_clay_treatment_cost = header_row
-                    .iter()
-                    .position(|cell| cell.to_string().trim() == "粘土処理");
-
-                self.production_col_freight_cost = header_row
-                    .iter()
-                    .position(|cell| cell.to_string().trim() == "材料運賃");
-
-                self.production_col_total_material_cost = header_row
-                    .iter()
-                    .position(|cell| cell.to_string().trim() == "材料費");
+        let sheet_name = self.mapping.sheet_name(ProductionSheetSchema::SHEET);
+        if let Ok(range) = source_workbook.worksheet_range(&sheet_name)
+            && let Some(header_row) = range.rows().next()
+        {
+            let headers: Vec<String> = header_row
+                .iter()
+                .map(|cell| cell.to_string().trim().to_string())
+                .collect();
+            let columns = ProductionOutputSchema::from_headers(&headers, &self.mapping);
+            let index = |col: Option<ColumnIndex>| col.map(|c| c.value());
 
-                self.log(format!(
-                    "  ✓ 列インデックス取得: 原砂金額={:?}, 原砂歩留金額={:?}, 凝集剤={:?}, 粘土処理={:?}, 材料運賃={:?}, 材料費={:?}",
-                    self.production_col_raw_material_cost,
-                    self.production_col_yield_cost,
-                    self.production_col_coagulant_cost,
-                    self.production_col_clay_treatment_cost,
-                    self.production_col_freight_cost,
-                    self.production_col_total_material_cost
+            self.log(format!(
+                    "  ✓ 列インデックス取得: 原砂金額={:?}, 原砂歩留金額={:?}, 凝集剤={:?}, 粘土処理={:?}, 材料運賃={:?}, 材料費={:?}, 歩留計算式={:?}",
+                    index(columns.raw_material_cost()),
+                    index(columns.yield_cost()),
+                    index(columns.coagulant()),
+                    index(columns.clay_treatment()),
+                    index(columns.freight()),
+                    index(columns.total_material_cost()),
+                    index(columns.yield_formula())
                 ));
-            }
+            self.production_columns = Some(columns);
+        }
 
         Ok(())
     }
@@ -175,6 +168,37 @@ impl ExcelPresenter {
         self.logs.push(message);
     }
 
+    /// 部品展開ツリーを階層ごとに字下げしてログ出力
+    fn log_bom_node(&mut self, node: &BomNodeDto, level: usize) {
+        let kind = if node.children.is_empty() {
+            "購入材料"
+        } else {
+            "中間品"
+        };
+        self.log(format!(
+            "    {}└ [{}] {} ({}): {:.2} kg, 材料費 {:.2} 円, 運賃 {:.2} 円",
+            "  ".repeat(level),
+            kind,
+            node.material_name,
+            node.material_code,
+            node.quantity,
+            node.material_cost,
+            node.freight_cost
+        ));
+        for child in &node.children {
+            self.log_bom_node(child, level + 1);
+        }
+    }
+
+    /// シートを取得（テンプレートにない場合は末尾に追加）
+    fn sheet_or_new<'w>(workbook: &'w mut Workbook, sheet_name: &str) -> Result<&'w mut Worksheet> {
+        if workbook.worksheet_from_name(sheet_name).is_err() {
+            let worksheet = workbook.add_worksheet();
+            worksheet.set_name(sheet_name)?;
+        }
+        Ok(workbook.worksheet_from_name(sheet_name)?)
+    }
+
     /// Excelファイルに結果を書き込んで保存
     pub fn finalize(&mut self) -> Result<()> {
         let Some(mut workbook) = self.workbook.take() else {
@@ -184,29 +208,35 @@ impl ExcelPresenter {
         self.log("\nExcelファイルに結果を書き込み中...".to_string());
 
         // 【入庫】生産シートに結果を書き込み
-        if !self.results.is_empty() {
-            let sheet = workbook.worksheet_from_name("【入庫】生産")?;
+        if !self.results.is_empty()
+            && let Some(columns) = &self.production_columns
+        {
+            let sheet = workbook
+                .worksheet_from_name(&self.mapping.sheet_name(ProductionSheetSchema::SHEET))?;
 
             for result in &self.results {
                 let row = (result.row_number - 1) as u32;
-                // 四捨五入して整数に変換
-                if let Some(col) = self.production_col_raw_material_cost {
-                    sheet.write_number(row, col as u16, result.raw_material_cost.round())?;
+                // 金額は材料費計算で端数処理済みのため、そのまま書き込む
+                if let Some(col) = columns.raw_material_cost() {
+                    sheet.write_number(row, col.value() as u16, result.raw_material_cost)?;
+                }
+                if let Some(col) = columns.yield_cost() {
+                    sheet.write_number(row, col.value() as u16, result.yield_cost)?;
                 }
-                if let Some(col) = self.production_col_yield_cost {
-                    sheet.write_number(row, col as u16, result.yield_cost.round())?;
+                if let Some(col) = columns.coagulant() {
+                    sheet.write_number(row, col.value() as u16, result.coagulant_cost)?;
                 }
-                if let Some(col) = self.production_col_coagulant_cost {
-                    sheet.write_number(row, col as u16, result.coagulant_cost.round())?;
+                if let Some(col) = columns.clay_treatment() {
+                    sheet.write_number(row, col.value() as u16, result.clay_treatment_cost)?;
                 }
-                if let Some(col) = self.production_col_clay_treatment_cost {
-                    sheet.write_number(row, col as u16, result.clay_treatment_cost.round())?;
+                if let Some(col) = columns.freight() {
+                    sheet.write_number(row, col.value() as u16, result.freight_cost)?;
                 }
-                if let Some(col) = self.production_col_freight_cost {
-                    sheet.write_number(row, col as u16, result.freight_cost.round())?;
+                if let Some(col) = columns.total_material_cost() {
+                    sheet.write_number(row, col.value() as u16, result.total_material_cost)?;
                 }
-                if let Some(col) = self.production_col_total_material_cost {
-                    sheet.write_number(row, col as u16, result.total_material_cost.round())?;
+                if let Some(col) = columns.yield_formula() {
+                    sheet.write_string(row, col.value() as u16, &result.yield_formula)?;
                 }
             }
 
@@ -216,7 +246,8 @@ impl ExcelPresenter {
         // 入出庫履歴シートに書き込み
         if !self.history_records.is_empty() {
             self.log("\n入出庫履歴シートに書き込み中...".to_string());
-            let history_sheet = workbook.worksheet_from_name("【集計】入出庫履歴")?;
+            let history_sheet =
+                workbook.worksheet_from_name(&self.mapping.sheet_name("【集計】入出庫履歴"))?;
 
             // 日付フォーマットを作成
             let date_format = rust_xlsxwriter::Format::new().set_num_format("yyyy-mm-dd");
@@ -236,9 +267,157 @@ impl ExcelPresenter {
             self.log("  ✓ 入出庫履歴の書き込み完了".to_string());
         }
 
+        // ロット残高シートに書き込み
+        if !self.lot_balances.is_empty() {
+            self.log("\nロット残高シートに書き込み中...".to_string());
+            let lot_sheet = Self::sheet_or_new(
+                &mut workbook,
+                &self.mapping.sheet_name("【集計】ロット残高"),
+            )?;
+
+            let headers = [
+                "商品コード",
+                "商品名",
+                "仕入日",
+                "仕入単価",
+                "仕入数量",
+                "残数量",
+                "残高金額",
+            ];
+            for (col, header) in headers.iter().enumerate() {
+                lot_sheet.write_string(0, col as u16, *header)?;
+            }
+
+            for (idx, balance) in self.lot_balances.iter().enumerate() {
+                let row = (idx + 1) as u32;
+                lot_sheet.write_string(row, 0, &balance.product_code)?;
+                lot_sheet.write_string(row, 1, &balance.product_name)?;
+                lot_sheet.write_string(row, 2, &balance.purchase_date)?;
+                lot_sheet.write_number(row, 3, balance.unit_price)?;
+                lot_sheet.write_number(row, 4, balance.purchased_quantity)?;
+                lot_sheet.write_number(row, 5, balance.remaining_quantity)?;
+                lot_sheet.write_number(row, 6, balance.remaining_amount)?;
+            }
+
+            self.log("  ✓ ロット残高の書き込み完了".to_string());
+        }
+
+        // 月次受払表シートに書き込み
+        if !self.monthly_reports.is_empty() {
+            self.log("\n月次受払表シートに書き込み中...".to_string());
+            let report_sheet = Self::sheet_or_new(
+                &mut workbook,
+                &self.mapping.sheet_name("【集計】月次受払表"),
+            )?;
+
+            let headers = [
+                "年月",
+                "商品コード",
+                "商品名",
+                "単価",
+                "期首数量",
+                "期首金額",
+                "生産受入数量",
+                "仕入受入数量",
+                "受入金額",
+                "売上払出数量",
+                "材料出庫数量",
+                "払出金額",
+                "期末数量",
+                "期末金額",
+            ];
+            for (col, header) in headers.iter().enumerate() {
+                report_sheet.write_string(0, col as u16, *header)?;
+            }
+
+            for (idx, report) in self.monthly_reports.iter().enumerate() {
+                let row = (idx + 1) as u32;
+                report_sheet.write_string(row, 0, &report.month)?;
+                report_sheet.write_string(row, 1, &report.product_code)?;
+                report_sheet.write_string(row, 2, &report.product_name)?;
+                match report.unit_price {
+                    Some(price) => report_sheet.write_number(row, 3, price)?,
+                    None => report_sheet.write_string(row, 3, "単価なし")?,
+                };
+                report_sheet.write_number(row, 4, report.opening_quantity)?;
+                report_sheet.write_number(row, 5, report.opening_amount)?;
+                report_sheet.write_number(row, 6, report.production_quantity)?;
+                report_sheet.write_number(row, 7, report.purchase_quantity)?;
+                report_sheet.write_number(row, 8, report.receipt_amount)?;
+                report_sheet.write_number(row, 9, report.sales_quantity)?;
+                report_sheet.write_number(row, 10, report.material_issue_quantity)?;
+                report_sheet.write_number(row, 11, report.issue_amount)?;
+                report_sheet.write_number(row, 12, report.closing_quantity)?;
+                report_sheet.write_number(row, 13, report.closing_amount)?;
+            }
+
+            self.log("  ✓ 月次受払表の書き込み完了".to_string());
+        }
+
+        // 在庫警告シートに書き込み
+        if !self.inventory_warnings.is_empty() {
+            self.log("\n在庫警告シートに書き込み中...".to_string());
+            let warning_sheet =
+                Self::sheet_or_new(&mut workbook, &self.mapping.sheet_name("【集計】在庫警告"))?;
+
+            let headers = [
+                "警告",
+                "日付",
+                "在庫区分",
+                "商品コード",
+                "商品名",
+                "変化数量",
+                "残高",
+                "しきい値",
+            ];
+            for (col, header) in headers.iter().enumerate() {
+                warning_sheet.write_string(0, col as u16, *header)?;
+            }
+
+            for (idx, warning) in self.inventory_warnings.iter().enumerate() {
+                let row = (idx + 1) as u32;
+                warning_sheet.write_string(row, 0, &warning.kind)?;
+                warning_sheet.write_string(row, 1, &warning.date)?;
+                warning_sheet.write_string(row, 2, &warning.inventory_type)?;
+                warning_sheet.write_string(row, 3, &warning.product_code)?;
+                warning_sheet.write_string(row, 4, &warning.product_name)?;
+                warning_sheet.write_number(row, 5, warning.change_quantity)?;
+                warning_sheet.write_number(row, 6, warning.balance)?;
+                warning_sheet.write_number(row, 7, warning.threshold)?;
+            }
+
+            self.log("  ✓ 在庫警告の書き込み完了".to_string());
+        }
+
+        // 入力データの問題をエラー一覧シートに書き込み（syslogの隣）
+        if !self.diagnostics.is_empty() {
+            self.log("\nエラー一覧シートに書き込み中...".to_string());
+            let diagnostic_sheet = Self::sheet_or_new(
+                &mut workbook,
+                &self.mapping.sheet_name("【診断】エラー一覧"),
+            )?;
+
+            let headers = ["シート", "行", "列", "内容"];
+            for (col, header) in headers.iter().enumerate() {
+                diagnostic_sheet.write_string(0, col as u16, *header)?;
+            }
+
+            for (idx, diagnostic) in self.diagnostics.iter().enumerate() {
+                let row = (idx + 1) as u32;
+                diagnostic_sheet.write_string(row, 0, &diagnostic.sheet)?;
+                if let Some(source_row) = diagnostic.row {
+                    diagnostic_sheet.write_number(row, 1, source_row as f64)?;
+                }
+                diagnostic_sheet.write_string(row, 2, &diagnostic.column)?;
+                diagnostic_sheet.write_string(row, 3, &diagnostic.message)?;
+            }
+
+            self.log("  ✓ エラー一覧の書き込み完了".to_string());
+        }
+
         // syslogシートを作成してログを書き込み
         let syslog_sheet = workbook.add_worksheet();
-        syslog_sheet.set_name("syslog")?;
+        syslog_sheet.set_name(self.mapping.sheet_name("syslog"))?;
 
         for (idx, log_message) in self.logs.iter().enumerate() {
             syslog_sheet.write_string(idx as u32, 0, log_message)?;
@@ -253,6 +432,55 @@ impl ExcelPresenter {
     }
 }
 
+impl ReportDiagnosticsOutputPort for ExcelPresenter {
+    fn present_diagnostics(&mut self, diagnostics: &[DiagnosticDto]) {
+        if diagnostics.is_empty() {
+            return;
+        }
+
+        self.log(format!(
+            "\n⚠️  入力データの問題: {} 件（該当行をスキップしました）",
+            diagnostics.len()
+        ));
+        for diagnostic in diagnostics {
+            self.log(format!("    {}", diagnostic_label(diagnostic)));
+        }
+        self.diagnostics.extend_from_slice(diagnostics);
+    }
+}
+
+impl ValidateFormulaOutputPort for ExcelPresenter {
+    fn present_validation_start(&mut self, total_versions: usize) {
+        self.log("\n🔧 配合マスタの検証を開始...".to_string());
+        self.log(format!("  ✓ 検証対象: {} 版", total_versions));
+    }
+
+    fn present_formula_issues(&mut self, issues: &[FormulaIssueDto], strict: bool) {
+        if issues.is_empty() {
+            self.log("✅ 配合マスタに問題は見つかりませんでした".to_string());
+            return;
+        }
+
+        let mark = if strict { "❌" } else { "⚠️ " };
+        self.log(format!("  {} 配合マスタの問題: {} 件", mark, issues.len()));
+        for issue in issues {
+            let material = if issue.material_code.is_empty() {
+                String::new()
+            } else {
+                format!(" 材料 {}", issue.material_code)
+            };
+            self.log(format!(
+                "    [{}] 製造商品コード {} (有効期間 {}){}: {}",
+                issue.kind, issue.product_code, issue.period, material, issue.message
+            ));
+        }
+    }
+
+    fn present_validation_error(&mut self, error: &ErrorDto) {
+        self.log_error(format!("\n❌ 配合マスタ検証エラー: {}", error_label(error)));
+    }
+}
+
 impl CalculateMaterialCostOutputPort for ExcelPresenter {
     fn present_no_data(&mut self) {
         self.log("  ℹ️  【入庫】生産シートにデータがありません（ヘッダーのみ）".to_string());
@@ -263,10 +491,15 @@ impl CalculateMaterialCostOutputPort for ExcelPresenter {
         self.log(format!("  ✓ データ行数: {} 行", total_rows));
     }
 
-    fn present_processing_row(&mut self, row_number: usize, product_code: &str) {
+    fn present_processing_row(
+        &mut self,
+        row_number: usize,
+        production_date: &str,
+        product_code: &str,
+    ) {
         self.log(format!(
-            "\n  処理中: 行{} - 商品コード: {}",
-            row_number, product_code
+            "\n  処理中: 行{} - 生産日: {} - 商品コード: {}",
+            row_number, production_date, product_code
         ));
     }
 
@@ -278,8 +511,8 @@ impl CalculateMaterialCostOutputPort for ExcelPresenter {
                 consumption.material_name, consumption.material_code, consumption.quantity
             ));
             self.log(format!(
-                "        単価: {:.2} 円 → 金額: {:.2} 円",
-                consumption.unit_price, consumption.total_cost
+                "        単価: {:.2} 円 (仕入日: {}) → 金額: {:.2} 円",
+                consumption.unit_price, consumption.purchase_date, consumption.total_cost
             ));
             self.log(format!(
                 "        仕入数量: {:.2} kg, 運賃コード: {}, 運賃Kg単価: {:.2} 円/kg",
@@ -287,19 +520,81 @@ impl CalculateMaterialCostOutputPort for ExcelPresenter {
                 consumption.freight_code_str,
                 consumption.freight_kg_price
             ));
-            self.log(format!(
-                "        実質運賃（按分後）: {:.2} 円 (= {:.2} × {:.2})",
-                consumption.freight_cost, consumption.freight_kg_price, consumption.quantity
-            ));
+            // ロット運賃のロットを引き当てた場合は、ロットごとのロット運賃で計算し直している
+            let lot_freight: Option<f64> = consumption
+                .lot_allocations
+                .iter()
+                .filter_map(|a| a.freight)
+                .reduce(|acc, f| acc + f);
+            match lot_freight {
+                Some(lot_freight) => self.log(format!(
+                    "        実質運賃（按分後）: {:.2} 円 (= ロット運賃 {:.2} + その他 {:.2})",
+                    consumption.freight_cost,
+                    lot_freight,
+                    consumption.freight_cost - lot_freight
+                )),
+                None => self.log(format!(
+                    "        実質運賃（按分後）: {:.2} 円 (= {:.2} × {:.2})",
+                    consumption.freight_cost, consumption.freight_kg_price, consumption.quantity
+                )),
+            }
+            for allocation in &consumption.lot_allocations {
+                let freight = allocation
+                    .freight
+                    .map(|f| format!(", ロット運賃 {:.2} 円", f))
+                    .unwrap_or_default();
+                self.log(format!(
+                    "        ロット引当: {} 仕入分 {:.2} kg × {:.2} 円 = {:.2} 円{}",
+                    allocation.purchase_date,
+                    allocation.quantity,
+                    allocation.unit_price,
+                    allocation.amount,
+                    freight
+                ));
+            }
+            if consumption.lot_shortage > 0.0 {
+                self.log(format!(
+                    "        ⚠️  ロット不足: {:.2} kg（生産日以前の仕入ロットが不足しています）",
+                    consumption.lot_shortage
+                ));
+            }
         }
     }
 
     fn present_calculation_result(&mut self, result: &MaterialCostResultDto) {
+        if !result.intermediate_costs.is_empty() {
+            self.log(format!(
+                "    部品展開（中間品 {} 件）:",
+                result.intermediate_costs.len()
+            ));
+            for node in &result.explosion {
+                self.log_bom_node(node, 1);
+            }
+            for intermediate in &result.intermediate_costs {
+                let unit_cost = if intermediate.quantity > 0.0 {
+                    intermediate.material_cost / intermediate.quantity
+                } else {
+                    0.0
+                };
+                self.log(format!(
+                    "    中間品原価: {} (レベル{}) {:.2} kg, 材料費 {:.2} 円 ({:.2} 円/kg), 運賃 {:.2} 円",
+                    intermediate.material_code,
+                    intermediate.level,
+                    intermediate.quantity,
+                    intermediate.material_cost,
+                    unit_cost,
+                    intermediate.freight_cost
+                ));
+            }
+        }
+        self.log(format!(
+            "    原砂金額合計: {:.2} 円 ({} 時点の単価)",
+            result.raw_material_cost, result.production_date
+        ));
         self.log(format!(
-            "    原砂金額合計: {:.2} 円",
-            result.raw_material_cost
+            "    原砂歩留金額: {:.2} 円 ({}: 歩留率 {})",
+            result.yield_cost, result.yield_formula, result.yield_rate
         ));
-        self.log(format!("    原砂歩留金額: {:.2} 円", result.yield_cost));
         self.log(format!("    凝集剤: {:.2} 円", result.coagulant_cost));
         self.log(format!(
             "    粘土処理: {:.2} 円",
@@ -315,12 +610,64 @@ impl CalculateMaterialCostOutputPort for ExcelPresenter {
         self.results.push(result.clone());
     }
 
+    fn present_lot_balances(&mut self, balances: &[LotBalanceDto]) {
+        self.log(format!("\n  期末ロット残高: {} ロット", balances.len()));
+        self.lot_balances = balances.to_vec();
+    }
+
+    fn present_row_error(&mut self, diagnostic: &DiagnosticDto) {
+        self.log(format!(
+            "  ⚠️  計算できないためスキップ: {}",
+            diagnostic_label(diagnostic)
+        ));
+        self.diagnostics.push(diagnostic.clone());
+    }
+
     fn present_completion(&mut self) {
         self.log("\n✅ 【入庫】生産シートの処理が完了しました".to_string());
     }
 
-    fn present_error(&mut self, message: &str) {
-        self.log_error(format!("\n❌ エラー: {}", message));
+    fn present_error(&mut self, error: &ErrorDto) {
+        self.log_error(format!("\n❌ エラー: {}", error_label(error)));
+    }
+}
+
+impl CreateMonthlyInventoryReportOutputPort for ExcelPresenter {
+    fn present_report_start(&mut self) {
+        self.log("\n🔧 月次受払表の作成を開始...".to_string());
+    }
+
+    fn present_monthly_report(&mut self, reports: &[MonthlyInventoryReportDto]) {
+        let mut months: Vec<&str> = reports.iter().map(|r| r.month.as_str()).collect();
+        months.dedup();
+        self.log(format!(
+            "  ✓ 対象月: {} ヶ月, 行数: {} 行",
+            months.len(),
+            reports.len()
+        ));
+        self.log("✅ 月次受払表の作成が完了しました".to_string());
+        self.monthly_reports = reports.to_vec();
+    }
+
+    fn present_missing_prices(&mut self, missing: &[MissingPriceDto]) {
+        if missing.is_empty() {
+            return;
+        }
+
+        self.log(format!(
+            "  ⚠️  単価なし: {} 件（金額0で評価）",
+            missing.len()
+        ));
+        for m in missing {
+            self.log(format!(
+                "    {} {} ({}): {:.2} kg",
+                m.month, m.product_name, m.product_code, m.quantity
+            ));
+        }
+    }
+
+    fn present_report_error(&mut self, error: &ErrorDto) {
+        self.log_error(format!("\n❌ 月次受払表エラー: {}", error_label(error)));
     }
 }
 
@@ -333,16 +680,74 @@ impl CreateInventoryHistoryOutputPort for ExcelPresenter {
         self.history_records.push(record.clone());
     }
 
+    fn present_inventory_warnings(&mut self, warnings: &[InventoryWarningDto]) {
+        if warnings.is_empty() {
+            self.log("  ✓ 在庫警告: なし".to_string());
+            return;
+        }
+
+        self.log(format!("  ⚠️  在庫警告: {} 件", warnings.len()));
+        for warning in warnings {
+            self.log(format!(
+                "    [{}] {} {} ({}) {} {:.2} → 残高 {:.2} (しきい値 {:.2})",
+                warning.kind,
+                warning.date,
+                warning.product_name,
+                warning.product_code,
+                warning.inventory_type,
+                warning.change_quantity,
+                warning.balance,
+                warning.threshold
+            ));
+        }
+        self.inventory_warnings = warnings.to_vec();
+    }
+
     fn present_history_completion(&mut self, total_records: usize) {
         self.log(format!("  ✓ 入出庫履歴レコード数: {} 件", total_records));
         self.log("✅ 入出庫履歴の作成が完了しました".to_string());
     }
 
-    fn present_history_error(&mut self, message: &str) {
-        self.log_error(format!("\n❌ 入出庫履歴エラー: {}", message));
+    fn present_history_error(&mut self, error: &ErrorDto) {
+        self.log_error(format!("\n❌ 入出庫履歴エラー: {}", error_label(error)));
     }
 
     fn finalize(&mut self) -> Result<()> {
         self.finalize()
     }
 }
+
+/// エラーの表示用文字列（分類・内容・位置・コード）
+fn error_label(error: &ErrorDto) -> String {
+    let mut location = Vec::new();
+    if !error.sheet.is_empty() {
+        location.push(format!("{}シート", error.sheet));
+    }
+    if let Some(row) = error.row {
+        location.push(format!("{}行目", row));
+    }
+    if !error.column.is_empty() {
+        location.push(format!("列: {}", error.column));
+    }
+    if !error.code.is_empty() {
+        location.push(format!("コード: {}", error.code));
+    }
+
+    let mut label = format!("[{}] {}", error.category, error.message);
+    if !location.is_empty() {
+        label.push_str(&format!("\n  位置: {}", location.join(" ")));
+    }
+    label
+}
+
+/// 入力データの問題の表示用文字列
+fn diagnostic_label(diagnostic: &DiagnosticDto) -> String {
+    let mut label = format!("{}シート", diagnostic.sheet);
+    if let Some(row) = diagnostic.row {
+        label.push_str(&format!(" {}行目", row));
+    }
+    if !diagnostic.column.is_empty() {
+        label.push_str(&format!(" [{}]", diagnostic.column));
+    }
+    format!("{}: {}", label, diagnostic.message)
+}
@@ -0,0 +1,110 @@
+use crate::usecase::dtos::*;
+use crate::usecase::ports::*;
+use color_eyre::Result;
+
+/// 実行結果保存プレゼンター
+///
+/// 材料費計算結果と入出庫履歴を集め、終了時に実行結果保存アウトプットポートへ1回の実行として保存する。
+/// 進捗の表示は行わない。
+pub struct RunStorePresenter<S> {
+    store: S,
+    results: Vec<MaterialCostResultDto>,
+    history_records: Vec<InventoryHistoryRecordDto>,
+    saved: bool,
+}
+
+impl<S: SaveRunResultsOutputPort> RunStorePresenter<S> {
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            results: Vec::new(),
+            history_records: Vec::new(),
+            saved: false,
+        }
+    }
+
+    /// 実行結果を保存する（2回目以降は何もしない）
+    pub fn finalize(&mut self) -> Result<()> {
+        if self.saved {
+            return Ok(());
+        }
+        self.saved = true;
+
+        let run_id = self.store.save_run(&self.results, &self.history_records)?;
+        println!(
+            "  ✓ 実行結果を保存: 実行ID {}（材料費計算結果 {} 件、入出庫履歴 {} 件）",
+            run_id,
+            self.results.len(),
+            self.history_records.len()
+        );
+        Ok(())
+    }
+}
+
+impl<S> ReportDiagnosticsOutputPort for RunStorePresenter<S> {
+    fn present_diagnostics(&mut self, _diagnostics: &[DiagnosticDto]) {}
+}
+
+impl<S> ValidateFormulaOutputPort for RunStorePresenter<S> {
+    fn present_validation_start(&mut self, _total_versions: usize) {}
+
+    fn present_formula_issues(&mut self, _issues: &[FormulaIssueDto], _strict: bool) {}
+
+    fn present_validation_error(&mut self, _error: &ErrorDto) {}
+}
+
+impl<S> CalculateMaterialCostOutputPort for RunStorePresenter<S> {
+    fn present_no_data(&mut self) {}
+
+    fn present_calculation_start(&mut self, _total_rows: usize) {}
+
+    fn present_processing_row(
+        &mut self,
+        _row_number: usize,
+        _production_date: &str,
+        _product_code: &str,
+    ) {
+    }
+
+    fn present_material_consumptions(&mut self, _consumptions: &[MaterialConsumptionDto]) {}
+
+    fn present_calculation_result(&mut self, result: &MaterialCostResultDto) {
+        self.results.push(result.clone());
+    }
+
+    fn present_lot_balances(&mut self, _balances: &[LotBalanceDto]) {}
+
+    fn present_row_error(&mut self, _diagnostic: &DiagnosticDto) {}
+
+    fn present_completion(&mut self) {}
+
+    fn present_error(&mut self, _error: &ErrorDto) {}
+}
+
+impl<S> CreateMonthlyInventoryReportOutputPort for RunStorePresenter<S> {
+    fn present_report_start(&mut self) {}
+
+    fn present_monthly_report(&mut self, _reports: &[MonthlyInventoryReportDto]) {}
+
+    fn present_missing_prices(&mut self, _missing: &[MissingPriceDto]) {}
+
+    fn present_report_error(&mut self, _error: &ErrorDto) {}
+}
+
+impl<S: SaveRunResultsOutputPort> CreateInventoryHistoryOutputPort for RunStorePresenter<S> {
+    fn present_history_start(&mut self) {}
+
+    fn present_history_record(&mut self, record: &InventoryHistoryRecordDto) {
+        self.history_records.push(record.clone());
+    }
+
+    fn present_inventory_warnings(&mut self, _warnings: &[InventoryWarningDto]) {}
+
+    fn present_history_completion(&mut self, _total_records: usize) {}
+
+    fn present_history_error(&mut self, _error: &ErrorDto) {}
+
+    fn finalize(&mut self) -> Result<()> {
+        self.finalize()
+    }
+}
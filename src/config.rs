@@ -1,10 +1,34 @@
+use crate::domain::formula_validation::FormulaValidationPolicy;
 use color_eyre::{Result, eyre};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub paths: Paths,
+    #[serde(default)]
+    pub input: Input,
+    #[serde(default)]
+    pub output: Output,
+    #[serde(default)]
+    pub database: Database,
+    #[serde(default)]
+    pub pricing: Pricing,
+    #[serde(default)]
+    pub inventory: Inventory,
+    #[serde(default)]
+    pub formula_validation: FormulaValidation,
+    #[serde(default)]
+    pub diagnostics: Diagnostics,
+    #[serde(default)]
+    pub rounding: Rounding,
+    #[serde(default)]
+    pub yield_rate: Yield,
+    #[serde(default)]
+    pub freight: Freight,
+    #[serde(default)]
+    pub schema: Schema,
 }
 
 #[derive(Debug, Deserialize)]
@@ -13,6 +37,247 @@ pub struct Paths {
     pub output_file: String,
 }
 
+/// 入力データの形式（excel: paths.input_file のブック / csv: csv_dir のCSVファイル / sqlite: database.path）
+#[derive(Debug, Deserialize)]
+pub struct Input {
+    #[serde(default = "default_input_format")]
+    pub format: String,
+    /// CSVファイルのフォルダ（シートごとに `<シート名>.csv`）
+    #[serde(default = "default_csv_dir")]
+    pub csv_dir: String,
+    /// CSVファイルの文字コード（auto / utf-8 / shift_jis）
+    #[serde(default = "default_csv_encoding")]
+    pub encoding: String,
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self {
+            format: default_input_format(),
+            csv_dir: default_csv_dir(),
+            encoding: default_csv_encoding(),
+        }
+    }
+}
+
+fn default_input_format() -> String {
+    "excel".to_string()
+}
+
+fn default_csv_dir() -> String {
+    "csv".to_string()
+}
+
+fn default_csv_encoding() -> String {
+    "auto".to_string()
+}
+
+/// 結果の出力形式（excel / csv / json / sqlite、複数指定すると同時に出力）
+#[derive(Debug, Deserialize)]
+pub struct Output {
+    #[serde(default = "default_output_formats")]
+    pub formats: Vec<String>,
+    /// CSV・JSONファイルの出力先フォルダ
+    #[serde(default = "default_output_dir")]
+    pub dir: String,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self {
+            formats: default_output_formats(),
+            dir: default_output_dir(),
+        }
+    }
+}
+
+fn default_output_formats() -> Vec<String> {
+    vec!["excel".to_string()]
+}
+
+fn default_output_dir() -> String {
+    "output".to_string()
+}
+
+/// SQLiteデータベース（入力データの取り込み先・実行結果の保存先）
+#[derive(Debug, Deserialize)]
+pub struct Database {
+    #[serde(default = "default_database_path")]
+    pub path: String,
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self {
+            path: default_database_path(),
+        }
+    }
+}
+
+fn default_database_path() -> String {
+    "material_cost.db".to_string()
+}
+
+/// 材料単価の計算方法（latest / moving_average / periodic_average）
+#[derive(Debug, Deserialize)]
+pub struct Pricing {
+    #[serde(default = "default_pricing_method")]
+    pub method: String,
+}
+
+impl Default for Pricing {
+    fn default() -> Self {
+        Self {
+            method: default_pricing_method(),
+        }
+    }
+}
+
+fn default_pricing_method() -> String {
+    "latest".to_string()
+}
+
+/// 入出庫履歴の設定（在庫警告・期首日付）
+#[derive(Debug, Default, Deserialize)]
+pub struct Inventory {
+    /// 全商品の安全在庫（既定値 0 = マイナス在庫のみ警告）
+    #[serde(default)]
+    pub safety_stock: f64,
+    /// 商品コード別の安全在庫
+    #[serde(default)]
+    pub safety_stock_by_product: HashMap<String, f64>,
+    /// 期首日付（繰越行の日付・月次受払表の最初の月。省略時は最初のトランザクションの月初）
+    #[serde(default)]
+    pub period_start: Option<String>,
+}
+
+/// 配合マスタ検証の設定
+#[derive(Debug, Deserialize)]
+pub struct FormulaValidation {
+    /// 消費比率合計の下限（省略時は検証しない）
+    #[serde(default)]
+    pub min_total_ratio: Option<f64>,
+    /// 消費比率合計の上限（省略時は検証しない）
+    #[serde(default)]
+    pub max_total_ratio: Option<f64>,
+    /// 問題がある場合に処理を中止する
+    #[serde(default)]
+    pub strict: bool,
+}
+
+impl Default for FormulaValidation {
+    fn default() -> Self {
+        let policy = FormulaValidationPolicy::default();
+        Self {
+            min_total_ratio: policy.min_total_ratio,
+            max_total_ratio: policy.max_total_ratio,
+            strict: policy.strict,
+        }
+    }
+}
+
+/// 入力データ診断の設定
+#[derive(Debug, Default, Deserialize)]
+pub struct Diagnostics {
+    /// 問題のある行を記録して処理を続ける（エラー一覧シートを出力）
+    #[serde(default)]
+    pub accumulate: bool,
+}
+
+/// 材料費の構成要素ごとの端数処理（half_up / half_even / truncate / ceiling）
+#[derive(Debug, Deserialize)]
+pub struct Rounding {
+    /// 端数処理する桁（0 = 円未満）
+    #[serde(default)]
+    pub digits: u32,
+    /// 原砂金額
+    #[serde(default = "default_rounding_mode")]
+    pub raw_material_cost: String,
+    /// 原砂歩留金額
+    #[serde(default = "default_rounding_mode")]
+    pub yield_cost: String,
+    /// 材料運賃
+    #[serde(default = "default_rounding_mode")]
+    pub freight_cost: String,
+    /// 材料費
+    #[serde(default = "default_rounding_mode")]
+    pub material_cost: String,
+}
+
+impl Default for Rounding {
+    fn default() -> Self {
+        Self {
+            digits: 0,
+            raw_material_cost: default_rounding_mode(),
+            yield_cost: default_rounding_mode(),
+            freight_cost: default_rounding_mode(),
+            material_cost: default_rounding_mode(),
+        }
+    }
+}
+
+/// 歩留率の計算方法（multiply: 原砂金額 × 歩留率 / divide: 原砂金額 ÷ 歩留率）
+#[derive(Debug, Deserialize)]
+pub struct Yield {
+    /// 全商品の計算方法
+    #[serde(default = "default_yield_method")]
+    pub method: String,
+    /// 工場別の計算方法
+    #[serde(default)]
+    pub method_by_plant: HashMap<String, String>,
+    /// 商品コード別の計算方法（工場別の設定より優先）
+    #[serde(default)]
+    pub method_by_product: HashMap<String, String>,
+}
+
+impl Default for Yield {
+    fn default() -> Self {
+        Self {
+            method: default_yield_method(),
+            method_by_plant: HashMap::new(),
+            method_by_product: HashMap::new(),
+        }
+    }
+}
+
+/// 便の運賃総額の配賦基準（weight / value / equal）
+#[derive(Debug, Deserialize)]
+pub struct Freight {
+    #[serde(default = "default_freight_allocation")]
+    pub allocation: String,
+}
+
+impl Default for Freight {
+    fn default() -> Self {
+        Self {
+            allocation: default_freight_allocation(),
+        }
+    }
+}
+
+/// シート名・列名の対応表（既定のシート名・列名 → テンプレートのシート名・列名）
+#[derive(Debug, Default, Deserialize)]
+pub struct Schema {
+    /// 既定のシート名 → テンプレートのシート名
+    #[serde(default)]
+    pub sheets: HashMap<String, String>,
+    /// 既定のシート名 → 既定の列名 → 列名の別名
+    #[serde(default)]
+    pub columns: HashMap<String, HashMap<String, Vec<String>>>,
+}
+
+fn default_freight_allocation() -> String {
+    "weight".to_string()
+}
+
+fn default_yield_method() -> String {
+    "multiply".to_string()
+}
+
+fn default_rounding_mode() -> String {
+    "half_up".to_string()
+}
+
 impl Config {
     pub fn load() -> Result<Self> {
         let config_path = "config.toml";
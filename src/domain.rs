@@ -1,5 +1,13 @@
+pub mod cost_layer;
+pub mod diagnostics;
 pub mod entities;
+pub mod error;
+pub mod formula_validation;
+pub mod freight_allocation;
+pub mod pricing;
 pub mod repositories;
 pub mod services;
 pub mod sheet_schema;
+pub mod unit_conversion;
+pub mod valuation;
 pub mod value_objects;
@@ -0,0 +1,423 @@
+use super::entities::*;
+use super::error::Result;
+use super::services::MaterialCostResult;
+use super::value_objects::*;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// 仕入ロット（原価層）
+#[derive(Debug, Clone)]
+pub struct CostLayer {
+    pub product_code: ProductCode,
+    pub product_name: String,
+    pub purchase_date: Option<TransactionDate>, // 期首在庫の場合はNone
+    pub unit_price: Amount,
+    pub purchased_quantity: Quantity,
+    pub remaining_quantity: Quantity,
+    pub lot_freight: Option<Amount>, // ロット運賃（便の運賃総額の配賦額）
+}
+
+impl CostLayer {
+    fn from_purchase(purchase: &Purchase) -> Self {
+        Self {
+            product_code: purchase.product_code.clone(),
+            product_name: purchase.product_name.clone(),
+            purchase_date: Some(purchase.purchase_date.clone()),
+            unit_price: purchase.unit_price,
+            purchased_quantity: purchase.quantity,
+            remaining_quantity: purchase.quantity,
+            lot_freight: match purchase.freight_code {
+                FreightCode::LotFreight(amount) => Some(amount),
+                _ => None,
+            },
+        }
+    }
+
+    fn from_opening_balance(opening: &OpeningBalance, unit_price: Amount) -> Self {
+        Self {
+            product_code: opening.product_code.clone(),
+            product_name: opening.product_name.clone(),
+            purchase_date: None,
+            unit_price,
+            purchased_quantity: opening.quantity,
+            remaining_quantity: opening.quantity,
+            lot_freight: None,
+        }
+    }
+
+    /// 生産日時点で引当可能か（期首在庫は常に引当可能）
+    fn is_available_on(&self, as_of: &TransactionDate) -> bool {
+        self.purchase_date.as_ref().is_none_or(|d| d <= as_of)
+    }
+
+    /// 残高金額 = 残数量 × 単価
+    pub fn remaining_amount(&self) -> Amount {
+        self.unit_price.multiply(self.remaining_quantity.decimal())
+    }
+}
+
+/// 消費に対するロット引当
+#[derive(Debug, Clone)]
+pub struct LotAllocation {
+    pub purchase_date: Option<TransactionDate>, // 期首在庫の場合はNone
+    pub quantity: Quantity,
+    pub unit_price: Amount,
+    pub amount: Amount,
+    pub freight: Option<Amount>, // ロット運賃 × 引当数量 / 仕入数量（ロット運賃の仕入のみ）
+}
+
+/// 先入先出法によるロット台帳
+///
+/// 期首在庫を最初のロットとし、生産日以前に仕入れたロットのうち古いものから順に消費する。
+pub struct CostLayerLedger {
+    // 商品コードごとのロット（仕入日の昇順）
+    layers: HashMap<String, Vec<CostLayer>>,
+}
+
+impl CostLayerLedger {
+    /// 仕入と期首在庫からロット台帳を作成
+    ///
+    /// 期首金額が未入力の場合は最初の仕入単価で評価する。
+    pub fn new(purchases: Vec<Purchase>, opening_balances: Vec<OpeningBalance>) -> Self {
+        let mut layers: HashMap<String, Vec<CostLayer>> = HashMap::new();
+        for purchase in &purchases {
+            layers
+                .entry(purchase.product_code.value().to_string())
+                .or_default()
+                .push(CostLayer::from_purchase(purchase));
+        }
+
+        // 同日のロットは仕入データの順序を保つ（安定ソート、期首在庫は先頭）
+        for product_layers in layers.values_mut() {
+            product_layers.sort_by(|a, b| a.purchase_date.cmp(&b.purchase_date));
+        }
+
+        for opening in &opening_balances {
+            let product_layers = layers
+                .entry(opening.product_code.value().to_string())
+                .or_default();
+            let unit_price = opening
+                .unit_price()
+                .or_else(|| product_layers.first().map(|l| l.unit_price))
+                .unwrap_or_else(Amount::zero);
+            product_layers.insert(0, CostLayer::from_opening_balance(opening, unit_price));
+        }
+
+        Self { layers }
+    }
+
+    /// 消費数量をロットに引き当て、引当結果と引当不足数量を返す
+    pub fn consume(
+        &mut self,
+        product_code: &ProductCode,
+        quantity: &Quantity,
+        as_of: &TransactionDate,
+    ) -> Result<(Vec<LotAllocation>, Quantity)> {
+        let mut allocations = Vec::new();
+        let mut remaining = quantity.decimal();
+
+        if let Some(product_layers) = self.layers.get_mut(product_code.value()) {
+            for layer in product_layers
+                .iter_mut()
+                .filter(|l| l.is_available_on(as_of))
+            {
+                if remaining <= Decimal::ZERO {
+                    break;
+                }
+
+                quantity.ensure_unit(layer.remaining_quantity.unit())?;
+                let available = layer.remaining_quantity.decimal();
+                if available <= Decimal::ZERO {
+                    continue;
+                }
+
+                let taken = available.min(remaining);
+                layer.remaining_quantity = Quantity::from_decimal(available - taken)?;
+                remaining -= taken;
+
+                // 残数量のあるロットは仕入数量が0より大きい
+                let freight = layer
+                    .lot_freight
+                    .map(|f| f.multiply(taken / layer.purchased_quantity.decimal()));
+
+                allocations.push(LotAllocation {
+                    purchase_date: layer.purchase_date.clone(),
+                    quantity: Quantity::from_decimal(taken)?,
+                    unit_price: layer.unit_price,
+                    amount: layer.unit_price.multiply(taken),
+                    freight,
+                });
+            }
+        }
+
+        Ok((
+            allocations,
+            Quantity::from_decimal(remaining.max(Decimal::ZERO))?,
+        ))
+    }
+
+    /// 材料費計算結果の各材料消費にロットを引き当てる
+    ///
+    /// ロット運賃の仕入ロットから引き当てた数量は、そのロットのロット運賃を按分して運賃とし、
+    /// それ以外の数量は材料費計算の運賃（消費数量あたり）のままとする。
+    pub fn allocate(
+        &mut self,
+        result: &mut MaterialCostResult,
+        as_of: &TransactionDate,
+    ) -> Result<()> {
+        for consumption in &mut result.consumptions {
+            let (allocations, shortage) =
+                self.consume(&consumption.material_code, &consumption.quantity, as_of)?;
+
+            let lot_freight_allocations: Vec<(Decimal, Amount)> = allocations
+                .iter()
+                .filter_map(|a| a.freight.map(|f| (a.quantity.decimal(), f)))
+                .collect();
+            if !lot_freight_allocations.is_empty() {
+                let lot_quantity: Decimal = lot_freight_allocations.iter().map(|(q, _)| *q).sum();
+                let other_freight = consumption.freight_cost.multiply(
+                    (consumption.quantity.decimal() - lot_quantity)
+                        / consumption.quantity.decimal(),
+                );
+                consumption.freight_cost = lot_freight_allocations
+                    .iter()
+                    .fold(other_freight, |acc, (_, f)| acc.add(f));
+            }
+
+            consumption.lot_allocations = allocations;
+            consumption.lot_shortage = shortage;
+        }
+        result.refresh_freight();
+        Ok(())
+    }
+
+    /// 期末時点で残数量のあるロット（商品コード・仕入日順）
+    pub fn remaining_layers(&self) -> Vec<CostLayer> {
+        let mut codes: Vec<&String> = self.layers.keys().collect();
+        codes.sort();
+
+        codes
+            .into_iter()
+            .flat_map(|code| self.layers[code].iter())
+            .filter(|l| l.remaining_quantity.value() > 0.0)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::services::{BomNode, IntermediateCost, MaterialConsumption};
+
+    fn purchase(code: &str, date: &str, unit_price: f64, quantity: f64) -> Purchase {
+        Purchase::new(
+            TransactionDate::new(date.to_string()).unwrap(),
+            ProductCode::new(code.to_string()).unwrap(),
+            format!("材料{}", code),
+            Amount::new(unit_price).unwrap(),
+            Quantity::new(quantity).unwrap(),
+            FreightCode::DirectPrice(0.0),
+        )
+    }
+
+    fn code(value: &str) -> ProductCode {
+        ProductCode::new(value.to_string()).unwrap()
+    }
+
+    fn date(value: &str) -> TransactionDate {
+        TransactionDate::new(value.to_string()).unwrap()
+    }
+
+    fn qty(value: f64) -> Quantity {
+        Quantity::new(value).unwrap()
+    }
+
+    #[test]
+    fn test_consume_first_in_first_out() {
+        // ロット1: 2026-01-05 100kg @100円
+        // ロット2: 2026-01-10 100kg @120円
+        // 150kg消費 → ロット1から100kg、ロット2から50kg
+        let mut ledger = CostLayerLedger::new(
+            vec![
+                purchase("M001", "2026-01-10", 120.0, 100.0),
+                purchase("M001", "2026-01-05", 100.0, 100.0),
+            ],
+            Vec::new(),
+        );
+
+        let (allocations, shortage) = ledger
+            .consume(&code("M001"), &qty(150.0), &date("2026-01-20"))
+            .unwrap();
+
+        assert_eq!(allocations.len(), 2);
+        assert_eq!(allocations[0].purchase_date, Some(date("2026-01-05")));
+        assert_eq!(allocations[0].quantity.value(), 100.0);
+        assert_eq!(allocations[0].amount.value(), 10000.0);
+        assert_eq!(allocations[1].purchase_date, Some(date("2026-01-10")));
+        assert_eq!(allocations[1].quantity.value(), 50.0);
+        assert_eq!(allocations[1].amount.value(), 6000.0);
+        assert_eq!(shortage.value(), 0.0);
+
+        let remaining = ledger.remaining_layers();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].remaining_quantity.value(), 50.0);
+        assert_eq!(remaining[0].remaining_amount().value(), 6000.0);
+    }
+
+    #[test]
+    fn test_consume_ignores_future_lots() {
+        // 生産日より後に仕入れたロットは引き当てない
+        let mut ledger = CostLayerLedger::new(
+            vec![
+                purchase("M001", "2026-01-05", 100.0, 30.0),
+                purchase("M001", "2026-01-25", 120.0, 100.0),
+            ],
+            Vec::new(),
+        );
+
+        let (allocations, shortage) = ledger
+            .consume(&code("M001"), &qty(50.0), &date("2026-01-20"))
+            .unwrap();
+
+        assert_eq!(allocations.len(), 1);
+        assert_eq!(allocations[0].quantity.value(), 30.0);
+        assert_eq!(shortage.value(), 20.0);
+    }
+
+    #[test]
+    fn test_consume_unknown_material() {
+        let mut ledger = CostLayerLedger::new(Vec::new(), Vec::new());
+
+        let (allocations, shortage) = ledger
+            .consume(&code("M999"), &qty(10.0), &date("2026-01-20"))
+            .unwrap();
+
+        assert!(allocations.is_empty());
+        assert_eq!(shortage.value(), 10.0);
+    }
+
+    #[test]
+    fn test_remaining_layers_sorted_by_code() {
+        let ledger = CostLayerLedger::new(
+            vec![
+                purchase("M002", "2026-01-05", 100.0, 10.0),
+                purchase("M001", "2026-01-07", 100.0, 10.0),
+                purchase("M001", "2026-01-06", 100.0, 0.0),
+            ],
+            Vec::new(),
+        );
+
+        let remaining = ledger.remaining_layers();
+
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[0].product_code.value(), "M001");
+        assert_eq!(remaining[1].product_code.value(), "M002");
+    }
+
+    #[test]
+    fn test_opening_balance_consumed_first() {
+        // 期首在庫 40kg（金額未入力 → 最初の仕入単価 100円で評価）
+        let mut ledger = CostLayerLedger::new(
+            vec![purchase("M001", "2026-02-05", 100.0, 100.0)],
+            vec![OpeningBalance::new(
+                code("M001"),
+                "材料M001".to_string(),
+                qty(40.0),
+                None,
+            )],
+        );
+
+        let (allocations, _) = ledger
+            .consume(&code("M001"), &qty(60.0), &date("2026-02-10"))
+            .unwrap();
+
+        assert_eq!(allocations.len(), 2);
+        assert!(allocations[0].purchase_date.is_none());
+        assert_eq!(allocations[0].quantity.value(), 40.0);
+        assert_eq!(allocations[0].unit_price.value(), 100.0);
+        assert_eq!(allocations[1].quantity.value(), 20.0);
+    }
+
+    #[test]
+    fn test_consume_rejects_incompatible_unit() {
+        // ロットは kg のため、袋の数量は引き当てない
+        let mut ledger = CostLayerLedger::new(
+            vec![purchase("M001", "2026-01-05", 100.0, 100.0)],
+            Vec::new(),
+        );
+
+        let result = ledger.consume(
+            &code("M001"),
+            &Quantity::in_unit(2.0, Unit::Bag).unwrap(),
+            &date("2026-01-20"),
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_allocate_charges_lot_freight_per_lot() {
+        // ロット1: 2026-01-05 100kg ロット運賃 500円
+        // ロット2: 2026-01-10 100kg ロット運賃 1,500円（単価を採用した最新の仕入）
+        // 中間品 I001 経由で 150kg 消費 → 500 × 100/100 + 1,500 × 50/100 = 1,250円
+        let mut lot1 = purchase("M001", "2026-01-05", 100.0, 100.0);
+        lot1.freight_code = FreightCode::LotFreight(Amount::new(500.0).unwrap());
+        let mut lot2 = purchase("M001", "2026-01-10", 100.0, 100.0);
+        lot2.freight_code = FreightCode::LotFreight(Amount::new(1500.0).unwrap());
+        let mut ledger = CostLayerLedger::new(vec![lot1, lot2], Vec::new());
+
+        // 材料費計算では最新ロットのロット運賃で按分: 1,500 × 150/100 = 2,250円
+        let freight = Amount::new(2250.0).unwrap();
+        let mut result = MaterialCostResult {
+            consumptions: vec![MaterialConsumption {
+                material_code: code("M001"),
+                material_name: "材料M001".to_string(),
+                quantity: qty(150.0),
+                unit_price: Amount::new(100.0).unwrap(),
+                purchase_date: date("2026-01-10"),
+                total_cost: Amount::new(15000.0).unwrap(),
+                freight_cost: freight,
+                purchase_quantity: qty(100.0),
+                freight_code_str: String::new(),
+                freight_kg_price: 15.0,
+                lot_allocations: Vec::new(),
+                lot_shortage: Quantity::zero(),
+            }],
+            intermediates: vec![IntermediateCost {
+                material_code: code("I001"),
+                level: 1,
+                quantity: qty(150.0),
+                material_cost: Amount::new(15000.0).unwrap(),
+                freight_cost: freight,
+            }],
+            explosion: vec![BomNode {
+                material_code: code("I001"),
+                material_name: "I001".to_string(),
+                quantity: qty(150.0),
+                material_cost: Amount::new(15000.0).unwrap(),
+                freight_cost: freight,
+                children: vec![BomNode {
+                    material_code: code("M001"),
+                    material_name: "材料M001".to_string(),
+                    quantity: qty(150.0),
+                    material_cost: Amount::new(15000.0).unwrap(),
+                    freight_cost: freight,
+                    children: Vec::new(),
+                }],
+            }],
+            total_freight_cost: freight,
+        };
+
+        ledger.allocate(&mut result, &date("2026-01-20")).unwrap();
+
+        let allocations = &result.consumptions[0].lot_allocations;
+        assert_eq!(allocations[0].freight.map(|f| f.value()), Some(500.0));
+        assert_eq!(allocations[1].freight.map(|f| f.value()), Some(750.0));
+        assert_eq!(result.consumptions[0].freight_cost.value(), 1250.0);
+        assert_eq!(result.total_freight_cost.value(), 1250.0);
+        assert_eq!(result.explosion[0].freight_cost.value(), 1250.0);
+        assert_eq!(result.explosion[0].children[0].freight_cost.value(), 1250.0);
+        assert_eq!(result.intermediates[0].freight_cost.value(), 1250.0);
+    }
+}
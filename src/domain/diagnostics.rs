@@ -0,0 +1,131 @@
+use super::error::{DomainError, Result};
+use std::fmt;
+
+/// 入力データの問題（シート・行・列の位置つき）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub sheet: String,
+    pub row: Option<usize>,     // Excelの行番号（1始まり）
+    pub column: Option<String>, // 列名
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(sheet: &str, message: impl fmt::Display) -> Self {
+        Self {
+            sheet: sheet.to_string(),
+            row: None,
+            column: None,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn with_row(mut self, row: usize) -> Self {
+        self.row = Some(row);
+        self
+    }
+
+    pub fn with_column(mut self, column: &str) -> Self {
+        self.column = Some(column.to_string());
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}シート", self.sheet)?;
+        if let Some(row) = self.row {
+            write!(f, " {}行目", row)?;
+        }
+        if let Some(column) = &self.column {
+            write!(f, " [{}]", column)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// 入力データの問題の収集
+///
+/// 通常は最初の問題でエラーを返す。蓄積モードでは問題を記録して該当行をスキップし、処理を続ける。
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    accumulate: bool,
+    entries: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new(accumulate: bool) -> Self {
+        Self {
+            accumulate,
+            entries: Vec::new(),
+        }
+    }
+
+    /// 問題を報告（蓄積モードでは記録してOk、そうでなければエラー）
+    pub fn report(&mut self, diagnostic: Diagnostic) -> Result<()> {
+        if !self.accumulate {
+            return Err(DomainError::InvalidCell(diagnostic));
+        }
+        // 同じシートを複数のリポジトリで読むため、同じ問題は1回だけ記録する
+        if !self.entries.contains(&diagnostic) {
+            self.entries.push(diagnostic);
+        }
+        Ok(())
+    }
+
+    /// 行の読み取り結果を処理（問題がある場合は報告してNone）
+    pub fn collect<T>(&mut self, result: std::result::Result<T, Diagnostic>) -> Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(diagnostic) => {
+                self.report(diagnostic)?;
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn entries(&self) -> &[Diagnostic] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic() -> Diagnostic {
+        Diagnostic::new("【入庫】仕入", "仕入単価が数値ではありません: 'abc'")
+            .with_row(3)
+            .with_column("仕入単価")
+    }
+
+    #[test]
+    fn test_diagnostic_display() {
+        assert_eq!(
+            diagnostic().to_string(),
+            "【入庫】仕入シート 3行目 [仕入単価]: 仕入単価が数値ではありません: 'abc'"
+        );
+    }
+
+    #[test]
+    fn test_fail_fast_returns_error() {
+        let mut diagnostics = Diagnostics::new(false);
+        let result = diagnostics.collect::<f64>(Err(diagnostic()));
+        assert!(result.is_err());
+        assert!(diagnostics.entries().is_empty());
+    }
+
+    #[test]
+    fn test_accumulate_records_and_skips() {
+        let mut diagnostics = Diagnostics::new(true);
+
+        let skipped = diagnostics.collect::<f64>(Err(diagnostic())).unwrap();
+        let duplicate = diagnostics.collect::<f64>(Err(diagnostic())).unwrap();
+        let valid = diagnostics.collect::<f64>(Ok(1.0)).unwrap();
+
+        assert!(skipped.is_none());
+        assert!(duplicate.is_none());
+        assert_eq!(valid, Some(1.0));
+        assert_eq!(diagnostics.entries().len(), 1);
+    }
+}
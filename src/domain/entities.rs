@@ -1,11 +1,19 @@
 mod formula_entry;
+mod formula_version;
 mod freight_master;
+mod freight_rate_table;
 mod inventory_transaction;
+mod opening_balance;
 mod production;
 mod purchase;
+mod unit_conversion;
 
 pub use formula_entry::FormulaEntry;
+pub use formula_version::FormulaVersion;
 pub use freight_master::FreightMaster;
+pub use freight_rate_table::FreightRateTable;
 pub use inventory_transaction::InventoryTransaction;
+pub use opening_balance::OpeningBalance;
 pub use production::Production;
 pub use purchase::Purchase;
+pub use unit_conversion::UnitConversion;
@@ -0,0 +1,170 @@
+use crate::domain::entities::FormulaEntry;
+use crate::domain::error::{DomainError, MasterKind, Result};
+use crate::domain::value_objects::*;
+
+/// 配合マスタの版（同じ有効期間を持つ製造商品の配合行のまとまり）
+#[derive(Debug, Clone)]
+pub struct FormulaVersion {
+    pub product_code: ProductCode,
+    pub valid_from: Option<TransactionDate>, // 空の場合は期間の始まりなし
+    pub valid_to: Option<TransactionDate>,   // 空の場合は無期限
+    pub entries: Vec<FormulaEntry>,
+    pub duplicates: Vec<ProductCode>, // 重複として除いた配合行の材料（検証での報告用）
+    pub conflicts: Vec<FormulaEntry>, // 先の行と消費比率が異なるため除いた配合行（検証での報告用）
+}
+
+impl FormulaVersion {
+    pub fn new(
+        product_code: ProductCode,
+        valid_from: Option<TransactionDate>,
+        valid_to: Option<TransactionDate>,
+    ) -> Result<Self> {
+        if let (Some(from), Some(to)) = (&valid_from, &valid_to)
+            && to < from
+        {
+            return Err(DomainError::InvalidPeriod {
+                master: MasterKind::Formula,
+                code: product_code.value().to_string(),
+                valid_from: from.value().to_string(),
+                valid_to: to.value().to_string(),
+            });
+        }
+
+        Ok(Self {
+            product_code,
+            valid_from,
+            valid_to,
+            entries: Vec::new(),
+            duplicates: Vec::new(),
+            conflicts: Vec::new(),
+        })
+    }
+
+    /// 配合行を追加（同じ材料・同じ消費比率の行は重複として1行にまとめ、
+    /// 同じ材料が異なる消費比率で登録されている場合は先の行を使用して後の行を記録）
+    pub fn add_entry(&mut self, entry: FormulaEntry) {
+        match self
+            .entries
+            .iter()
+            .find(|e| e.material_code == entry.material_code)
+        {
+            Some(existing)
+                if existing.consumption_ratio.value() == entry.consumption_ratio.value() =>
+            {
+                self.duplicates.push(entry.material_code)
+            }
+            Some(_) => self.conflicts.push(entry),
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// 有効期間が同じか（同じ版の行か）
+    pub fn has_period(
+        &self,
+        valid_from: Option<&TransactionDate>,
+        valid_to: Option<&TransactionDate>,
+    ) -> bool {
+        self.valid_from.as_ref() == valid_from && self.valid_to.as_ref() == valid_to
+    }
+
+    /// 指定日が有効期間内か
+    pub fn is_valid_on(&self, date: &TransactionDate) -> bool {
+        self.valid_from.as_ref().is_none_or(|from| *from <= *date)
+            && self.valid_to.as_ref().is_none_or(|to| *date <= *to)
+    }
+
+    /// 有効期間が他の版と重複しているか
+    pub fn overlaps(&self, other: &FormulaVersion) -> bool {
+        let self_starts_before_other_ends = match (&self.valid_from, &other.valid_to) {
+            (Some(from), Some(to)) => from <= to,
+            _ => true,
+        };
+        let other_starts_before_self_ends = match (&other.valid_from, &self.valid_to) {
+            (Some(from), Some(to)) => from <= to,
+            _ => true,
+        };
+        self_starts_before_other_ends && other_starts_before_self_ends
+    }
+
+    /// 有効期間の表示用文字列
+    pub fn period_label(&self) -> String {
+        format!(
+            "{}～{}",
+            self.valid_from.as_ref().map(|d| d.value()).unwrap_or(""),
+            self.valid_to.as_ref().map(|d| d.value()).unwrap_or("")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> Option<TransactionDate> {
+        Some(TransactionDate::new(value.to_string()).unwrap())
+    }
+
+    fn version(from: Option<TransactionDate>, to: Option<TransactionDate>) -> FormulaVersion {
+        FormulaVersion::new(ProductCode::new("P001".to_string()).unwrap(), from, to).unwrap()
+    }
+
+    fn entry(material_code: &str, ratio: f64) -> FormulaEntry {
+        FormulaEntry::new(
+            ProductCode::new(material_code.to_string()).unwrap(),
+            ConsumptionRatio::new(ratio).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_formula_version_valid_on() {
+        let v = version(date("2026-01-01"), date("2026-03-31"));
+        assert!(v.is_valid_on(&date("2026-01-01").unwrap()));
+        assert!(v.is_valid_on(&date("2026-03-31").unwrap()));
+        assert!(!v.is_valid_on(&date("2026-04-01").unwrap()));
+
+        let unbounded = version(None, None);
+        assert!(unbounded.is_valid_on(&date("1990-01-01").unwrap()));
+    }
+
+    #[test]
+    fn test_formula_version_overlaps() {
+        let q1 = version(date("2026-01-01"), date("2026-03-31"));
+        let q2 = version(date("2026-04-01"), None);
+        let open_start = version(None, date("2026-01-15"));
+        assert!(!q1.overlaps(&q2));
+        assert!(q1.overlaps(&open_start));
+        assert!(!q2.overlaps(&open_start));
+    }
+
+    #[test]
+    fn test_formula_version_invalid_period() {
+        let result = FormulaVersion::new(
+            ProductCode::new("P001".to_string()).unwrap(),
+            date("2026-03-01"),
+            date("2026-02-01"),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_formula_version_conflicting_ratio() {
+        let mut v = version(None, None);
+        v.add_entry(entry("M001", 0.5));
+        v.add_entry(entry("M001", 0.4));
+        assert_eq!(v.entries.len(), 1);
+        assert_eq!(v.entries[0].consumption_ratio.value(), 0.5);
+        assert_eq!(v.conflicts.len(), 1);
+        assert_eq!(v.conflicts[0].consumption_ratio.value(), 0.4);
+    }
+
+    #[test]
+    fn test_formula_version_deduplicates_identical_entry() {
+        let mut v = version(None, None);
+        v.add_entry(entry("M001", 0.5));
+        v.add_entry(entry("M001", 0.5));
+        v.add_entry(entry("M002", 0.5));
+        assert_eq!(v.entries.len(), 2);
+        assert_eq!(v.duplicates.len(), 1);
+        assert_eq!(v.duplicates[0].value(), "M001");
+    }
+}
@@ -1,5 +1,6 @@
+use super::FreightRateTable;
+use crate::domain::error::{DomainError, MasterKind, Result};
 use crate::domain::value_objects::*;
-use color_eyre::{Result, eyre::eyre};
 
 /// 運賃マスタエンティティ
 #[derive(Debug, Clone)]
@@ -9,6 +10,8 @@ pub struct FreightMaster {
     pub kg_unit_price: Amount,
     pub valid_from: TransactionDate,
     pub valid_to: Option<TransactionDate>,
+    pub zone: Option<String>, // 配送先の地区（運賃表の地区割増を引く）
+    pub rate_table: Option<FreightRateTable>, // パターンの運賃表（ない場合は Kg単価のみ）
 }
 
 impl FreightMaster {
@@ -21,27 +24,89 @@ impl FreightMaster {
     ) -> Result<Self> {
         // 運賃コードのバリデーション（T01形式）
         if !freight_code.starts_with('T') || freight_code.len() != 3 {
-            return Err(eyre!(
-                "運賃コードの形式が不正です: '{}' (T01～T99の形式が必要)",
-                freight_code
+            return Err(DomainError::invalid_format(
+                "運賃コード",
+                &freight_code,
+                "T01～T99",
             ));
         }
         let digits = &freight_code[1..];
         if !digits.chars().all(|c| c.is_ascii_digit()) {
-            return Err(eyre!(
-                "運賃コードの形式が不正です: '{}' (T01～T99の形式が必要)",
-                freight_code
+            return Err(DomainError::invalid_format(
+                "運賃コード",
+                &freight_code,
+                "T01～T99",
             ));
         }
 
+        if let Some(to) = &valid_to
+            && *to < valid_from
+        {
+            return Err(DomainError::InvalidPeriod {
+                master: MasterKind::FreightMaster,
+                code: freight_code,
+                valid_from: valid_from.value().to_string(),
+                valid_to: to.value().to_string(),
+            });
+        }
+
         Ok(Self {
             freight_code,
             pattern_name,
             kg_unit_price,
             valid_from,
             valid_to,
+            zone: None,
+            rate_table: None,
         })
     }
+
+    pub fn with_zone(mut self, zone: String) -> Self {
+        self.zone = Some(zone);
+        self
+    }
+
+    pub fn with_rate_table(mut self, rate_table: FreightRateTable) -> Self {
+        self.rate_table = Some(rate_table);
+        self
+    }
+
+    /// 仕入数量に適用する Kg単価（運賃表がない場合は Kg単価）
+    pub fn kg_unit_price_for(&self, quantity: &Quantity) -> Result<Amount> {
+        match &self.rate_table {
+            Some(table) => {
+                table.effective_kg_price(self.kg_unit_price, quantity, self.zone.as_deref())
+            }
+            None => Ok(self.kg_unit_price),
+        }
+    }
+
+    /// 指定日が有効期間内か（有効終了日が空の場合は無期限）
+    pub fn is_valid_on(&self, date: &TransactionDate) -> bool {
+        self.valid_from <= *date && self.valid_to.as_ref().is_none_or(|to| *date <= *to)
+    }
+
+    /// 有効期間が他の運賃マスタと重複しているか
+    pub fn overlaps(&self, other: &FreightMaster) -> bool {
+        let self_starts_before_other_ends = other
+            .valid_to
+            .as_ref()
+            .is_none_or(|to| self.valid_from <= *to);
+        let other_starts_before_self_ends = self
+            .valid_to
+            .as_ref()
+            .is_none_or(|to| other.valid_from <= *to);
+        self_starts_before_other_ends && other_starts_before_self_ends
+    }
+
+    /// 有効期間の表示用文字列
+    pub fn period_label(&self) -> String {
+        format!(
+            "{}～{}",
+            self.valid_from.value(),
+            self.valid_to.as_ref().map(|d| d.value()).unwrap_or("")
+        )
+    }
 }
 
 #[cfg(test)]
@@ -136,4 +201,69 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    fn freight_master(valid_from: &str, valid_to: Option<&str>) -> FreightMaster {
+        FreightMaster::new(
+            "T01".to_string(),
+            PatternName::new("標準運賃".to_string()).unwrap(),
+            Amount::new(150.0).unwrap(),
+            TransactionDate::new(valid_from.to_string()).unwrap(),
+            valid_to.map(|d| TransactionDate::new(d.to_string()).unwrap()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_freight_master_valid_to_before_valid_from() {
+        let result = FreightMaster::new(
+            "T01".to_string(),
+            PatternName::new("標準運賃".to_string()).unwrap(),
+            Amount::new(150.0).unwrap(),
+            TransactionDate::new("2024-12-31".to_string()).unwrap(),
+            Some(TransactionDate::new("2024-01-01".to_string()).unwrap()),
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_freight_master_is_valid_on() {
+        let master = freight_master("2024-01-01", Some("2024-06-30"));
+        let date = |d: &str| TransactionDate::new(d.to_string()).unwrap();
+
+        assert!(!master.is_valid_on(&date("2023-12-31")));
+        assert!(master.is_valid_on(&date("2024-01-01")));
+        assert!(master.is_valid_on(&date("2024-06-30")));
+        assert!(!master.is_valid_on(&date("2024-07-01")));
+
+        let open_ended = freight_master("2024-07-01", None);
+        assert!(open_ended.is_valid_on(&date("2099-12-31")));
+    }
+
+    #[test]
+    fn test_freight_master_overlaps() {
+        let first_half = freight_master("2024-01-01", Some("2024-06-30"));
+        let second_half = freight_master("2024-07-01", None);
+        let overlapping = freight_master("2024-06-30", Some("2024-12-31"));
+
+        assert!(!first_half.overlaps(&second_half));
+        assert!(!second_half.overlaps(&first_half));
+        assert!(first_half.overlaps(&overlapping));
+        assert!(second_half.overlaps(&overlapping));
+    }
+
+    #[test]
+    fn test_kg_unit_price_for_uses_rate_table() {
+        let mut table = FreightRateTable::new(PatternName::new("標準運賃".to_string()).unwrap());
+        table.add_weight_break(Quantity::new(0.0).unwrap(), Amount::new(20.0).unwrap());
+        table.add_weight_break(Quantity::new(500.0).unwrap(), Amount::new(15.0).unwrap());
+        table.add_zone_surcharge("九州".to_string(), Amount::new(2.0).unwrap());
+
+        let master = freight_master("2024-01-01", None);
+        let quantity = Quantity::new(600.0).unwrap();
+        assert_eq!(master.kg_unit_price_for(&quantity).unwrap().value(), 150.0);
+
+        let master = master.with_zone("九州".to_string()).with_rate_table(table);
+        assert_eq!(master.kg_unit_price_for(&quantity).unwrap().value(), 17.0);
+    }
 }
@@ -0,0 +1,215 @@
+use crate::domain::error::{DomainError, MasterKind, Result};
+use crate::domain::value_objects::*;
+use std::collections::HashMap;
+
+/// 重量帯（下限重量以上の仕入に適用する Kg単価）
+#[derive(Debug, Clone)]
+struct WeightBreak {
+    min_quantity: Quantity,
+    kg_unit_price: Amount,
+}
+
+/// 運賃表エンティティ（運賃パターンごとの重量帯・最低運賃・地区割増）
+#[derive(Debug, Clone)]
+pub struct FreightRateTable {
+    pub pattern_name: PatternName,
+    weight_breaks: Vec<WeightBreak>, // 下限重量の昇順
+    minimum_charge: Option<Amount>,
+    zone_surcharges: HashMap<String, Amount>, // 地区ごとの割増Kg単価
+}
+
+impl FreightRateTable {
+    pub fn new(pattern_name: PatternName) -> Self {
+        Self {
+            pattern_name,
+            weight_breaks: Vec::new(),
+            minimum_charge: None,
+            zone_surcharges: HashMap::new(),
+        }
+    }
+
+    /// 重量帯を追加（下限重量の昇順を保つ）
+    pub fn add_weight_break(&mut self, min_quantity: Quantity, kg_unit_price: Amount) {
+        let position = self
+            .weight_breaks
+            .partition_point(|b| b.min_quantity.decimal() <= min_quantity.decimal());
+        self.weight_breaks.insert(
+            position,
+            WeightBreak {
+                min_quantity,
+                kg_unit_price,
+            },
+        );
+    }
+
+    pub fn set_minimum_charge(&mut self, minimum_charge: Amount) {
+        self.minimum_charge = Some(minimum_charge);
+    }
+
+    pub fn add_zone_surcharge(&mut self, zone: String, kg_surcharge: Amount) {
+        self.zone_surcharges.insert(zone, kg_surcharge);
+    }
+
+    pub fn minimum_charge(&self) -> Option<Amount> {
+        self.minimum_charge
+    }
+
+    /// 重量帯（下限重量・Kg単価）を下限重量の昇順で取得
+    pub fn weight_breaks(&self) -> impl Iterator<Item = (Quantity, Amount)> + '_ {
+        self.weight_breaks
+            .iter()
+            .map(|b| (b.min_quantity, b.kg_unit_price))
+    }
+
+    /// 地区ごとの割増Kg単価を取得
+    pub fn zone_surcharges(&self) -> impl Iterator<Item = (&str, Amount)> + '_ {
+        self.zone_surcharges
+            .iter()
+            .map(|(zone, surcharge)| (zone.as_str(), *surcharge))
+    }
+
+    pub fn has_weight_break(&self, min_quantity: &Quantity) -> bool {
+        self.weight_breaks
+            .iter()
+            .any(|b| b.min_quantity.decimal() == min_quantity.decimal())
+    }
+
+    pub fn has_zone(&self, zone: &str) -> bool {
+        self.zone_surcharges.contains_key(zone)
+    }
+
+    /// 仕入数量に適用する実効 Kg単価
+    ///
+    /// 1. 仕入数量が下限重量以上の重量帯のうち最も重い帯の Kg単価（重量帯がない場合は `base_price`、
+    ///    最も軽い帯に満たない場合は最も軽い帯）
+    /// 2. 地区の割増Kg単価を加算（地区割増が登録されている運賃表のみ）
+    /// 3. 運賃（Kg単価 × 仕入数量）が最低運賃に満たない場合は 最低運賃 ÷ 仕入数量
+    pub fn effective_kg_price(
+        &self,
+        base_price: Amount,
+        quantity: &Quantity,
+        zone: Option<&str>,
+    ) -> Result<Amount> {
+        quantity.ensure_unit(Unit::Kilogram)?;
+
+        let mut kg_price = self
+            .weight_breaks
+            .iter()
+            .rev()
+            .find(|b| b.min_quantity.decimal() <= quantity.decimal())
+            .or_else(|| self.weight_breaks.first())
+            .map(|b| b.kg_unit_price)
+            .unwrap_or(base_price);
+
+        if let Some(zone) = zone
+            && !self.zone_surcharges.is_empty()
+        {
+            let surcharge =
+                self.zone_surcharges
+                    .get(zone)
+                    .ok_or_else(|| DomainError::MasterNotFound {
+                        master: MasterKind::FreightRate,
+                        code: format!("{} / {}", self.pattern_name.value(), zone),
+                    })?;
+            kg_price = kg_price.add(surcharge);
+        }
+
+        if let Some(minimum) = self.minimum_charge
+            && kg_price.multiply(quantity.decimal()).decimal() < minimum.decimal()
+            && let Some(minimum_price) = minimum.divide_by(quantity.decimal())
+        {
+            kg_price = minimum_price;
+        }
+
+        Ok(kg_price)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(value: f64) -> Amount {
+        Amount::new(value).unwrap()
+    }
+
+    fn qty(value: f64) -> Quantity {
+        Quantity::new(value).unwrap()
+    }
+
+    fn table() -> FreightRateTable {
+        // 0kg～: 20円/kg、500kg～: 15円/kg、1,000kg～: 12円/kg、最低運賃 3,000円
+        let mut table = FreightRateTable::new(PatternName::new("路線便".to_string()).unwrap());
+        table.add_weight_break(qty(1000.0), amount(12.0));
+        table.add_weight_break(qty(0.0), amount(20.0));
+        table.add_weight_break(qty(500.0), amount(15.0));
+        table.set_minimum_charge(amount(3000.0));
+        table
+    }
+
+    #[test]
+    fn test_weight_break_selected_by_quantity() {
+        let table = table();
+        let base = amount(99.0);
+
+        assert_eq!(
+            table
+                .effective_kg_price(base, &qty(499.0), None)
+                .unwrap()
+                .value(),
+            20.0
+        );
+        assert_eq!(
+            table
+                .effective_kg_price(base, &qty(500.0), None)
+                .unwrap()
+                .value(),
+            15.0
+        );
+        assert_eq!(
+            table
+                .effective_kg_price(base, &qty(2000.0), None)
+                .unwrap()
+                .value(),
+            12.0
+        );
+    }
+
+    #[test]
+    fn test_minimum_charge_raises_kg_price() {
+        // 100kg × 20円 = 2,000円 < 最低運賃 3,000円 → 30円/kg
+        let price = table()
+            .effective_kg_price(amount(99.0), &qty(100.0), None)
+            .unwrap();
+        assert_eq!(price.value(), 30.0);
+    }
+
+    #[test]
+    fn test_zone_surcharge_added() {
+        let mut table = table();
+        table.add_zone_surcharge("九州".to_string(), amount(3.0));
+
+        let price = table
+            .effective_kg_price(amount(99.0), &qty(1000.0), Some("九州"))
+            .unwrap();
+        assert_eq!(price.value(), 15.0);
+
+        let missing = table.effective_kg_price(amount(99.0), &qty(1000.0), Some("北海道"));
+        assert!(matches!(
+            missing,
+            Err(DomainError::MasterNotFound {
+                master: MasterKind::FreightRate,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_base_price_used_without_weight_breaks() {
+        let table = FreightRateTable::new(PatternName::new("チャーター".to_string()).unwrap());
+        let price = table
+            .effective_kg_price(amount(18.0), &qty(100.0), Some("関東"))
+            .unwrap();
+        assert_eq!(price.value(), 18.0);
+    }
+}
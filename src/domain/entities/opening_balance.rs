@@ -0,0 +1,63 @@
+use crate::domain::value_objects::*;
+
+/// 期首在庫エンティティ
+#[derive(Debug, Clone)]
+pub struct OpeningBalance {
+    pub product_code: ProductCode,
+    pub product_name: String,
+    pub quantity: Quantity,
+    pub amount: Option<Amount>, // 期首金額（未入力の場合は仕入単価で評価）
+}
+
+impl OpeningBalance {
+    pub fn new(
+        product_code: ProductCode,
+        product_name: String,
+        quantity: Quantity,
+        amount: Option<Amount>,
+    ) -> Self {
+        Self {
+            product_code,
+            product_name,
+            quantity,
+            amount,
+        }
+    }
+
+    /// 期首単価 = 期首金額 ÷ 期首数量（金額未入力または数量0の場合はNone）
+    pub fn unit_price(&self) -> Option<Amount> {
+        self.amount?.divide_by(self.quantity.decimal())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opening_balance_creation() {
+        let product_code = ProductCode::new("M001".to_string()).unwrap();
+        let quantity = Quantity::new(200.0).unwrap();
+        let amount = Amount::new(10000.0).unwrap();
+
+        let opening =
+            OpeningBalance::new(product_code, "材料A".to_string(), quantity, Some(amount));
+
+        assert_eq!(opening.product_code.value(), "M001");
+        assert_eq!(opening.product_name, "材料A");
+        assert_eq!(opening.quantity.value(), 200.0);
+        assert_eq!(opening.amount.map(|a| a.value()), Some(10000.0));
+        assert_eq!(opening.unit_price().map(|p| p.value()), Some(50.0));
+    }
+
+    #[test]
+    fn test_opening_balance_without_amount() {
+        let product_code = ProductCode::new("M001".to_string()).unwrap();
+        let quantity = Quantity::new(200.0).unwrap();
+
+        let opening = OpeningBalance::new(product_code, "材料A".to_string(), quantity, None);
+
+        assert!(opening.amount.is_none());
+        assert!(opening.unit_price().is_none());
+    }
+}
@@ -3,15 +3,18 @@ use crate::domain::value_objects::*;
 /// 生産エンティティ
 #[derive(Debug, Clone)]
 pub struct Production {
+    pub production_date: TransactionDate,
     pub product_code: ProductCode,
     pub quantity: Quantity,
     pub yield_rate: YieldRate,
     pub coagulant_cost: Amount,
     pub clay_treatment_cost: Amount,
+    pub plant: Option<String>, // 工場（歩留率の計算方法の選択に使用）
 }
 
 impl Production {
     pub fn new(
+        production_date: TransactionDate,
         product_code: ProductCode,
         quantity: Quantity,
         yield_rate: YieldRate,
@@ -19,13 +22,21 @@ impl Production {
         clay_treatment_cost: Amount,
     ) -> Self {
         Self {
+            production_date,
             product_code,
             quantity,
             yield_rate,
             coagulant_cost,
             clay_treatment_cost,
+            plant: None,
         }
     }
+
+    /// 工場を設定
+    pub fn with_plant(mut self, plant: String) -> Self {
+        self.plant = Some(plant);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -34,6 +45,7 @@ mod tests {
 
     #[test]
     fn test_production_creation() {
+        let production_date = TransactionDate::new("2024-01-15".to_string()).unwrap();
         let product_code = ProductCode::new("P001".to_string()).unwrap();
         let quantity = Quantity::new(1000.0).unwrap();
         let yield_rate = YieldRate::new(0.95).unwrap();
@@ -41,6 +53,7 @@ mod tests {
         let clay_treatment_cost = Amount::new(50.0).unwrap();
 
         let production = Production::new(
+            production_date,
             product_code.clone(),
             quantity,
             yield_rate,
@@ -48,9 +61,10 @@ mod tests {
             clay_treatment_cost,
         );
 
+        assert_eq!(production.production_date.value(), "2024-01-15");
         assert_eq!(production.product_code.value(), "P001");
         assert_eq!(production.quantity.value(), 1000.0);
-        assert_eq!(production.yield_rate.value(), 0.95);
+        assert_eq!(production.yield_rate, YieldRate::new(0.95).unwrap());
         assert_eq!(production.coagulant_cost.value(), 100.0);
         assert_eq!(production.clay_treatment_cost.value(), 50.0);
     }
@@ -3,26 +3,45 @@ use crate::domain::value_objects::*;
 /// 仕入エンティティ
 #[derive(Debug, Clone)]
 pub struct Purchase {
+    pub purchase_date: TransactionDate,
+    pub product_code: ProductCode,
     pub product_name: String,
-    pub unit_price: Amount,
+    pub unit_price: Amount, // 数量の単位あたりの単価
     pub quantity: Quantity,
     pub freight_code: FreightCode,
+    pub shipment_id: Option<String>, // 便番号（同じ便の仕入で運賃総額を配賦）
 }
 
 impl Purchase {
     pub fn new(
+        purchase_date: TransactionDate,
+        product_code: ProductCode,
         product_name: String,
         unit_price: Amount,
         quantity: Quantity,
         freight_code: FreightCode,
     ) -> Self {
         Self {
+            purchase_date,
+            product_code,
             product_name,
             unit_price,
             quantity,
             freight_code,
+            shipment_id: None,
         }
     }
+
+    /// 便番号を設定
+    pub fn with_shipment(mut self, shipment_id: String) -> Self {
+        self.shipment_id = Some(shipment_id);
+        self
+    }
+
+    /// 仕入の単位（単価・数量の単位）
+    pub fn unit(&self) -> Unit {
+        self.quantity.unit()
+    }
 }
 
 #[cfg(test)]
@@ -35,8 +54,19 @@ mod tests {
         let quantity = Quantity::new(50.0).unwrap();
         let freight_code = FreightCode::new("T01".to_string()).unwrap();
 
-        let purchase = Purchase::new("原材料A".to_string(), unit_price, quantity, freight_code);
+        let purchase_date = TransactionDate::new("2024-01-10".to_string()).unwrap();
+
+        let purchase = Purchase::new(
+            purchase_date,
+            ProductCode::new("M001".to_string()).unwrap(),
+            "原材料A".to_string(),
+            unit_price,
+            quantity,
+            freight_code,
+        );
 
+        assert_eq!(purchase.purchase_date.value(), "2024-01-10");
+        assert_eq!(purchase.product_code.value(), "M001");
         assert_eq!(purchase.product_name, "原材料A");
         assert_eq!(purchase.unit_price.value(), 100.0);
         assert_eq!(purchase.quantity.value(), 50.0);
@@ -49,7 +79,16 @@ mod tests {
         let quantity = Quantity::new(50.0).unwrap();
         let freight_code = FreightCode::new("150.5".to_string()).unwrap();
 
-        let purchase = Purchase::new("原材料B".to_string(), unit_price, quantity, freight_code);
+        let purchase_date = TransactionDate::new("2024-01-10".to_string()).unwrap();
+
+        let purchase = Purchase::new(
+            purchase_date,
+            ProductCode::new("M001".to_string()).unwrap(),
+            "原材料B".to_string(),
+            unit_price,
+            quantity,
+            freight_code,
+        );
 
         assert_eq!(purchase.product_name, "原材料B");
         assert!(purchase.freight_code.is_direct_price());
@@ -0,0 +1,78 @@
+use crate::domain::error::{DomainError, Result};
+use crate::domain::value_objects::*;
+
+/// 単位換算マスタエンティティ（1単位あたりの kg）
+#[derive(Debug, Clone)]
+pub struct UnitConversion {
+    pub product_code: Option<ProductCode>, // 空欄の場合は全材料に共通の換算
+    pub unit: Unit,
+    pub kg_per_unit: Quantity,
+}
+
+impl UnitConversion {
+    pub fn new(
+        product_code: Option<ProductCode>,
+        unit: Unit,
+        kg_per_unit: Quantity,
+    ) -> Result<Self> {
+        if unit == Unit::Kilogram {
+            return Err(DomainError::invalid_format("単位", unit.as_str(), "t, 袋"));
+        }
+        if kg_per_unit.decimal().is_zero() {
+            return Err(DomainError::invalid_format(
+                "換算kg",
+                "0",
+                "0より大きい数値",
+            ));
+        }
+        Ok(Self {
+            product_code,
+            unit,
+            kg_per_unit,
+        })
+    }
+
+    /// 指定した材料・単位に適用する換算か
+    pub fn applies_to(&self, product_code: &ProductCode, unit: Unit) -> bool {
+        self.unit == unit && self.product_code.as_ref().is_none_or(|c| c == product_code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(value: &str) -> ProductCode {
+        ProductCode::new(value.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_unit_conversion_applies_to_material() {
+        let conversion =
+            UnitConversion::new(Some(code("M001")), Unit::Bag, Quantity::new(25.0).unwrap())
+                .unwrap();
+
+        assert!(conversion.applies_to(&code("M001"), Unit::Bag));
+        assert!(!conversion.applies_to(&code("M002"), Unit::Bag));
+        assert!(!conversion.applies_to(&code("M001"), Unit::Tonne));
+    }
+
+    #[test]
+    fn test_unit_conversion_without_code_applies_to_all() {
+        let conversion =
+            UnitConversion::new(None, Unit::Bag, Quantity::new(20.0).unwrap()).unwrap();
+        assert!(conversion.applies_to(&code("M002"), Unit::Bag));
+    }
+
+    #[test]
+    fn test_unit_conversion_zero_weight() {
+        let result = UnitConversion::new(None, Unit::Bag, Quantity::zero());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_unit_conversion_kilogram_rejected() {
+        let result = UnitConversion::new(None, Unit::Kilogram, Quantity::new(1.0).unwrap());
+        assert!(result.is_err());
+    }
+}
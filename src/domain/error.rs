@@ -0,0 +1,318 @@
+use super::diagnostics::Diagnostic;
+use std::fmt;
+use thiserror::Error;
+
+/// ドメイン層の結果型
+pub type Result<T> = std::result::Result<T, DomainError>;
+
+/// マスタデータの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MasterKind {
+    Formula,       // 配合マスタ
+    Purchase,      // 仕入データ
+    FreightMaster, // 運賃マスタ
+    FreightRate,   // 運賃表（地区割増）
+}
+
+impl MasterKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            MasterKind::Formula => "配合マスタ",
+            MasterKind::Purchase => "仕入データ",
+            MasterKind::FreightMaster => "運賃マスタ",
+            MasterKind::FreightRate => "運賃表",
+        }
+    }
+
+    /// マスタを引くコードの名称
+    pub fn code_label(&self) -> &str {
+        match self {
+            MasterKind::Formula | MasterKind::Purchase => "商品コード",
+            MasterKind::FreightMaster => "運賃コード",
+            MasterKind::FreightRate => "パターン名 / 地区",
+        }
+    }
+}
+
+impl fmt::Display for MasterKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// エラーの分類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    MissingMaster, // マスタデータの不足
+    Validation,    // 値の検証エラー
+    Schema,        // シート・列の構成エラー
+}
+
+impl ErrorCategory {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ErrorCategory::MissingMaster => "マスタ不足",
+            ErrorCategory::Validation => "検証エラー",
+            ErrorCategory::Schema => "シート構成エラー",
+        }
+    }
+}
+
+/// ドメインエラー
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum DomainError {
+    // ---- マスタデータの不足 ----
+    #[error("{master}に{} '{code}' が見つかりません", .master.code_label())]
+    MasterNotFound { master: MasterKind, code: String },
+
+    #[error(
+        "{master}に{} '{code}' の {date} 時点で有効な期間がありません\n  登録済みの期間: {}",
+        .master.code_label(),
+        .periods.join(", ")
+    )]
+    NoValidPeriod {
+        master: MasterKind,
+        code: String,
+        date: String,
+        periods: Vec<String>,
+    },
+
+    #[error("単位換算マスタに商品コード '{code}' の単位 '{unit}' の換算が見つかりません")]
+    UnitConversionNotFound { code: String, unit: String },
+
+    #[error("仕入データに商品コード '{code}' の {date} 以前の仕入が見つかりません")]
+    NoPurchaseBefore { code: String, date: String },
+
+    #[error(
+        "商品コード '{code}' の {date} 時点の単価を計算できません（{method}: 仕入・期首在庫が見つかりません）"
+    )]
+    PriceNotFound {
+        code: String,
+        date: String,
+        method: String,
+    },
+
+    // ---- 値の検証エラー ----
+    #[error("{field}が空です")]
+    Empty { field: String },
+
+    #[error("{field}が負の値です: {value}")]
+    Negative { field: String, value: f64 },
+
+    #[error("{field}は{min}から{max}の範囲である必要があります: {value}")]
+    OutOfRange {
+        field: String,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+
+    #[error("{field}の形式が不正です: '{value}'\n  有効な形式: {expected}")]
+    InvalidFormat {
+        field: String,
+        value: String,
+        expected: String,
+    },
+
+    #[error("{field}の範囲が不正です: {min} ～ {max}")]
+    InvalidRange { field: String, min: f64, max: f64 },
+
+    #[error(
+        "{master}の{} '{code}' の有効終了日 {valid_to} が有効開始日 {valid_from} より前です",
+        .master.code_label()
+    )]
+    InvalidPeriod {
+        master: MasterKind,
+        code: String,
+        valid_from: String,
+        valid_to: String,
+    },
+
+    #[error("単位の異なる数量は計算できません: {expected} と {actual}")]
+    IncompatibleUnits { expected: String, actual: String },
+
+    #[error("便番号 '{shipment}' に異なる運賃総額が入力されています: {existing} と {conflicting}")]
+    ConflictingShipmentFreight {
+        shipment: String,
+        existing: f64,
+        conflicting: f64,
+    },
+
+    #[error(
+        "商品コード '{code}' の {date} の仕入数量が0のため、ロット運賃 {freight} を配賦できません"
+    )]
+    ZeroQuantityLotFreight {
+        code: String,
+        date: String,
+        freight: f64,
+    },
+
+    #[error("配合マスタに循環参照があります: {}", .path.join(" → "))]
+    FormulaCycle { path: Vec<String> },
+
+    #[error(
+        "配合マスタの検証で {count} 件の問題が見つかったため処理を中止しました（strictモード）"
+    )]
+    FormulaValidationFailed { count: usize },
+
+    #[error("{0}")]
+    InvalidCell(Diagnostic),
+
+    // ---- シート・列の構成エラー ----
+    #[error("{sheet}シートを読み取れません: {reason}")]
+    SheetUnreadable { sheet: String, reason: String },
+
+    #[error("入力データ {path} を開けませんでした: {reason}")]
+    InputUnreadable { path: String, reason: String },
+
+    #[error("データベースの{table}を読み取れません: {reason}")]
+    TableUnreadable { table: String, reason: String },
+
+    #[error("{sheet}シートが空です")]
+    EmptySheet { sheet: String },
+
+    #[error("{sheet}シートに必須カラムが見つかりません: {}", .columns.join(", "))]
+    MissingColumns { sheet: String, columns: Vec<String> },
+}
+
+impl DomainError {
+    pub fn empty(field: &str) -> Self {
+        DomainError::Empty {
+            field: field.to_string(),
+        }
+    }
+
+    pub fn negative(field: &str, value: f64) -> Self {
+        DomainError::Negative {
+            field: field.to_string(),
+            value,
+        }
+    }
+
+    pub fn invalid_format(field: &str, value: &str, expected: &str) -> Self {
+        DomainError::InvalidFormat {
+            field: field.to_string(),
+            value: value.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+
+    pub fn missing_columns(sheet: &str, columns: &[&str]) -> Self {
+        DomainError::MissingColumns {
+            sheet: sheet.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            DomainError::MasterNotFound { .. }
+            | DomainError::NoValidPeriod { .. }
+            | DomainError::UnitConversionNotFound { .. }
+            | DomainError::NoPurchaseBefore { .. }
+            | DomainError::PriceNotFound { .. } => ErrorCategory::MissingMaster,
+            DomainError::SheetUnreadable { .. }
+            | DomainError::InputUnreadable { .. }
+            | DomainError::TableUnreadable { .. }
+            | DomainError::EmptySheet { .. }
+            | DomainError::MissingColumns { .. } => ErrorCategory::Schema,
+            _ => ErrorCategory::Validation,
+        }
+    }
+
+    /// エラーの発生したシート
+    pub fn sheet(&self) -> Option<&str> {
+        match self {
+            DomainError::InvalidCell(diagnostic) => Some(&diagnostic.sheet),
+            DomainError::SheetUnreadable { sheet, .. }
+            | DomainError::EmptySheet { sheet }
+            | DomainError::MissingColumns { sheet, .. } => Some(sheet),
+            _ => None,
+        }
+    }
+
+    /// エラーの発生した行（Excelの行番号）
+    pub fn row(&self) -> Option<usize> {
+        match self {
+            DomainError::InvalidCell(diagnostic) => diagnostic.row,
+            _ => None,
+        }
+    }
+
+    /// エラーの発生した列
+    pub fn column(&self) -> Option<String> {
+        match self {
+            DomainError::InvalidCell(diagnostic) => diagnostic.column.clone(),
+            DomainError::MissingColumns { columns, .. } => Some(columns.join(", ")),
+            DomainError::Empty { field }
+            | DomainError::Negative { field, .. }
+            | DomainError::OutOfRange { field, .. }
+            | DomainError::InvalidFormat { field, .. }
+            | DomainError::InvalidRange { field, .. } => Some(field.clone()),
+            _ => None,
+        }
+    }
+
+    /// エラーの対象コード（商品コード・運賃コード・便番号）
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            DomainError::MasterNotFound { code, .. }
+            | DomainError::NoValidPeriod { code, .. }
+            | DomainError::UnitConversionNotFound { code, .. }
+            | DomainError::NoPurchaseBefore { code, .. }
+            | DomainError::PriceNotFound { code, .. }
+            | DomainError::InvalidPeriod { code, .. }
+            | DomainError::ConflictingShipmentFreight { shipment: code, .. } => Some(code),
+            _ => None,
+        }
+    }
+}
+
+impl From<Diagnostic> for DomainError {
+    fn from(diagnostic: Diagnostic) -> Self {
+        DomainError::InvalidCell(diagnostic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_master_not_found_message_and_fields() {
+        let error = DomainError::MasterNotFound {
+            master: MasterKind::FreightMaster,
+            code: "T01".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "運賃マスタに運賃コード 'T01' が見つかりません"
+        );
+        assert_eq!(error.category(), ErrorCategory::MissingMaster);
+        assert_eq!(error.code(), Some("T01"));
+        assert_eq!(error.sheet(), None);
+    }
+
+    #[test]
+    fn test_invalid_cell_carries_location() {
+        let error = DomainError::from(
+            Diagnostic::new("【入庫】仕入", "仕入単価が数値ではありません: 'abc'")
+                .with_row(3)
+                .with_column("仕入単価"),
+        );
+        assert_eq!(error.category(), ErrorCategory::Validation);
+        assert_eq!(error.sheet(), Some("【入庫】仕入"));
+        assert_eq!(error.row(), Some(3));
+        assert_eq!(error.column().as_deref(), Some("仕入単価"));
+    }
+
+    #[test]
+    fn test_missing_columns_is_schema_error() {
+        let error = DomainError::missing_columns("【入庫】生産", &["歩留率", "凝集剤"]);
+        assert_eq!(
+            error.to_string(),
+            "【入庫】生産シートに必須カラムが見つかりません: 歩留率, 凝集剤"
+        );
+        assert_eq!(error.category(), ErrorCategory::Schema);
+    }
+}
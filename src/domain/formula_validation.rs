@@ -0,0 +1,293 @@
+use super::entities::*;
+use super::error::{DomainError, Result};
+use std::collections::HashMap;
+
+/// 配合マスタ検証の設定
+#[derive(Debug, Clone, Default)]
+pub struct FormulaValidationPolicy {
+    pub min_total_ratio: Option<f64>, // 消費比率合計の下限（未設定の場合は検証しない）
+    pub max_total_ratio: Option<f64>, // 消費比率合計の上限（未設定の場合は検証しない）
+    pub strict: bool,                 // 問題がある場合に処理を中止するか
+}
+
+impl FormulaValidationPolicy {
+    pub fn new(
+        min_total_ratio: Option<f64>,
+        max_total_ratio: Option<f64>,
+        strict: bool,
+    ) -> Result<Self> {
+        let negative = min_total_ratio.is_some_and(|min| min < 0.0);
+        let inverted =
+            matches!((min_total_ratio, max_total_ratio), (Some(min), Some(max)) if max < min);
+        if negative || inverted {
+            return Err(DomainError::InvalidRange {
+                field: "消費比率合計".to_string(),
+                min: min_total_ratio.unwrap_or_default(),
+                max: max_total_ratio.unwrap_or_default(),
+            });
+        }
+        Ok(Self {
+            min_total_ratio,
+            max_total_ratio,
+            strict,
+        })
+    }
+
+    /// 消費比率合計が範囲外か
+    fn is_out_of_range(&self, total: f64) -> bool {
+        self.min_total_ratio.is_some_and(|min| total < min)
+            || self.max_total_ratio.is_some_and(|max| total > max)
+    }
+
+    /// 範囲の表示用文字列
+    fn range_label(&self) -> String {
+        format!(
+            "{}～{}",
+            self.min_total_ratio
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+            self.max_total_ratio
+                .map(|v| v.to_string())
+                .unwrap_or_default()
+        )
+    }
+}
+
+/// 配合マスタの問題の種類
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormulaIssueKind {
+    TotalRatioOutOfRange { total: f64 }, // 消費比率合計が範囲外
+    DuplicateMaterial { count: usize },  // 同じ材料の行が重複
+    ConflictingRatio { existing: f64, conflicting: f64 }, // 同じ材料に異なる消費比率
+    ZeroRatio,                           // 消費比率が0の行
+}
+
+impl FormulaIssueKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            FormulaIssueKind::TotalRatioOutOfRange { .. } => "消費比率合計が範囲外",
+            FormulaIssueKind::DuplicateMaterial { .. } => "材料の重複",
+            FormulaIssueKind::ConflictingRatio { .. } => "消費比率の不一致",
+            FormulaIssueKind::ZeroRatio => "消費比率が0",
+        }
+    }
+}
+
+/// 配合マスタの問題
+#[derive(Debug, Clone)]
+pub struct FormulaIssue {
+    pub product_code: String,
+    pub period: String,                // 版の有効期間
+    pub material_code: Option<String>, // 材料単位の問題の場合
+    pub kind: FormulaIssueKind,
+    pub message: String,
+}
+
+/// 配合マスタ検証ドメインサービス
+pub struct FormulaValidationService;
+
+impl FormulaValidationService {
+    /// 版ごとに消費比率合計・材料の重複・消費比率の不一致・消費比率0の行を検証
+    pub fn validate(
+        versions: &[FormulaVersion],
+        policy: &FormulaValidationPolicy,
+    ) -> Vec<FormulaIssue> {
+        let mut issues = Vec::new();
+
+        for version in versions {
+            let product_code = version.product_code.value().to_string();
+            let period = version.period_label();
+
+            let total: f64 = version
+                .entries
+                .iter()
+                .map(|e| e.consumption_ratio.value())
+                .sum();
+            if policy.is_out_of_range(total) {
+                issues.push(FormulaIssue {
+                    product_code: product_code.clone(),
+                    period: period.clone(),
+                    material_code: None,
+                    kind: FormulaIssueKind::TotalRatioOutOfRange { total },
+                    message: format!(
+                        "消費比率の合計 {} が範囲 {} の外です",
+                        total,
+                        policy.range_label()
+                    ),
+                });
+            }
+
+            // 重複として除いた行の材料ごとの行数（登録順を保つ）
+            let mut counts: Vec<(String, usize)> = Vec::new();
+            let mut positions: HashMap<String, usize> = HashMap::new();
+            for material_code in &version.duplicates {
+                let code = material_code.value().to_string();
+                match positions.get(&code) {
+                    Some(&index) => counts[index].1 += 1,
+                    None => {
+                        positions.insert(code.clone(), counts.len());
+                        counts.push((code, 2));
+                    }
+                }
+            }
+            for (material_code, count) in counts {
+                issues.push(FormulaIssue {
+                    product_code: product_code.clone(),
+                    period: period.clone(),
+                    material_code: Some(material_code.clone()),
+                    kind: FormulaIssueKind::DuplicateMaterial { count },
+                    message: format!("同じ材料の行が {} 行あります", count),
+                });
+            }
+
+            // 先に登録された行の消費比率を計算に使用する
+            for conflict in &version.conflicts {
+                let existing = version
+                    .entries
+                    .iter()
+                    .find(|e| e.material_code == conflict.material_code)
+                    .map(|e| e.consumption_ratio.value())
+                    .unwrap_or_default();
+                let conflicting = conflict.consumption_ratio.value();
+                issues.push(FormulaIssue {
+                    product_code: product_code.clone(),
+                    period: period.clone(),
+                    material_code: Some(conflict.material_code.value().to_string()),
+                    kind: FormulaIssueKind::ConflictingRatio {
+                        existing,
+                        conflicting,
+                    },
+                    message: format!(
+                        "異なる消費比率が登録されています: {} と {}（{} を使用）",
+                        existing, conflicting, existing
+                    ),
+                });
+            }
+
+            for entry in version
+                .entries
+                .iter()
+                .filter(|e| e.consumption_ratio.value() == 0.0)
+            {
+                issues.push(FormulaIssue {
+                    product_code: product_code.clone(),
+                    period: period.clone(),
+                    material_code: Some(entry.material_code.value().to_string()),
+                    kind: FormulaIssueKind::ZeroRatio,
+                    message: "消費比率が0です".to_string(),
+                });
+            }
+        }
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::value_objects::*;
+
+    fn version(product_code: &str, entries: &[(&str, f64)]) -> FormulaVersion {
+        let mut version = FormulaVersion::new(
+            ProductCode::new(product_code.to_string()).unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+        for (material_code, ratio) in entries {
+            version.add_entry(FormulaEntry::new(
+                ProductCode::new(material_code.to_string()).unwrap(),
+                ConsumptionRatio::new(*ratio).unwrap(),
+            ));
+        }
+        version
+    }
+
+    #[test]
+    fn test_valid_formula_has_no_issues() {
+        let versions = vec![version("P001", &[("M001", 0.6), ("M002", 0.4)])];
+        let issues =
+            FormulaValidationService::validate(&versions, &FormulaValidationPolicy::default());
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_default_policy_does_not_check_total_ratio() {
+        // 消費比率の合計が 0.05 の一般的な配合は既定では警告しない
+        let versions = vec![version("P001", &[("M001", 0.03), ("M002", 0.02)])];
+        let issues =
+            FormulaValidationService::validate(&versions, &FormulaValidationPolicy::default());
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_total_ratio_out_of_range() {
+        // 0.03 のつもりで 3.0 と入力
+        let versions = vec![version("P001", &[("M001", 0.02), ("M002", 3.0)])];
+        let policy = FormulaValidationPolicy::new(Some(0.01), Some(0.2), false).unwrap();
+        let issues = FormulaValidationService::validate(&versions, &policy);
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].product_code, "P001");
+        assert_eq!(
+            issues[0].kind,
+            FormulaIssueKind::TotalRatioOutOfRange { total: 3.02 }
+        );
+    }
+
+    #[test]
+    fn test_total_ratio_only_upper_bound() {
+        let versions = vec![
+            version("P001", &[("M001", 0.03)]),
+            version("P002", &[("M001", 1.5)]),
+        ];
+        let policy = FormulaValidationPolicy::new(None, Some(1.0), false).unwrap();
+        let issues = FormulaValidationService::validate(&versions, &policy);
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].product_code, "P002");
+    }
+
+    #[test]
+    fn test_duplicate_and_zero_ratio_lines() {
+        let versions = vec![version(
+            "P001",
+            &[("M001", 0.5), ("M001", 0.5), ("M002", 0.0)],
+        )];
+        let policy = FormulaValidationPolicy::default();
+        let issues = FormulaValidationService::validate(&versions, &policy);
+
+        assert_eq!(issues.len(), 2);
+        assert_eq!(
+            issues[0].kind,
+            FormulaIssueKind::DuplicateMaterial { count: 2 }
+        );
+        assert_eq!(issues[0].material_code.as_deref(), Some("M001"));
+        assert_eq!(issues[1].kind, FormulaIssueKind::ZeroRatio);
+        assert_eq!(issues[1].material_code.as_deref(), Some("M002"));
+    }
+
+    #[test]
+    fn test_conflicting_ratio_is_reported() {
+        let versions = vec![version("P001", &[("M001", 0.5), ("M001", 0.4)])];
+        let issues =
+            FormulaValidationService::validate(&versions, &FormulaValidationPolicy::default());
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].kind,
+            FormulaIssueKind::ConflictingRatio {
+                existing: 0.5,
+                conflicting: 0.4
+            }
+        );
+        assert_eq!(issues[0].material_code.as_deref(), Some("M001"));
+    }
+
+    #[test]
+    fn test_policy_invalid_range() {
+        assert!(FormulaValidationPolicy::new(Some(1.2), Some(0.8), false).is_err());
+        assert!(FormulaValidationPolicy::new(Some(-0.1), None, false).is_err());
+    }
+}
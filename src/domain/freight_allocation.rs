@@ -0,0 +1,200 @@
+use super::entities::*;
+use super::error::{DomainError, Result};
+use super::value_objects::*;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// 便単位の運賃配賦ドメインサービス
+///
+/// 運賃総額が入力された便の運賃を、同じ便番号の仕入ロットに配賦基準で按分する。
+/// 各ロットの運賃額は仕入数量に対する消費数量の割合で材料消費に負担させる。
+pub struct FreightAllocationService;
+
+impl FreightAllocationService {
+    /// 便の運賃総額を仕入ロットに配賦
+    ///
+    /// 便番号のない仕入は1行で1便とする。端数は便の最後のロットで調整し、配賦額の合計を運賃総額に一致させる。
+    pub fn allocate(
+        purchases: Vec<Purchase>,
+        method: FreightAllocationMethod,
+    ) -> Result<Vec<Purchase>> {
+        // 便番号ごとの運賃総額（行によって入力の有無が異なるため先に集める）
+        let mut totals: BTreeMap<String, Amount> = BTreeMap::new();
+        for purchase in &purchases {
+            let (Some(shipment), FreightCode::LotFreight(total)) =
+                (&purchase.shipment_id, &purchase.freight_code)
+            else {
+                continue;
+            };
+            match totals.get(shipment) {
+                Some(existing) if existing != total => {
+                    return Err(DomainError::ConflictingShipmentFreight {
+                        shipment: shipment.clone(),
+                        existing: existing.value(),
+                        conflicting: total.value(),
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    totals.insert(shipment.clone(), *total);
+                }
+            }
+        }
+
+        let mut purchases = purchases;
+        for (shipment, total) in totals {
+            let members: Vec<usize> = purchases
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| p.shipment_id.as_deref() == Some(shipment.as_str()))
+                .map(|(idx, _)| idx)
+                .collect();
+            let weights: Vec<Decimal> = members
+                .iter()
+                .map(|&idx| Self::basis(&purchases[idx], method))
+                .collect();
+
+            for (idx, share) in members.iter().zip(Self::shares(&total, &weights)?) {
+                purchases[*idx].freight_code = FreightCode::LotFreight(share);
+            }
+        }
+
+        Ok(purchases)
+    }
+
+    /// 配賦基準の値
+    fn basis(purchase: &Purchase, method: FreightAllocationMethod) -> Decimal {
+        match method {
+            FreightAllocationMethod::Weight => purchase.quantity.decimal(),
+            FreightAllocationMethod::Value => purchase
+                .unit_price
+                .multiply(purchase.quantity.decimal())
+                .decimal(),
+            FreightAllocationMethod::Equal => Decimal::ONE,
+        }
+    }
+
+    /// 運賃総額を基準値の比で按分（基準値の合計が0の場合は均等割り）
+    fn shares(total: &Amount, weights: &[Decimal]) -> Result<Vec<Amount>> {
+        let sum: Decimal = weights.iter().sum();
+        let count = Decimal::from(weights.len());
+
+        let mut shares = Vec::with_capacity(weights.len());
+        let mut allocated = Amount::zero();
+        for (i, weight) in weights.iter().enumerate() {
+            let share = if i + 1 == weights.len() {
+                Amount::from_decimal(total.decimal() - allocated.decimal())?
+            } else if sum.is_zero() {
+                total.multiply(Decimal::ONE / count)
+            } else {
+                total.multiply(weight / sum)
+            };
+            allocated = allocated.add(&share);
+            shares.push(share);
+        }
+        Ok(shares)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn purchase(code: &str, unit_price: f64, quantity: f64, freight: FreightCode) -> Purchase {
+        Purchase::new(
+            TransactionDate::new("2026-01-10".to_string()).unwrap(),
+            ProductCode::new(code.to_string()).unwrap(),
+            format!("材料{}", code),
+            Amount::new(unit_price).unwrap(),
+            Quantity::new(quantity).unwrap(),
+            freight,
+        )
+        .with_shipment("S001".to_string())
+    }
+
+    fn lot_freight(purchase: &Purchase) -> f64 {
+        match purchase.freight_code {
+            FreightCode::LotFreight(amount) => amount.value(),
+            _ => panic!("運賃が配賦されていません"),
+        }
+    }
+
+    fn shipment() -> Vec<Purchase> {
+        // 便 S001 の運賃総額 9,000円（1行目にのみ入力）
+        vec![
+            purchase(
+                "M001",
+                10.0,
+                1000.0,
+                FreightCode::LotFreight(Amount::new(9000.0).unwrap()),
+            ),
+            purchase("M002", 40.0, 500.0, FreightCode::DirectPrice(0.0)),
+        ]
+    }
+
+    #[test]
+    fn test_allocate_by_weight() {
+        // 1,000kg : 500kg → 6,000円 : 3,000円
+        let allocated =
+            FreightAllocationService::allocate(shipment(), FreightAllocationMethod::Weight)
+                .unwrap();
+        assert_eq!(lot_freight(&allocated[0]), 6000.0);
+        assert_eq!(lot_freight(&allocated[1]), 3000.0);
+    }
+
+    #[test]
+    fn test_allocate_by_value() {
+        // 10,000円 : 20,000円 → 3,000円 : 6,000円
+        let allocated =
+            FreightAllocationService::allocate(shipment(), FreightAllocationMethod::Value).unwrap();
+        assert_eq!(lot_freight(&allocated[0]), 3000.0);
+        assert_eq!(lot_freight(&allocated[1]), 6000.0);
+    }
+
+    #[test]
+    fn test_allocate_equally_keeps_total() {
+        // 1,000円を3ロットで均等割り → 333.3333 + 333.3333 + 333.3334
+        let mut purchases = shipment();
+        purchases[0].freight_code = FreightCode::LotFreight(Amount::new(1000.0).unwrap());
+        purchases.push(purchase("M003", 5.0, 100.0, FreightCode::DirectPrice(0.0)));
+
+        let allocated =
+            FreightAllocationService::allocate(purchases, FreightAllocationMethod::Equal).unwrap();
+        let total: f64 = allocated.iter().map(lot_freight).sum();
+        assert_eq!(lot_freight(&allocated[0]), 333.3333);
+        assert_eq!(lot_freight(&allocated[2]), 333.3334);
+        assert!((total - 1000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_purchase_without_shipment_unchanged() {
+        let mut purchases = shipment();
+        purchases.push(Purchase::new(
+            TransactionDate::new("2026-01-10".to_string()).unwrap(),
+            ProductCode::new("M009".to_string()).unwrap(),
+            "材料M009".to_string(),
+            Amount::new(10.0).unwrap(),
+            Quantity::new(10.0).unwrap(),
+            FreightCode::DirectPrice(5.0),
+        ));
+
+        let allocated =
+            FreightAllocationService::allocate(purchases, FreightAllocationMethod::Weight).unwrap();
+        assert!(matches!(
+            allocated[2].freight_code,
+            FreightCode::DirectPrice(5.0)
+        ));
+    }
+
+    #[test]
+    fn test_conflicting_shipment_totals() {
+        let mut purchases = shipment();
+        purchases[1].freight_code = FreightCode::LotFreight(Amount::new(8000.0).unwrap());
+
+        let result = FreightAllocationService::allocate(purchases, FreightAllocationMethod::Weight);
+        assert!(matches!(
+            result,
+            Err(DomainError::ConflictingShipmentFreight { .. })
+        ));
+    }
+}
@@ -0,0 +1,506 @@
+use super::entities::*;
+use super::error::{DomainError, Result};
+use super::repositories::*;
+use super::value_objects::*;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// 材料単価の計算戦略
+pub trait UnitPriceStrategy {
+    /// 仕入履歴（仕入日の昇順）・材料出庫・期首在庫から、指定日時点の単価を計算（計算できない場合はNone）
+    fn unit_price(
+        &self,
+        history: &[Purchase],
+        issues: &[InventoryTransaction],
+        opening: Option<&OpeningBalance>,
+        as_of: &TransactionDate,
+    ) -> Option<Amount>;
+}
+
+/// 最終仕入原価法: 指定日以前で最も新しい仕入の単価
+///
+/// 指定日以前の仕入がない場合は期首単価を使う。
+pub struct LatestPriceStrategy;
+
+impl UnitPriceStrategy for LatestPriceStrategy {
+    fn unit_price(
+        &self,
+        history: &[Purchase],
+        _issues: &[InventoryTransaction],
+        opening: Option<&OpeningBalance>,
+        as_of: &TransactionDate,
+    ) -> Option<Amount> {
+        history
+            .iter()
+            .rev()
+            .find(|p| p.purchase_date <= *as_of)
+            .map(|p| p.unit_price)
+            .or_else(|| opening?.unit_price())
+    }
+}
+
+/// 移動平均法: 期首在庫から指定日までの受入・払出を日付順にたどった平均単価
+///
+/// 受入のたびに平均単価を更新し、払出はその時点の平均単価で在庫金額から差し引く。
+/// 同日の受入と払出は受入を先に処理する。
+pub struct MovingAverageStrategy;
+
+impl UnitPriceStrategy for MovingAverageStrategy {
+    fn unit_price(
+        &self,
+        history: &[Purchase],
+        issues: &[InventoryTransaction],
+        opening: Option<&OpeningBalance>,
+        as_of: &TransactionDate,
+    ) -> Option<Amount> {
+        let mut quantity = Decimal::ZERO;
+        let mut value = Decimal::ZERO;
+        if let Some(opening) = opening {
+            quantity = opening.quantity.decimal();
+            value = opening_value(opening, history, as_of)?.decimal();
+        }
+
+        let receipts = history.iter().filter(|p| p.purchase_date <= *as_of);
+        let mut issues = issues.iter().filter(|t| t.date <= *as_of).peekable();
+        let mut last_receipt = None;
+
+        for receipt in receipts {
+            // 受入日より前の払出を先に処理
+            while let Some(issue) = issues.next_if(|t| t.date < receipt.purchase_date) {
+                (quantity, value) = issue_at_average(quantity, value, issue.quantity.decimal());
+            }
+            quantity += receipt.quantity.decimal();
+            value += receipt
+                .unit_price
+                .multiply(receipt.quantity.decimal())
+                .decimal();
+            last_receipt = Some(receipt.unit_price);
+        }
+        for issue in issues {
+            (quantity, value) = issue_at_average(quantity, value, issue.quantity.decimal());
+        }
+
+        if quantity > Decimal::ZERO {
+            Amount::from_decimal(value).ok()?.divide_by(quantity)
+        } else {
+            // 在庫がない場合は最後の受入単価（期首在庫のみの場合は期首単価）
+            last_receipt.or_else(|| opening?.unit_price())
+        }
+    }
+}
+
+/// 払出数量をその時点の平均単価で差し引いた (数量, 金額)
+///
+/// 在庫数量を超える払出は在庫を0とする。
+fn issue_at_average(quantity: Decimal, value: Decimal, issued: Decimal) -> (Decimal, Decimal) {
+    if issued >= quantity {
+        return (Decimal::ZERO, Decimal::ZERO);
+    }
+    let issued_value = Amount::from_decimal(value)
+        .ok()
+        .and_then(|v| v.divide_by(quantity))
+        .map(|average| average.multiply(issued).decimal())
+        .unwrap_or_default();
+    (quantity - issued, value - issued_value)
+}
+
+/// 総平均法: 期首在庫と期間中（読み込んだ仕入データ全体）の受入の加重平均単価
+///
+/// 指定日によらず期間全体の仕入で計算する（期首金額が未入力の場合は期間の最初の仕入単価で評価）。
+pub struct PeriodicAverageStrategy;
+
+impl UnitPriceStrategy for PeriodicAverageStrategy {
+    fn unit_price(
+        &self,
+        history: &[Purchase],
+        _issues: &[InventoryTransaction],
+        opening: Option<&OpeningBalance>,
+        _as_of: &TransactionDate,
+    ) -> Option<Amount> {
+        let mut total_quantity = Decimal::ZERO;
+        let mut total_value = Amount::zero();
+
+        if let Some(opening) = opening {
+            let opening_value = match opening.amount {
+                Some(amount) => amount,
+                None => history
+                    .first()?
+                    .unit_price
+                    .multiply(opening.quantity.decimal()),
+            };
+            total_quantity += opening.quantity.decimal();
+            total_value = total_value.add(&opening_value);
+        }
+
+        for receipt in history {
+            total_quantity += receipt.quantity.decimal();
+            total_value = total_value.add(&receipt.unit_price.multiply(receipt.quantity.decimal()));
+        }
+
+        if total_quantity > Decimal::ZERO {
+            total_value.divide_by(total_quantity)
+        } else {
+            history.last().map(|p| p.unit_price)
+        }
+    }
+}
+
+/// 期首金額（未入力の場合は指定日以前の最初の仕入単価で評価、該当する仕入がなければNone）
+fn opening_value(
+    opening: &OpeningBalance,
+    history: &[Purchase],
+    as_of: &TransactionDate,
+) -> Option<Amount> {
+    match opening.amount {
+        Some(amount) => Some(amount),
+        None => history
+            .iter()
+            .find(|p| p.purchase_date <= *as_of)
+            .map(|p| p.unit_price.multiply(opening.quantity.decimal())),
+    }
+}
+
+/// 単価計算方法から戦略を生成
+pub fn strategy_for(method: PricingMethod) -> Box<dyn UnitPriceStrategy> {
+    match method {
+        PricingMethod::LatestPrice => Box::new(LatestPriceStrategy),
+        PricingMethod::MovingAverage => Box::new(MovingAverageStrategy),
+        PricingMethod::PeriodicAverage => Box::new(PeriodicAverageStrategy),
+    }
+}
+
+/// 単価計算方法を適用する仕入リポジトリ
+///
+/// 商品名・運賃コードなどは指定日以前の最新仕入から取り、単価のみ戦略で置き換える。
+/// 指定日以前の仕入がない材料は期首在庫から仕入を作る。
+/// 移動平均法の払出は配合マスタから求めた材料出庫（全生産分）を使うため、単価は材料費計算の順序によらない。
+pub struct PricedPurchaseRepository<'a, P>
+where
+    P: PurchaseRepository,
+{
+    inner: &'a P,
+    method: PricingMethod,
+    strategy: Box<dyn UnitPriceStrategy>,
+    opening_balances: HashMap<String, OpeningBalance>,
+    issues: HashMap<String, Vec<InventoryTransaction>>, // 商品コードごとの材料出庫（日付順）
+}
+
+impl<'a, P> PricedPurchaseRepository<'a, P>
+where
+    P: PurchaseRepository,
+{
+    pub fn new(inner: &'a P, method: PricingMethod) -> Self {
+        Self {
+            inner,
+            method,
+            strategy: strategy_for(method),
+            opening_balances: HashMap::new(),
+            issues: HashMap::new(),
+        }
+    }
+
+    /// 期首在庫を設定
+    pub fn with_opening_balances(mut self, opening_balances: Vec<OpeningBalance>) -> Self {
+        self.opening_balances = opening_balances
+            .into_iter()
+            .map(|o| (o.product_code.value().to_string(), o))
+            .collect();
+        self
+    }
+
+    /// 移動平均法の払出とする材料出庫を設定
+    pub fn with_issues(mut self, issues: Vec<InventoryTransaction>) -> Self {
+        self.issues = HashMap::new();
+        for issue in issues {
+            self.issues
+                .entry(issue.product_code.value().to_string())
+                .or_default()
+                .push(issue);
+        }
+        for product_issues in self.issues.values_mut() {
+            product_issues.sort_by(|a, b| a.date.cmp(&b.date));
+        }
+        self
+    }
+}
+
+impl<'a, P> PurchaseRepository for PricedPurchaseRepository<'a, P>
+where
+    P: PurchaseRepository,
+{
+    fn find_price_as_of(
+        &self,
+        product_code: &ProductCode,
+        date: &TransactionDate,
+    ) -> Result<Purchase> {
+        let opening = self.opening_balances.get(product_code.value());
+        let history = match self.inner.find_history(product_code) {
+            Ok(history) => history,
+            // 期首在庫のみの材料は仕入履歴なしとして扱う
+            Err(DomainError::MasterNotFound { .. }) if opening.is_some() => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        let issues = self
+            .issues
+            .get(product_code.value())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let unit_price = self
+            .strategy
+            .unit_price(&history, issues, opening, date)
+            .ok_or_else(|| DomainError::PriceNotFound {
+                code: product_code.value().to_string(),
+                date: date.value().to_string(),
+                method: self.method.as_str().to_string(),
+            })?;
+
+        let base = match (self.inner.find_price_as_of(product_code, date), opening) {
+            (Ok(purchase), _) => purchase,
+            // 期首在庫は月初日付・運賃込みの評価額として扱う
+            (
+                Err(DomainError::NoPurchaseBefore { .. } | DomainError::MasterNotFound { .. }),
+                Some(opening),
+            ) => Purchase::new(
+                date.first_day_of_month(),
+                opening.product_code.clone(),
+                opening.product_name.clone(),
+                unit_price,
+                opening.quantity,
+                FreightCode::DirectPrice(0.0),
+            ),
+            (Err(e), _) => return Err(e),
+        };
+
+        Ok(Purchase { unit_price, ..base })
+    }
+
+    fn find_history(&self, product_code: &ProductCode) -> Result<Vec<Purchase>> {
+        self.inner.find_history(product_code)
+    }
+
+    fn find_all(&self) -> Result<Vec<Purchase>> {
+        self.inner.find_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn purchase(date: &str, unit_price: f64, quantity: f64) -> Purchase {
+        Purchase::new(
+            TransactionDate::new(date.to_string()).unwrap(),
+            ProductCode::new("M001".to_string()).unwrap(),
+            "材料A".to_string(),
+            Amount::new(unit_price).unwrap(),
+            Quantity::new(quantity).unwrap(),
+            FreightCode::DirectPrice(0.0),
+        )
+    }
+
+    fn issue(date: &str, quantity: f64) -> InventoryTransaction {
+        InventoryTransaction::new(
+            TransactionDate::new(date.to_string()).unwrap(),
+            InventoryType::MaterialIssue,
+            ProductCode::new("M001".to_string()).unwrap(),
+            "材料A".to_string(),
+            Quantity::new(quantity).unwrap(),
+        )
+    }
+
+    fn date(value: &str) -> TransactionDate {
+        TransactionDate::new(value.to_string()).unwrap()
+    }
+
+    fn history() -> Vec<Purchase> {
+        vec![
+            purchase("2026-01-05", 100.0, 100.0),
+            purchase("2026-01-15", 130.0, 200.0),
+            purchase("2026-01-25", 160.0, 100.0),
+        ]
+    }
+
+    #[test]
+    fn test_latest_price() {
+        let price = LatestPriceStrategy
+            .unit_price(&history(), &[], None, &date("2026-01-20"))
+            .unwrap();
+        assert_eq!(price.value(), 130.0);
+    }
+
+    #[test]
+    fn test_moving_average() {
+        // (100×100 + 130×200) ÷ 300 = 120
+        let price = MovingAverageStrategy
+            .unit_price(&history(), &[], None, &date("2026-01-20"))
+            .unwrap();
+        assert_eq!(price.value(), 120.0);
+    }
+
+    #[test]
+    fn test_moving_average_with_opening_balance() {
+        // 期首 100kg / 8,000円
+        // (8,000 + 100×100) ÷ 200 = 90
+        let opening = OpeningBalance::new(
+            ProductCode::new("M001".to_string()).unwrap(),
+            "材料A".to_string(),
+            Quantity::new(100.0).unwrap(),
+            Some(Amount::new(8000.0).unwrap()),
+        );
+        let price = MovingAverageStrategy
+            .unit_price(&history(), &[], Some(&opening), &date("2026-01-10"))
+            .unwrap();
+        assert_eq!(price.value(), 90.0);
+    }
+
+    #[test]
+    fn test_moving_average_opening_without_amount() {
+        // 期首金額が未入力の場合は最初の仕入単価（100円）で評価
+        // (100×100 + 100×100) ÷ 200 = 100
+        let opening = OpeningBalance::new(
+            ProductCode::new("M001".to_string()).unwrap(),
+            "材料A".to_string(),
+            Quantity::new(100.0).unwrap(),
+            None,
+        );
+        let price = MovingAverageStrategy
+            .unit_price(&history(), &[], Some(&opening), &date("2026-01-10"))
+            .unwrap();
+        assert_eq!(price.value(), 100.0);
+
+        // 指定日以前の仕入がなければ期首金額を評価できない
+        let result =
+            MovingAverageStrategy.unit_price(&history(), &[], Some(&opening), &date("2026-01-01"));
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_moving_average_issue_between_receipts() {
+        // 100kg × 100円 → 払出 90kg（平均100円）→ 残 10kg / 1,000円
+        // 100kg × 200円 → (1,000 + 20,000) ÷ 110 = 190.9091
+        let history = vec![
+            purchase("2026-01-05", 100.0, 100.0),
+            purchase("2026-01-15", 200.0, 100.0),
+        ];
+        let issues = vec![issue("2026-01-10", 90.0)];
+
+        let price = MovingAverageStrategy
+            .unit_price(&history, &issues, None, &date("2026-01-20"))
+            .unwrap();
+        assert_eq!(price.value(), 190.9091);
+
+        // 払出のない累計平均とは異なる
+        let price = MovingAverageStrategy
+            .unit_price(&history, &[], None, &date("2026-01-20"))
+            .unwrap();
+        assert_eq!(price.value(), 150.0);
+    }
+
+    #[test]
+    fn test_periodic_average() {
+        // (100×100 + 130×200 + 160×100) ÷ 400 = 130
+        let price = PeriodicAverageStrategy
+            .unit_price(&history(), &[], None, &date("2026-01-10"))
+            .unwrap();
+        assert_eq!(price.value(), 130.0);
+    }
+
+    #[test]
+    fn test_periodic_average_before_first_purchase() {
+        // 期首金額が未入力でも、期間の最初の仕入単価（100円）で評価して期間全体で平均
+        // (100×100 + 100×100 + 130×200 + 160×100) ÷ 500 = 124
+        let opening = OpeningBalance::new(
+            ProductCode::new("M001".to_string()).unwrap(),
+            "材料A".to_string(),
+            Quantity::new(100.0).unwrap(),
+            None,
+        );
+        let price = PeriodicAverageStrategy
+            .unit_price(&history(), &[], Some(&opening), &date("2026-01-01"))
+            .unwrap();
+        assert_eq!(price.value(), 124.0);
+    }
+
+    #[test]
+    fn test_average_with_zero_quantity_falls_back_to_last_price() {
+        let history = vec![purchase("2026-01-05", 100.0, 0.0)];
+        let price = MovingAverageStrategy
+            .unit_price(&history, &[], None, &date("2026-01-10"))
+            .unwrap();
+        assert_eq!(price.value(), 100.0);
+    }
+
+    #[test]
+    fn test_no_purchase_before_date() {
+        let result = MovingAverageStrategy.unit_price(&history(), &[], None, &date("2026-01-01"));
+        assert!(result.is_none());
+    }
+
+    struct MockPurchaseRepository {
+        history: Vec<Purchase>,
+    }
+
+    impl PurchaseRepository for MockPurchaseRepository {
+        fn find_price_as_of(
+            &self,
+            product_code: &ProductCode,
+            date: &TransactionDate,
+        ) -> Result<Purchase> {
+            self.history
+                .iter()
+                .rev()
+                .find(|p| p.purchase_date <= *date)
+                .cloned()
+                .ok_or_else(|| DomainError::NoPurchaseBefore {
+                    code: product_code.value().to_string(),
+                    date: date.value().to_string(),
+                })
+        }
+
+        fn find_history(&self, _product_code: &ProductCode) -> Result<Vec<Purchase>> {
+            Ok(self.history.clone())
+        }
+
+        fn find_all(&self) -> Result<Vec<Purchase>> {
+            Ok(self.history.clone())
+        }
+    }
+
+    #[test]
+    fn test_priced_repository_uses_opening_balance_before_first_purchase() {
+        // 期首 100kg / 9,000円、最初の仕入は 2026-01-05
+        let inner = MockPurchaseRepository { history: history() };
+        let opening = OpeningBalance::new(
+            ProductCode::new("M001".to_string()).unwrap(),
+            "材料A".to_string(),
+            Quantity::new(100.0).unwrap(),
+            Some(Amount::new(9000.0).unwrap()),
+        );
+        let repo = PricedPurchaseRepository::new(&inner, PricingMethod::MovingAverage)
+            .with_opening_balances(vec![opening]);
+        let code = ProductCode::new("M001".to_string()).unwrap();
+
+        let purchase = repo.find_price_as_of(&code, &date("2026-01-03")).unwrap();
+        assert_eq!(purchase.unit_price.value(), 90.0);
+        assert_eq!(purchase.purchase_date.value(), "2026-01-01");
+        assert_eq!(purchase.product_name, "材料A");
+    }
+
+    #[test]
+    fn test_priced_repository_applies_issues() {
+        let inner = MockPurchaseRepository {
+            history: vec![
+                purchase("2026-01-05", 100.0, 100.0),
+                purchase("2026-01-15", 200.0, 100.0),
+            ],
+        };
+        let repo = PricedPurchaseRepository::new(&inner, PricingMethod::MovingAverage)
+            .with_issues(vec![issue("2026-01-20", 5.0), issue("2026-01-10", 90.0)]);
+        let code = ProductCode::new("M001".to_string()).unwrap();
+
+        let purchase = repo.find_price_as_of(&code, &date("2026-01-20")).unwrap();
+        assert_eq!(purchase.unit_price.value(), 190.9091);
+        assert_eq!(purchase.purchase_date.value(), "2026-01-15");
+    }
+}
@@ -1,20 +1,45 @@
 use super::entities::*;
+use super::error::Result;
 use super::value_objects::*;
-use color_eyre::Result;
 
 /// 配合マスタリポジトリ
 pub trait FormulaRepository {
-    fn find_by_product_code(&self, product_code: &ProductCode) -> Result<Vec<FormulaEntry>>;
+    /// 指定日時点で有効な版の配合を取得
+    fn find_by_product_code_as_of(
+        &self,
+        product_code: &ProductCode,
+        date: &TransactionDate,
+    ) -> Result<Vec<FormulaEntry>>;
+    /// 指定日時点で有効な版が配合マスタに登録されているか（中間品の判定）
+    fn has_formula_as_of(&self, product_code: &ProductCode, date: &TransactionDate) -> bool;
+    /// すべての版（製造商品コード・有効開始日順）
+    fn find_all_versions(&self) -> Result<Vec<FormulaVersion>>;
 }
 
 /// 仕入リポジトリ
 pub trait PurchaseRepository {
-    fn find_latest_price(&self, product_code: &ProductCode) -> Result<Purchase>;
+    /// 指定日以前で最も新しい仕入を取得
+    fn find_price_as_of(
+        &self,
+        product_code: &ProductCode,
+        date: &TransactionDate,
+    ) -> Result<Purchase>;
+
+    /// 商品コードの仕入履歴を仕入日の昇順で取得
+    fn find_history(&self, product_code: &ProductCode) -> Result<Vec<Purchase>>;
+
+    /// 全仕入を商品コード・仕入日の順で取得
+    fn find_all(&self) -> Result<Vec<Purchase>>;
 }
 
 /// 生産リポジトリ
 pub trait ProductionRepository {
     fn find_all(&self) -> Result<Vec<Production>>;
+
+    /// find_all の index 番目の生産を読み込んだシートの行番号（結果の書き戻し・エラー報告用）
+    fn source_row(&self, _index: usize) -> Option<usize> {
+        None
+    }
 }
 
 /// 入出庫トランザクションリポジトリ
@@ -24,5 +49,25 @@ pub trait InventoryTransactionRepository {
 
 /// 運賃マスタリポジトリ
 pub trait FreightMasterRepository {
-    fn find_by_code(&self, freight_code: &str) -> Result<FreightMaster>;
+    /// 指定日時点で有効な運賃マスタを取得
+    fn find_by_code_as_of(
+        &self,
+        freight_code: &str,
+        date: &TransactionDate,
+    ) -> Result<FreightMaster>;
+}
+
+/// 運賃表リポジトリ
+pub trait FreightRateTableRepository {
+    fn find_all(&self) -> Result<Vec<FreightRateTable>>;
+}
+
+/// 期首在庫リポジトリ
+pub trait OpeningBalanceRepository {
+    fn find_all(&self) -> Result<Vec<OpeningBalance>>;
+}
+
+/// 単位換算マスタリポジトリ
+pub trait UnitConversionRepository {
+    fn find_all(&self) -> Result<Vec<UnitConversion>>;
 }
@@ -1,7 +1,10 @@
+use super::cost_layer::LotAllocation;
 use super::entities::*;
+use super::error::{DomainError, Result};
 use super::repositories::*;
 use super::value_objects::*;
-use color_eyre::Result;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
 
 /// 材料消費計算結果
 #[derive(Debug, Clone)]
@@ -10,18 +13,203 @@ pub struct MaterialConsumption {
     pub material_name: String,
     pub quantity: Quantity,
     pub unit_price: Amount,
+    pub purchase_date: TransactionDate, // 単価を採用した仕入の仕入日
     pub total_cost: Amount,
-    pub freight_cost: Amount,        // 実質運賃（按分後）
-    pub purchase_quantity: Quantity, // 仕入数量
-    pub freight_code_str: String,    // 運賃コード（ロギング用）
-    pub freight_kg_price: f64,       // 運賃Kg単価（ロギング用）
+    pub freight_cost: Amount,                // 実質運賃（按分後）
+    pub purchase_quantity: Quantity,         // 仕入数量
+    pub freight_code_str: String,            // 運賃コード（ロギング用）
+    pub freight_kg_price: f64,               // 運賃Kg単価（ロギング用）
+    pub lot_allocations: Vec<LotAllocation>, // 仕入ロットの引当（先入先出）
+    pub lot_shortage: Quantity,              // ロットに引き当てられなかった数量
+}
+
+/// 中間品（配合マスタに登録された材料）の積上げ原価
+#[derive(Debug, Clone)]
+pub struct IntermediateCost {
+    pub material_code: ProductCode,
+    pub level: usize, // 展開レベル（製品の直接材料が1）
+    pub quantity: Quantity,
+    pub material_cost: Amount, // 構成材料の材料費合計（運賃を除く）
+    pub freight_cost: Amount,  // 構成材料の実質運賃合計
+}
+
+/// 部品展開ツリーのノード
+#[derive(Debug, Clone)]
+pub struct BomNode {
+    pub material_code: ProductCode,
+    pub material_name: String,
+    pub quantity: Quantity,
+    pub material_cost: Amount,
+    pub freight_cost: Amount,
+    pub children: Vec<BomNode>, // 中間品の構成材料（購入材料の場合は空）
 }
 
 /// 材料費計算結果
 #[derive(Debug, Clone)]
 pub struct MaterialCostResult {
-    pub consumptions: Vec<MaterialConsumption>,
-    pub total_freight_cost: Amount, // 全材料の運賃合計
+    pub consumptions: Vec<MaterialConsumption>, // 購入材料の消費（中間品は展開済み）
+    pub intermediates: Vec<IntermediateCost>,   // 中間品の積上げ原価
+    pub explosion: Vec<BomNode>,                // 部品展開ツリー（製品の直接材料ごと）
+    pub total_freight_cost: Amount,             // 全材料の運賃合計
+}
+
+impl MaterialCostResult {
+    /// 材料消費の運賃から、部品展開・中間品の運賃と運賃合計を集計し直す
+    pub fn refresh_freight(&mut self) {
+        let mut consumptions = self.consumptions.iter();
+        let mut intermediates = self.intermediates.iter_mut();
+        for node in &mut self.explosion {
+            Self::refresh_node_freight(node, &mut consumptions, &mut intermediates);
+        }
+        self.total_freight_cost = self
+            .consumptions
+            .iter()
+            .fold(Amount::zero(), |acc, c| acc.add(&c.freight_cost));
+    }
+
+    /// 部品展開は材料消費・中間品と同じ順序（購入材料は行きがけ順、中間品は帰りがけ順）で作成している
+    fn refresh_node_freight<'c>(
+        node: &mut BomNode,
+        consumptions: &mut impl Iterator<Item = &'c MaterialConsumption>,
+        intermediates: &mut std::slice::IterMut<'_, IntermediateCost>,
+    ) {
+        if node.children.is_empty() {
+            if let Some(consumption) = consumptions.next() {
+                node.freight_cost = consumption.freight_cost;
+            }
+            return;
+        }
+
+        for child in &mut node.children {
+            Self::refresh_node_freight(child, consumptions, intermediates);
+        }
+        node.freight_cost = node
+            .children
+            .iter()
+            .fold(Amount::zero(), |acc, c| acc.add(&c.freight_cost));
+        if let Some(intermediate) = intermediates.next() {
+            intermediate.freight_cost = node.freight_cost;
+        }
+    }
+}
+
+/// 材料費を計算した生産（入出庫履歴・月次受払表は材料費計算の結果を再利用する）
+#[derive(Debug, Clone)]
+pub struct CostedProduction {
+    pub production: Production,
+    pub result: MaterialCostResult,
+}
+
+impl CostedProduction {
+    /// 材料出庫トランザクション
+    pub fn material_issues(&self) -> Vec<InventoryTransaction> {
+        InventoryHistoryService::material_issues(&self.production, &self.result)
+    }
+}
+
+/// 材料費の構成要素ごとの端数処理
+///
+/// 各金額は計算した段階で端数処理し、後続の計算には端数処理後の金額を使う。
+#[derive(Debug, Clone)]
+pub struct RoundingPolicy {
+    pub raw_material_cost: RoundingMode,   // 原砂金額
+    pub yield_cost: RoundingMode,          // 原砂歩留金額
+    pub freight_cost: RoundingMode,        // 材料運賃
+    pub total_material_cost: RoundingMode, // 材料費
+    pub digits: u32,                       // 端数処理する桁（0 = 円未満）
+}
+
+impl RoundingPolicy {
+    pub fn new(
+        raw_material_cost: RoundingMode,
+        yield_cost: RoundingMode,
+        freight_cost: RoundingMode,
+        total_material_cost: RoundingMode,
+        digits: u32,
+    ) -> Result<Self> {
+        if digits > Amount::SCALE {
+            return Err(DomainError::OutOfRange {
+                field: "端数処理の桁数".to_string(),
+                value: digits as f64,
+                min: 0.0,
+                max: Amount::SCALE as f64,
+            });
+        }
+        Ok(Self {
+            raw_material_cost,
+            yield_cost,
+            freight_cost,
+            total_material_cost,
+            digits,
+        })
+    }
+
+    fn apply(&self, amount: &Amount, mode: RoundingMode) -> Amount {
+        amount.round(mode, self.digits)
+    }
+
+    /// 月次受払表の金額に材料費の端数処理を適用（マイナス在庫の負の金額も丸める）
+    pub fn round_inventory_amount(&self, value: Decimal) -> Decimal {
+        value.round_dp_with_strategy(self.digits, self.total_material_cost.strategy())
+    }
+}
+
+impl Default for RoundingPolicy {
+    fn default() -> Self {
+        Self {
+            raw_material_cost: RoundingMode::HalfUp,
+            yield_cost: RoundingMode::HalfUp,
+            freight_cost: RoundingMode::HalfUp,
+            total_material_cost: RoundingMode::HalfUp,
+            digits: 0,
+        }
+    }
+}
+
+/// 歩留率の計算方法の設定（商品別 > 工場別 > 既定値の順に適用）
+#[derive(Debug, Clone)]
+pub struct YieldPolicy {
+    default: YieldMethod,
+    per_plant: HashMap<String, YieldMethod>,
+    per_product: HashMap<String, YieldMethod>,
+}
+
+impl YieldPolicy {
+    pub fn new(
+        default: YieldMethod,
+        per_plant: HashMap<String, YieldMethod>,
+        per_product: HashMap<String, YieldMethod>,
+    ) -> Self {
+        Self {
+            default,
+            per_plant,
+            per_product,
+        }
+    }
+
+    pub fn default_method(&self) -> YieldMethod {
+        self.default
+    }
+
+    /// 生産に適用する計算方法
+    pub fn method_for(&self, production: &Production) -> YieldMethod {
+        self.per_product
+            .get(production.product_code.value())
+            .or_else(|| {
+                production
+                    .plant
+                    .as_ref()
+                    .and_then(|plant| self.per_plant.get(plant))
+            })
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+impl Default for YieldPolicy {
+    fn default() -> Self {
+        Self::new(YieldMethod::Multiply, HashMap::new(), HashMap::new())
+    }
 }
 
 /// 材料費計算ドメインサービス
@@ -29,6 +217,9 @@ pub struct MaterialCostCalculationService;
 
 impl MaterialCostCalculationService {
     /// 材料消費を計算
+    ///
+    /// 材料が配合マスタに製造商品として登録されている場合は中間品として再帰的に展開し、
+    /// 購入材料まで分解して消費数量・材料費・運賃を積み上げる。
     pub fn calculate_material_consumption<F, P, FR>(
         production: &Production,
         formula_repo: &F,
@@ -40,73 +231,74 @@ impl MaterialCostCalculationService {
         P: PurchaseRepository,
         FR: FreightMasterRepository,
     {
-        // 配合マスタから材料を取得
-        let formulas = formula_repo.find_by_product_code(&production.product_code)?;
-
-        let mut consumptions = Vec::new();
-        let mut total_freight = Amount::zero();
-
-        for formula in formulas {
-            // 消費数量を計算
-            let consumption_qty =
-                Quantity::new(production.quantity.value() * formula.consumption_ratio.value())?;
-
-            // 仕入データから単価を取得
-            let purchase = purchase_repo.find_latest_price(&formula.material_code)?;
-
-            // 運賃Kg単価を取得
-            let freight_kg_price = match &purchase.freight_code {
-                crate::domain::value_objects::FreightCode::DirectPrice(price) => *price,
-                crate::domain::value_objects::FreightCode::Code(code) => {
-                    let freight_master = freight_repo.find_by_code(code)?;
-                    freight_master.kg_unit_price.value()
-                }
-            };
-
-            // 運賃コードを文字列化（ロギング用）
-            let freight_code_str = match &purchase.freight_code {
-                crate::domain::value_objects::FreightCode::DirectPrice(price) => {
-                    format!("{:.2}", price)
-                }
-                crate::domain::value_objects::FreightCode::Code(code) => code.clone(),
-            };
+        let mut explosion = BomExplosion {
+            formula_repo,
+            purchase_repo,
+            freight_repo,
+            production_date: &production.production_date,
+            path: Vec::new(),
+            consumptions: Vec::new(),
+            intermediates: Vec::new(),
+        };
+        let nodes = explosion.explode(&production.product_code, production.quantity)?;
 
-            // 実質運賃（按分後） = 運賃Kg単価 × 消費数量
-            let material_freight = Amount::new(freight_kg_price * consumption_qty.value())?;
-            total_freight = total_freight.add(&material_freight);
-
-            // 材料費を計算（単価のみ、運賃は別途）
-            let total_cost = purchase.unit_price.multiply(consumption_qty.value());
-
-            consumptions.push(MaterialConsumption {
-                material_code: formula.material_code.clone(),
-                material_name: purchase.product_name.clone(),
-                quantity: consumption_qty,
-                unit_price: purchase.unit_price,
-                total_cost,
-                freight_cost: material_freight,
-                purchase_quantity: purchase.quantity,
-                freight_code_str,
-                freight_kg_price,
-            });
-        }
+        let total_freight = explosion
+            .consumptions
+            .iter()
+            .fold(Amount::zero(), |acc, c| acc.add(&c.freight_cost));
 
         Ok(MaterialCostResult {
-            consumptions,
+            consumptions: explosion.consumptions,
+            intermediates: explosion.intermediates,
+            explosion: nodes,
             total_freight_cost: total_freight,
         })
     }
 
-    /// 原砂金額を計算
-    pub fn calculate_raw_material_cost(consumptions: &[MaterialConsumption]) -> Amount {
-        consumptions
+    /// 原砂金額を計算（材料費の合計に端数処理を適用）
+    pub fn calculate_raw_material_cost(
+        consumptions: &[MaterialConsumption],
+        rounding: &RoundingPolicy,
+    ) -> Amount {
+        let total = consumptions
             .iter()
-            .fold(Amount::zero(), |acc, c| acc.add(&c.total_cost))
+            .fold(Amount::zero(), |acc, c| acc.add(&c.total_cost));
+        rounding.apply(&total, rounding.raw_material_cost)
+    }
+
+    /// 原砂歩留金額を計算（端数処理後の原砂金額 × 歩留率、または ÷ 歩留率）
+    pub fn calculate_yield_cost(
+        raw_material_cost: &Amount,
+        yield_rate: &YieldRate,
+        method: YieldMethod,
+        rounding: &RoundingPolicy,
+    ) -> Result<Amount> {
+        let yield_cost = match method {
+            YieldMethod::Multiply => raw_material_cost.multiply(yield_rate.decimal()),
+            YieldMethod::Divide => raw_material_cost
+                .divide_by(yield_rate.decimal())
+                .ok_or_else(|| {
+                    DomainError::invalid_format(
+                        "歩留率",
+                        &yield_rate.value().to_string(),
+                        "除算（原砂金額 ÷ 歩留率）の場合は0より大きい値",
+                    )
+                })?,
+        };
+        Ok(rounding.apply(&yield_cost, rounding.yield_cost))
+    }
+
+    /// 材料運賃を計算（全材料の運賃合計に端数処理を適用）
+    pub fn calculate_freight_cost(
+        total_freight_cost: &Amount,
+        rounding: &RoundingPolicy,
+    ) -> Amount {
+        rounding.apply(total_freight_cost, rounding.freight_cost)
     }
 
-    /// 原砂歩留金額を計算
-    pub fn calculate_yield_cost(raw_material_cost: &Amount, yield_rate: &YieldRate) -> Amount {
-        raw_material_cost.multiply(yield_rate.value())
+    /// 凝集剤・粘土処理の金額を計算（入力値に材料費の端数処理を適用）
+    pub fn calculate_processing_cost(amount: &Amount, rounding: &RoundingPolicy) -> Amount {
+        rounding.apply(amount, rounding.total_material_cost)
     }
 
     /// 材料費合計を計算（運賃を含む）
@@ -115,11 +307,13 @@ impl MaterialCostCalculationService {
         coagulant_cost: &Amount,
         clay_treatment_cost: &Amount,
         freight_cost: &Amount,
+        rounding: &RoundingPolicy,
     ) -> Amount {
-        yield_cost
+        let total = yield_cost
             .add(coagulant_cost)
             .add(clay_treatment_cost)
-            .add(freight_cost)
+            .add(freight_cost);
+        rounding.apply(&total, rounding.total_material_cost)
     }
 }
 
@@ -135,60 +329,465 @@ pub struct InventoryHistoryRecord {
     pub balance: InventoryBalance,
 }
 
+/// 配合マスタの再帰展開
+struct BomExplosion<'r, F, P, FR> {
+    formula_repo: &'r F,
+    purchase_repo: &'r P,
+    freight_repo: &'r FR,
+    production_date: &'r TransactionDate,
+    path: Vec<String>, // 展開中の商品コード（循環参照の検出用）
+    consumptions: Vec<MaterialConsumption>,
+    intermediates: Vec<IntermediateCost>,
+}
+
+impl<'r, F, P, FR> BomExplosion<'r, F, P, FR>
+where
+    F: FormulaRepository,
+    P: PurchaseRepository,
+    FR: FreightMasterRepository,
+{
+    /// 商品を配合マスタで1段展開し、中間品はさらに再帰的に展開する
+    fn explode(&mut self, product_code: &ProductCode, quantity: Quantity) -> Result<Vec<BomNode>> {
+        if self.path.iter().any(|code| code == product_code.value()) {
+            let mut cycle = self.path.clone();
+            cycle.push(product_code.value().to_string());
+            return Err(DomainError::FormulaCycle { path: cycle });
+        }
+
+        // 配合マスタから材料を取得
+        let formulas = self
+            .formula_repo
+            .find_by_product_code_as_of(product_code, self.production_date)?;
+        self.path.push(product_code.value().to_string());
+
+        let mut nodes = Vec::new();
+        for formula in formulas {
+            // 消費数量を計算
+            let consumption_qty = quantity.multiply(&formula.consumption_ratio);
+
+            if self
+                .formula_repo
+                .has_formula_as_of(&formula.material_code, self.production_date)
+            {
+                // 中間品: 構成材料の原価を積み上げる
+                let children = self.explode(&formula.material_code, consumption_qty)?;
+                let material_cost = children
+                    .iter()
+                    .fold(Amount::zero(), |acc, c| acc.add(&c.material_cost));
+                let freight_cost = children
+                    .iter()
+                    .fold(Amount::zero(), |acc, c| acc.add(&c.freight_cost));
+
+                self.intermediates.push(IntermediateCost {
+                    material_code: formula.material_code.clone(),
+                    level: self.path.len(),
+                    quantity: consumption_qty,
+                    material_cost,
+                    freight_cost,
+                });
+                nodes.push(BomNode {
+                    material_code: formula.material_code.clone(),
+                    material_name: formula.material_code.value().to_string(),
+                    quantity: consumption_qty,
+                    material_cost,
+                    freight_cost,
+                    children,
+                });
+            } else {
+                let consumption =
+                    self.purchased_material(&formula.material_code, consumption_qty)?;
+                nodes.push(BomNode {
+                    material_code: consumption.material_code.clone(),
+                    material_name: consumption.material_name.clone(),
+                    quantity: consumption.quantity,
+                    material_cost: consumption.total_cost,
+                    freight_cost: consumption.freight_cost,
+                    children: Vec::new(),
+                });
+                self.consumptions.push(consumption);
+            }
+        }
+
+        self.path.pop();
+        Ok(nodes)
+    }
+
+    /// 購入材料の消費（生産日時点の仕入単価・運賃）
+    fn purchased_material(
+        &self,
+        material_code: &ProductCode,
+        consumption_qty: Quantity,
+    ) -> Result<MaterialConsumption> {
+        // 生産日時点の仕入データから単価を取得
+        let purchase = self
+            .purchase_repo
+            .find_price_as_of(material_code, self.production_date)?;
+
+        // 運賃Kg単価を取得し、運賃コードを文字列化（ロギング用）
+        let (freight_kg_price, freight_code_str) = match &purchase.freight_code {
+            FreightCode::DirectPrice(price) => (Amount::new(*price)?, format!("{:.2}", price)),
+            FreightCode::Code(code) => {
+                let freight_master = self
+                    .freight_repo
+                    .find_by_code_as_of(code, self.production_date)?;
+                // 運賃表のあるパターンは仕入数量の重量帯・地区割増・最低運賃から Kg単価を計算
+                let kg_price = freight_master.kg_unit_price_for(&purchase.quantity)?;
+                let zone = freight_master
+                    .zone
+                    .as_ref()
+                    .map(|z| format!(" {}", z))
+                    .unwrap_or_default();
+                let label = format!(
+                    "{} ({}{} {})",
+                    freight_master.freight_code,
+                    freight_master.pattern_name.value(),
+                    zone,
+                    freight_master.period_label()
+                );
+                (kg_price, label)
+            }
+            FreightCode::LotFreight(lot_freight) => {
+                let kg_price = lot_freight
+                    .divide_by(purchase.quantity.decimal())
+                    .ok_or_else(|| DomainError::ZeroQuantityLotFreight {
+                        code: material_code.value().to_string(),
+                        date: purchase.purchase_date.value().to_string(),
+                        freight: lot_freight.value(),
+                    })?;
+                let label = format!(
+                    "ロット運賃 {:.0}円 / {}kg",
+                    lot_freight.value(),
+                    purchase.quantity.value()
+                );
+                (kg_price, label)
+            }
+        };
+
+        // 単価・運賃Kg単価は基準単位（kg）あたりのため、単位の異なる数量とは計算しない
+        consumption_qty.ensure_unit(purchase.unit())?;
+        consumption_qty.ensure_unit(Unit::Kilogram)?;
+
+        // 実質運賃（按分後） = 運賃Kg単価 × 消費数量
+        // ロット運賃は Kg単価の丸めを避けるため、ロット運賃 × 消費数量 / 仕入数量 で計算する
+        // （ロット引当後に引き当てた仕入ロットごとのロット運賃で計算し直す）
+        let material_freight = match &purchase.freight_code {
+            FreightCode::LotFreight(lot_freight) => {
+                lot_freight.multiply(consumption_qty.decimal() / purchase.quantity.decimal())
+            }
+            _ => freight_kg_price.multiply(consumption_qty.decimal()),
+        };
+
+        // 材料費を計算（単価のみ、運賃は別途）
+        let total_cost = purchase.unit_price.multiply(consumption_qty.decimal());
+
+        Ok(MaterialConsumption {
+            material_code: material_code.clone(),
+            material_name: purchase.product_name.clone(),
+            quantity: consumption_qty,
+            unit_price: purchase.unit_price,
+            purchase_date: purchase.purchase_date.clone(),
+            total_cost,
+            freight_cost: material_freight,
+            purchase_quantity: purchase.quantity,
+            freight_code_str,
+            freight_kg_price: freight_kg_price.value(),
+            lot_allocations: Vec::new(),
+            lot_shortage: Quantity::zero(),
+        })
+    }
+}
+
+/// 配合マスタを再帰的に展開し、購入材料の消費数量を集める（単価・運賃は求めない）
+fn explode_quantities<F>(
+    formula_repo: &F,
+    date: &TransactionDate,
+    product_code: &ProductCode,
+    quantity: Quantity,
+    path: &mut Vec<String>,
+    materials: &mut Vec<(ProductCode, Quantity)>,
+) -> Result<()>
+where
+    F: FormulaRepository,
+{
+    if path.iter().any(|code| code == product_code.value()) {
+        let mut cycle = path.clone();
+        cycle.push(product_code.value().to_string());
+        return Err(DomainError::FormulaCycle { path: cycle });
+    }
+
+    let formulas = formula_repo.find_by_product_code_as_of(product_code, date)?;
+    path.push(product_code.value().to_string());
+    for formula in formulas {
+        let consumption_qty = quantity.multiply(&formula.consumption_ratio);
+        if formula_repo.has_formula_as_of(&formula.material_code, date) {
+            explode_quantities(
+                formula_repo,
+                date,
+                &formula.material_code,
+                consumption_qty,
+                path,
+                materials,
+            )?;
+        } else {
+            materials.push((formula.material_code, consumption_qty));
+        }
+    }
+    path.pop();
+    Ok(())
+}
+
+/// 在庫警告の種類
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryWarningKind {
+    NegativeStock,    // マイナス在庫
+    BelowSafetyStock, // 安全在庫割れ
+}
+
+impl InventoryWarningKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            InventoryWarningKind::NegativeStock => "マイナス在庫",
+            InventoryWarningKind::BelowSafetyStock => "安全在庫割れ",
+        }
+    }
+}
+
+/// 在庫警告（残高がしきい値を下回ったトランザクション）
+#[derive(Debug, Clone)]
+pub struct InventoryWarning {
+    pub kind: InventoryWarningKind,
+    pub date: TransactionDate,
+    pub inventory_type: InventoryType,
+    pub product_code: ProductCode,
+    pub product_name: String,
+    pub change_quantity: Quantity,
+    pub balance: InventoryBalance,
+    pub threshold: f64,
+}
+
+/// 入出庫履歴（履歴レコードと在庫警告）
+#[derive(Debug, Clone)]
+pub struct InventoryHistory {
+    pub records: Vec<InventoryHistoryRecord>,
+    pub warnings: Vec<InventoryWarning>,
+}
+
+/// 安全在庫の設定（商品別の設定がない場合は既定値）
+#[derive(Debug, Clone, Default)]
+pub struct SafetyStockPolicy {
+    default: f64,
+    per_product: HashMap<String, f64>,
+}
+
+impl SafetyStockPolicy {
+    pub fn new(default: f64, per_product: HashMap<String, f64>) -> Result<Self> {
+        if default < 0.0 {
+            return Err(DomainError::negative("安全在庫", default));
+        }
+        if let Some((code, value)) = per_product.iter().find(|(_, v)| **v < 0.0) {
+            return Err(DomainError::negative(
+                &format!("商品コード '{}' の安全在庫", code),
+                *value,
+            ));
+        }
+        Ok(Self {
+            default,
+            per_product,
+        })
+    }
+
+    pub fn threshold(&self, product_code: &ProductCode) -> f64 {
+        self.per_product
+            .get(product_code.value())
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
 /// 入出庫履歴計算ドメインサービス
 pub struct InventoryHistoryService;
 
 impl InventoryHistoryService {
-    /// トランザクションから入出庫履歴を作成
+    /// 材料消費から材料出庫トランザクションを作成（消費数量0の材料は除く）
+    pub fn material_issues(
+        production: &Production,
+        result: &MaterialCostResult,
+    ) -> Vec<InventoryTransaction> {
+        result
+            .consumptions
+            .iter()
+            .filter(|c| !c.quantity.decimal().is_zero())
+            .map(|c| {
+                InventoryTransaction::new(
+                    production.production_date.clone(),
+                    InventoryType::MaterialIssue,
+                    c.material_code.clone(),
+                    c.material_name.clone(),
+                    c.quantity,
+                )
+            })
+            .collect()
+    }
+
+    /// 配合マスタだけから生産の材料出庫トランザクションを作成（移動平均法の払出用、生産日順）
+    ///
+    /// 消費数量は単価に依存しないため、材料費計算の前に全生産分をまとめて求める。
+    /// 配合を展開できない生産は材料費計算でエラーとして報告されるため、ここでは除く。
+    pub fn planned_material_issues<F>(
+        productions: &[Production],
+        formula_repo: &F,
+    ) -> Vec<InventoryTransaction>
+    where
+        F: FormulaRepository,
+    {
+        let mut issues = Vec::new();
+        for production in productions {
+            let mut materials = Vec::new();
+            let exploded = explode_quantities(
+                formula_repo,
+                &production.production_date,
+                &production.product_code,
+                production.quantity,
+                &mut Vec::new(),
+                &mut materials,
+            );
+            if exploded.is_err() {
+                continue;
+            }
+            issues.extend(
+                materials
+                    .into_iter()
+                    .filter(|(_, quantity)| !quantity.decimal().is_zero())
+                    .map(|(material_code, quantity)| {
+                        InventoryTransaction::new(
+                            production.production_date.clone(),
+                            InventoryType::MaterialIssue,
+                            material_code.clone(),
+                            material_code.value().to_string(),
+                            quantity,
+                        )
+                    }),
+            );
+        }
+        issues.sort_by(|a, b| a.date.cmp(&b.date));
+        issues
+    }
+
+    /// 期首在庫とトランザクションから入出庫履歴を作成
+    ///
+    /// 期首在庫は期首日付で「繰越」行として先頭に出力する。
+    /// 期首日付は指定した日付、なければ最初のトランザクションの月初、トランザクションもなければ当月1日とする。
+    /// 残高がマイナスまたは安全在庫を下回ったトランザクションは在庫警告として返す。
     pub fn create_history(
         transactions: Vec<InventoryTransaction>,
-    ) -> Result<Vec<InventoryHistoryRecord>> {
-        use std::collections::HashMap;
-
-        // 日付と商品コードでソート
+        opening_balances: Vec<OpeningBalance>,
+        period_start: Option<&TransactionDate>,
+        safety_stock: &SafetyStockPolicy,
+    ) -> Result<InventoryHistory> {
         let mut sorted_transactions = transactions;
+
+        // 期首在庫を繰越トランザクションに変換
+        let period_start = match period_start {
+            Some(date) => date.clone(),
+            None => sorted_transactions
+                .iter()
+                .map(|t| &t.date)
+                .min()
+                .unwrap_or(&TransactionDate::today())
+                .first_day_of_month(),
+        };
+        for opening in opening_balances {
+            sorted_transactions.push(InventoryTransaction::new(
+                period_start.clone(),
+                InventoryType::CarryForward,
+                opening.product_code,
+                opening.product_name,
+                opening.quantity,
+            ));
+        }
+
+        // 日付・繰越優先・商品コードでソート
         sorted_transactions.sort_by(|a, b| {
             a.date
                 .cmp(&b.date)
+                .then_with(|| {
+                    let a_carry = a.inventory_type == InventoryType::CarryForward;
+                    let b_carry = b.inventory_type == InventoryType::CarryForward;
+                    b_carry.cmp(&a_carry)
+                })
                 .then_with(|| a.product_code.value().cmp(b.product_code.value()))
         });
 
         // 商品ごとの残高を管理
-        let mut balances: HashMap<String, f64> = HashMap::new();
+        let mut balances: HashMap<String, InventoryBalance> = HashMap::new();
         let mut records = Vec::new();
+        let mut warnings = Vec::new();
 
         for transaction in sorted_transactions {
             let product_code_str = transaction.product_code.value().to_string();
-            let current_balance = *balances.get(&product_code_str).unwrap_or(&0.0);
+            let current_balance = balances
+                .get(&product_code_str)
+                .copied()
+                .unwrap_or_else(InventoryBalance::zero);
+
+            // 在庫残高は基準単位（kg）で管理する
+            transaction.quantity.ensure_unit(Unit::Kilogram)?;
 
-            // 増減数量を計算（生産・仕入は加算、売上は減算）
+            // 増減数量を計算（繰越・生産・仕入は加算、売上・材料出庫は減算）
             let change = match transaction.inventory_type {
-                InventoryType::Production | InventoryType::Purchase => transaction.quantity.value(),
-                InventoryType::Sales => -transaction.quantity.value(),
+                InventoryType::CarryForward
+                | InventoryType::Production
+                | InventoryType::Purchase => transaction.quantity.decimal(),
+                InventoryType::Sales | InventoryType::MaterialIssue => {
+                    -transaction.quantity.decimal()
+                }
             };
 
-            let new_balance = current_balance + change;
+            let new_balance = InventoryBalance::from_decimal(current_balance.decimal() + change);
             balances.insert(product_code_str, new_balance);
 
+            // しきい値を下回った時点のみ警告（下回ったままの間は警告しない）
+            let threshold = safety_stock.threshold(&transaction.product_code);
+            let (current, new) = (current_balance.value(), new_balance.value());
+            let warning_kind = if new < 0.0 && current >= 0.0 {
+                Some(InventoryWarningKind::NegativeStock)
+            } else if new < threshold && current >= threshold {
+                Some(InventoryWarningKind::BelowSafetyStock)
+            } else {
+                None
+            };
+            if let Some(kind) = warning_kind {
+                warnings.push(InventoryWarning {
+                    kind,
+                    date: transaction.date.clone(),
+                    inventory_type: transaction.inventory_type.clone(),
+                    product_code: transaction.product_code.clone(),
+                    product_name: transaction.product_name.clone(),
+                    change_quantity: transaction.quantity,
+                    balance: new_balance,
+                    threshold,
+                });
+            }
+
             records.push(InventoryHistoryRecord {
                 date: transaction.date,
                 inventory_type: transaction.inventory_type,
                 product_code: transaction.product_code,
                 product_name: transaction.product_name,
-                base_quantity: InventoryBalance::new(current_balance)?,
-                change_quantity: Quantity::new(change.abs())?,
-                balance: InventoryBalance::new(new_balance)?,
+                base_quantity: current_balance,
+                change_quantity: transaction.quantity,
+                balance: new_balance,
             });
         }
 
-        Ok(records)
+        Ok(InventoryHistory { records, warnings })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use crate::domain::error::MasterKind;
 
     // モックリポジトリ
     struct MockFormulaRepository {
@@ -196,24 +795,66 @@ mod tests {
     }
 
     impl FormulaRepository for MockFormulaRepository {
-        fn find_by_product_code(&self, product_code: &ProductCode) -> Result<Vec<FormulaEntry>> {
+        fn find_by_product_code_as_of(
+            &self,
+            product_code: &ProductCode,
+            _date: &TransactionDate,
+        ) -> Result<Vec<FormulaEntry>> {
             self.formulas
                 .get(product_code.value())
                 .cloned()
-                .ok_or_else(|| color_eyre::eyre::eyre!("配合マスタが見つかりません"))
+                .ok_or_else(|| DomainError::MasterNotFound {
+                    master: MasterKind::Formula,
+                    code: product_code.value().to_string(),
+                })
+        }
+
+        fn has_formula_as_of(&self, product_code: &ProductCode, _date: &TransactionDate) -> bool {
+            self.formulas.contains_key(product_code.value())
+        }
+
+        fn find_all_versions(&self) -> Result<Vec<FormulaVersion>> {
+            Ok(Vec::new())
         }
     }
 
     struct MockPurchaseRepository {
-        purchases: HashMap<String, Purchase>,
+        purchases: HashMap<String, Vec<Purchase>>,
     }
 
     impl PurchaseRepository for MockPurchaseRepository {
-        fn find_latest_price(&self, product_code: &ProductCode) -> Result<Purchase> {
+        fn find_price_as_of(
+            &self,
+            product_code: &ProductCode,
+            date: &TransactionDate,
+        ) -> Result<Purchase> {
+            self.purchases
+                .get(product_code.value())
+                .and_then(|purchases| {
+                    purchases
+                        .iter()
+                        .filter(|p| p.purchase_date <= *date)
+                        .max_by(|a, b| a.purchase_date.cmp(&b.purchase_date))
+                })
+                .cloned()
+                .ok_or_else(|| DomainError::NoPurchaseBefore {
+                    code: product_code.value().to_string(),
+                    date: date.value().to_string(),
+                })
+        }
+
+        fn find_history(&self, product_code: &ProductCode) -> Result<Vec<Purchase>> {
             self.purchases
                 .get(product_code.value())
                 .cloned()
-                .ok_or_else(|| color_eyre::eyre::eyre!("仕入データが見つかりません"))
+                .ok_or_else(|| DomainError::MasterNotFound {
+                    master: MasterKind::Purchase,
+                    code: product_code.value().to_string(),
+                })
+        }
+
+        fn find_all(&self) -> Result<Vec<Purchase>> {
+            Ok(self.purchases.values().flatten().cloned().collect())
         }
     }
 
@@ -222,11 +863,15 @@ mod tests {
     }
 
     impl FreightMasterRepository for MockFreightMasterRepository {
-        fn find_by_code(&self, code: &str) -> Result<FreightMaster> {
+        fn find_by_code_as_of(&self, code: &str, date: &TransactionDate) -> Result<FreightMaster> {
             self.freight_masters
                 .get(code)
+                .filter(|m| m.is_valid_on(date))
                 .cloned()
-                .ok_or_else(|| color_eyre::eyre::eyre!("運賃マスタが見つかりません"))
+                .ok_or_else(|| DomainError::MasterNotFound {
+                    master: MasterKind::FreightMaster,
+                    code: code.to_string(),
+                })
         }
     }
 
@@ -239,6 +884,7 @@ mod tests {
         // 期待される実質運賃: 10.0 × 30 = 300円
 
         let production = Production::new(
+            TransactionDate::new("2026-01-15".to_string()).unwrap(),
             ProductCode::new("P001".to_string()).unwrap(),
             Quantity::new(1000.0).unwrap(),
             YieldRate::new(0.95).unwrap(),
@@ -258,12 +904,14 @@ mod tests {
         let mut purchases = HashMap::new();
         purchases.insert(
             "M001".to_string(),
-            Purchase::new(
+            vec![Purchase::new(
+                TransactionDate::new("2026-01-10".to_string()).unwrap(),
+                ProductCode::new("M001".to_string()).unwrap(),
                 "材料A".to_string(),
                 Amount::new(50.0).unwrap(),
                 Quantity::new(100.0).unwrap(),
                 FreightCode::DirectPrice(10.0), // 直接指定: 10円/kg
-            ),
+            )],
         );
 
         let formula_repo = MockFormulaRepository { formulas };
@@ -306,6 +954,7 @@ mod tests {
         // 期待される実質運賃: 15.0 × 50 = 750円
 
         let production = Production::new(
+            TransactionDate::new("2026-01-15".to_string()).unwrap(),
             ProductCode::new("P002".to_string()).unwrap(),
             Quantity::new(500.0).unwrap(),
             YieldRate::new(0.90).unwrap(),
@@ -325,12 +974,14 @@ mod tests {
         let mut purchases = HashMap::new();
         purchases.insert(
             "M002".to_string(),
-            Purchase::new(
+            vec![Purchase::new(
+                TransactionDate::new("2026-01-10".to_string()).unwrap(),
+                ProductCode::new("M002".to_string()).unwrap(),
                 "材料B".to_string(),
                 Amount::new(80.0).unwrap(),
                 Quantity::new(200.0).unwrap(),
                 FreightCode::Code("T01".to_string()),
-            ),
+            )],
         );
 
         let mut freight_masters = HashMap::new();
@@ -382,6 +1033,7 @@ mod tests {
         // 合計運賃: 2300円
 
         let production = Production::new(
+            TransactionDate::new("2026-01-15".to_string()).unwrap(),
             ProductCode::new("P003".to_string()).unwrap(),
             Quantity::new(1000.0).unwrap(),
             YieldRate::new(0.92).unwrap(),
@@ -407,21 +1059,25 @@ mod tests {
         let mut purchases = HashMap::new();
         purchases.insert(
             "M003".to_string(),
-            Purchase::new(
+            vec![Purchase::new(
+                TransactionDate::new("2026-01-10".to_string()).unwrap(),
+                ProductCode::new("M003".to_string()).unwrap(),
                 "材料C".to_string(),
                 Amount::new(60.0).unwrap(),
                 Quantity::new(150.0).unwrap(),
                 FreightCode::DirectPrice(20.0), // 20円/kg
-            ),
+            )],
         );
         purchases.insert(
             "M004".to_string(),
-            Purchase::new(
+            vec![Purchase::new(
+                TransactionDate::new("2026-01-10".to_string()).unwrap(),
+                ProductCode::new("M004".to_string()).unwrap(),
                 "材料D".to_string(),
                 Amount::new(70.0).unwrap(),
                 Quantity::new(180.0).unwrap(),
                 FreightCode::Code("T02".to_string()),
-            ),
+            )],
         );
 
         let mut freight_masters = HashMap::new();
@@ -477,6 +1133,7 @@ mod tests {
         // 期待される実質運賃: 0円
 
         let production = Production::new(
+            TransactionDate::new("2026-01-15".to_string()).unwrap(),
             ProductCode::new("P004".to_string()).unwrap(),
             Quantity::new(0.0).unwrap(), // 生産数量0
             YieldRate::new(0.95).unwrap(),
@@ -496,12 +1153,14 @@ mod tests {
         let mut purchases = HashMap::new();
         purchases.insert(
             "M005".to_string(),
-            Purchase::new(
+            vec![Purchase::new(
+                TransactionDate::new("2026-01-10".to_string()).unwrap(),
+                ProductCode::new("M005".to_string()).unwrap(),
                 "材料E".to_string(),
                 Amount::new(100.0).unwrap(),
                 Quantity::new(100.0).unwrap(),
                 FreightCode::DirectPrice(10.0),
-            ),
+            )],
         );
 
         let formula_repo = MockFormulaRepository { formulas };
@@ -540,6 +1199,7 @@ mod tests {
         // 期待される実質運賃: 12.5 × 37.5 = 468.75円
 
         let production = Production::new(
+            TransactionDate::new("2026-01-15".to_string()).unwrap(),
             ProductCode::new("P005".to_string()).unwrap(),
             Quantity::new(1250.0).unwrap(),
             YieldRate::new(0.88).unwrap(),
@@ -559,12 +1219,14 @@ mod tests {
         let mut purchases = HashMap::new();
         purchases.insert(
             "M006".to_string(),
-            Purchase::new(
+            vec![Purchase::new(
+                TransactionDate::new("2026-01-10".to_string()).unwrap(),
+                ProductCode::new("M006".to_string()).unwrap(),
                 "材料F".to_string(),
                 Amount::new(90.0).unwrap(),
                 Quantity::new(250.0).unwrap(),
                 FreightCode::DirectPrice(12.5), // 12.5円/kg
-            ),
+            )],
         );
 
         let formula_repo = MockFormulaRepository { formulas };
@@ -596,4 +1258,715 @@ mod tests {
         // 合計運賃
         assert_eq!(result.total_freight_cost.value(), 468.75);
     }
+
+    #[test]
+    fn test_lot_freight_charged_by_consumption() {
+        // ロット運賃 3,000円 / 仕入 300kg のロットから 37.5kg 消費
+        // 実質運賃: 3,000 × 37.5 / 300 = 375円
+        let production = Production::new(
+            TransactionDate::new("2026-01-15".to_string()).unwrap(),
+            ProductCode::new("P005".to_string()).unwrap(),
+            Quantity::new(1250.0).unwrap(),
+            YieldRate::new(0.88).unwrap(),
+            Amount::new(120.0).unwrap(),
+            Amount::new(60.0).unwrap(),
+        );
+
+        let mut formulas = HashMap::new();
+        formulas.insert(
+            "P005".to_string(),
+            vec![FormulaEntry::new(
+                ProductCode::new("M006".to_string()).unwrap(),
+                ConsumptionRatio::new(0.03).unwrap(), // 3% = 37.5kg
+            )],
+        );
+
+        let mut purchases = HashMap::new();
+        purchases.insert(
+            "M006".to_string(),
+            vec![Purchase::new(
+                TransactionDate::new("2026-01-10".to_string()).unwrap(),
+                ProductCode::new("M006".to_string()).unwrap(),
+                "材料F".to_string(),
+                Amount::new(90.0).unwrap(),
+                Quantity::new(300.0).unwrap(),
+                FreightCode::LotFreight(Amount::new(3000.0).unwrap()),
+            )],
+        );
+
+        let result = MaterialCostCalculationService::calculate_material_consumption(
+            &production,
+            &MockFormulaRepository { formulas },
+            &MockPurchaseRepository { purchases },
+            &MockFreightMasterRepository {
+                freight_masters: HashMap::new(),
+            },
+        )
+        .unwrap();
+
+        let consumption = &result.consumptions[0];
+        assert_eq!(consumption.freight_kg_price, 10.0);
+        assert_eq!(consumption.freight_cost.value(), 375.0);
+    }
+
+    #[test]
+    fn test_lot_freight_with_zero_purchase_quantity_is_error() {
+        // 仕入数量0のロットはロット運賃を按分できない（運賃0にしない）
+        let production = Production::new(
+            TransactionDate::new("2026-01-15".to_string()).unwrap(),
+            ProductCode::new("P005".to_string()).unwrap(),
+            Quantity::new(1000.0).unwrap(),
+            YieldRate::new(1.0).unwrap(),
+            Amount::zero(),
+            Amount::zero(),
+        );
+
+        let mut formulas = HashMap::new();
+        formulas.insert(
+            "P005".to_string(),
+            vec![FormulaEntry::new(
+                ProductCode::new("M006".to_string()).unwrap(),
+                ConsumptionRatio::new(0.03).unwrap(),
+            )],
+        );
+
+        let mut purchases = HashMap::new();
+        purchases.insert(
+            "M006".to_string(),
+            vec![Purchase::new(
+                TransactionDate::new("2026-01-10".to_string()).unwrap(),
+                ProductCode::new("M006".to_string()).unwrap(),
+                "材料F".to_string(),
+                Amount::new(90.0).unwrap(),
+                Quantity::new(0.0).unwrap(),
+                FreightCode::LotFreight(Amount::new(3000.0).unwrap()),
+            )],
+        );
+
+        let result = MaterialCostCalculationService::calculate_material_consumption(
+            &production,
+            &MockFormulaRepository { formulas },
+            &MockPurchaseRepository { purchases },
+            &MockFreightMasterRepository {
+                freight_masters: HashMap::new(),
+            },
+        );
+
+        assert!(matches!(
+            result,
+            Err(DomainError::ZeroQuantityLotFreight { ref code, .. }) if code == "M006"
+        ));
+    }
+
+    #[test]
+    fn test_purchase_price_as_of_production_date() {
+        // 生産日以前で最も新しい仕入単価が採用されることのテスト
+        // 仕入1: 2026-01-05 単価 50円
+        // 仕入2: 2026-01-20 単価 70円（生産日より後）
+        // 生産日: 2026-01-15 → 単価 50円
+
+        let production = Production::new(
+            TransactionDate::new("2026-01-15".to_string()).unwrap(),
+            ProductCode::new("P006".to_string()).unwrap(),
+            Quantity::new(100.0).unwrap(),
+            YieldRate::new(1.0).unwrap(),
+            Amount::zero(),
+            Amount::zero(),
+        );
+
+        let mut formulas = HashMap::new();
+        formulas.insert(
+            "P006".to_string(),
+            vec![FormulaEntry::new(
+                ProductCode::new("M007".to_string()).unwrap(),
+                ConsumptionRatio::new(1.0).unwrap(),
+            )],
+        );
+
+        let mut purchases = HashMap::new();
+        purchases.insert(
+            "M007".to_string(),
+            vec![
+                Purchase::new(
+                    TransactionDate::new("2026-01-05".to_string()).unwrap(),
+                    ProductCode::new("M007".to_string()).unwrap(),
+                    "材料G".to_string(),
+                    Amount::new(50.0).unwrap(),
+                    Quantity::new(100.0).unwrap(),
+                    FreightCode::DirectPrice(0.0),
+                ),
+                Purchase::new(
+                    TransactionDate::new("2026-01-20".to_string()).unwrap(),
+                    ProductCode::new("M007".to_string()).unwrap(),
+                    "材料G".to_string(),
+                    Amount::new(70.0).unwrap(),
+                    Quantity::new(100.0).unwrap(),
+                    FreightCode::DirectPrice(0.0),
+                ),
+            ],
+        );
+
+        let formula_repo = MockFormulaRepository { formulas };
+        let purchase_repo = MockPurchaseRepository { purchases };
+        let freight_repo = MockFreightMasterRepository {
+            freight_masters: HashMap::new(),
+        };
+
+        let result = MaterialCostCalculationService::calculate_material_consumption(
+            &production,
+            &formula_repo,
+            &purchase_repo,
+            &freight_repo,
+        )
+        .unwrap();
+
+        assert_eq!(result.consumptions[0].unit_price.value(), 50.0);
+        assert_eq!(result.consumptions[0].total_cost.value(), 5000.0);
+
+        // 生産日以前の仕入がない場合はエラー
+        let early_production = Production::new(
+            TransactionDate::new("2026-01-01".to_string()).unwrap(),
+            ProductCode::new("P006".to_string()).unwrap(),
+            Quantity::new(100.0).unwrap(),
+            YieldRate::new(1.0).unwrap(),
+            Amount::zero(),
+            Amount::zero(),
+        );
+
+        let result = MaterialCostCalculationService::calculate_material_consumption(
+            &early_production,
+            &formula_repo,
+            &purchase_repo,
+            &freight_repo,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_history_starts_from_opening_balance() {
+        // 期首在庫 M001: 100kg
+        // 2026-02-10 仕入 50kg → 残高 150kg
+        // 2026-02-20 売上 30kg → 残高 120kg
+        let transactions = vec![
+            InventoryTransaction::new(
+                TransactionDate::new("2026-02-20".to_string()).unwrap(),
+                InventoryType::Sales,
+                ProductCode::new("M001".to_string()).unwrap(),
+                "材料A".to_string(),
+                Quantity::new(30.0).unwrap(),
+            ),
+            InventoryTransaction::new(
+                TransactionDate::new("2026-02-10".to_string()).unwrap(),
+                InventoryType::Purchase,
+                ProductCode::new("M001".to_string()).unwrap(),
+                "材料A".to_string(),
+                Quantity::new(50.0).unwrap(),
+            ),
+        ];
+        let opening_balances = vec![OpeningBalance::new(
+            ProductCode::new("M001".to_string()).unwrap(),
+            "材料A".to_string(),
+            Quantity::new(100.0).unwrap(),
+            None,
+        )];
+
+        let records = InventoryHistoryService::create_history(
+            transactions,
+            opening_balances,
+            None,
+            &SafetyStockPolicy::default(),
+        )
+        .unwrap()
+        .records;
+
+        assert_eq!(records.len(), 3);
+
+        // 繰越行
+        assert_eq!(records[0].inventory_type, InventoryType::CarryForward);
+        assert_eq!(records[0].date.value(), "2026-02-01");
+        assert_eq!(records[0].base_quantity.value(), 0.0);
+        assert_eq!(records[0].balance.value(), 100.0);
+
+        assert_eq!(records[1].base_quantity.value(), 100.0);
+        assert_eq!(records[1].balance.value(), 150.0);
+        assert_eq!(records[2].balance.value(), 120.0);
+    }
+
+    #[test]
+    fn test_material_issues_reduce_material_balance() {
+        // 生産 P001 1000kg、配合 M001 3% → 材料出庫 30kg
+        // 仕入 M001 100kg → 材料出庫後の残高 70kg
+        let production = Production::new(
+            TransactionDate::new("2026-01-15".to_string()).unwrap(),
+            ProductCode::new("P001".to_string()).unwrap(),
+            Quantity::new(1000.0).unwrap(),
+            YieldRate::new(0.95).unwrap(),
+            Amount::zero(),
+            Amount::zero(),
+        );
+
+        let mut formulas = HashMap::new();
+        formulas.insert(
+            "P001".to_string(),
+            vec![FormulaEntry::new(
+                ProductCode::new("M001".to_string()).unwrap(),
+                ConsumptionRatio::new(0.03).unwrap(),
+            )],
+        );
+
+        let mut purchases = HashMap::new();
+        purchases.insert(
+            "M001".to_string(),
+            vec![Purchase::new(
+                TransactionDate::new("2026-01-10".to_string()).unwrap(),
+                ProductCode::new("M001".to_string()).unwrap(),
+                "材料A".to_string(),
+                Amount::new(50.0).unwrap(),
+                Quantity::new(100.0).unwrap(),
+                FreightCode::DirectPrice(0.0),
+            )],
+        );
+
+        let result = MaterialCostCalculationService::calculate_material_consumption(
+            &production,
+            &MockFormulaRepository { formulas },
+            &MockPurchaseRepository { purchases },
+            &MockFreightMasterRepository {
+                freight_masters: HashMap::new(),
+            },
+        )
+        .unwrap();
+
+        let issues = InventoryHistoryService::material_issues(&production, &result);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].inventory_type, InventoryType::MaterialIssue);
+        assert_eq!(issues[0].date.value(), "2026-01-15");
+        assert_eq!(issues[0].product_code.value(), "M001");
+        assert_eq!(issues[0].quantity.value(), 30.0);
+
+        let mut transactions = vec![InventoryTransaction::new(
+            TransactionDate::new("2026-01-10".to_string()).unwrap(),
+            InventoryType::Purchase,
+            ProductCode::new("M001".to_string()).unwrap(),
+            "材料A".to_string(),
+            Quantity::new(100.0).unwrap(),
+        )];
+        transactions.extend(issues);
+
+        let records = InventoryHistoryService::create_history(
+            transactions,
+            Vec::new(),
+            None,
+            &SafetyStockPolicy::default(),
+        )
+        .unwrap()
+        .records;
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].inventory_type, InventoryType::MaterialIssue);
+        assert_eq!(records[1].change_quantity.value(), 30.0);
+        assert_eq!(records[1].balance.value(), 70.0);
+    }
+
+    #[test]
+    fn test_history_carries_forward_without_transactions() {
+        // トランザクションのない月も期首在庫を繰越行として出力する
+        let opening_balances = vec![
+            OpeningBalance::new(
+                ProductCode::new("M001".to_string()).unwrap(),
+                "材料A".to_string(),
+                Quantity::new(100.0).unwrap(),
+                None,
+            ),
+            OpeningBalance::new(
+                ProductCode::new("M002".to_string()).unwrap(),
+                "材料B".to_string(),
+                Quantity::new(40.0).unwrap(),
+                None,
+            ),
+        ];
+        let period_start = TransactionDate::new("2026-03-01".to_string()).unwrap();
+
+        let records = InventoryHistoryService::create_history(
+            Vec::new(),
+            opening_balances.clone(),
+            Some(&period_start),
+            &SafetyStockPolicy::default(),
+        )
+        .unwrap()
+        .records;
+
+        assert_eq!(records.len(), 2);
+        assert!(
+            records
+                .iter()
+                .all(|r| r.inventory_type == InventoryType::CarryForward
+                    && r.date.value() == "2026-03-01")
+        );
+        assert_eq!(records[0].product_code.value(), "M001");
+        assert_eq!(records[0].balance.value(), 100.0);
+        assert_eq!(records[1].product_code.value(), "M002");
+        assert_eq!(records[1].balance.value(), 40.0);
+
+        // 期首日付の指定がなければ当月1日
+        let records = InventoryHistoryService::create_history(
+            Vec::new(),
+            opening_balances,
+            None,
+            &SafetyStockPolicy::default(),
+        )
+        .unwrap()
+        .records;
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0].date,
+            TransactionDate::today().first_day_of_month()
+        );
+    }
+
+    fn transaction(
+        date: &str,
+        inventory_type: InventoryType,
+        quantity: f64,
+    ) -> InventoryTransaction {
+        InventoryTransaction::new(
+            TransactionDate::new(date.to_string()).unwrap(),
+            inventory_type,
+            ProductCode::new("P001".to_string()).unwrap(),
+            "製品A".to_string(),
+            Quantity::new(quantity).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_negative_stock_warning() {
+        // 生産 50 → 売上 80（残高 -30 でマイナス在庫）→ 売上 10（マイナスのままなので警告なし）
+        let transactions = vec![
+            transaction("2026-01-05", InventoryType::Production, 50.0),
+            transaction("2026-01-10", InventoryType::Sales, 80.0),
+            transaction("2026-01-12", InventoryType::Sales, 10.0),
+        ];
+
+        let history = InventoryHistoryService::create_history(
+            transactions,
+            Vec::new(),
+            None,
+            &SafetyStockPolicy::default(),
+        )
+        .unwrap();
+
+        assert_eq!(history.warnings.len(), 1);
+        let warning = &history.warnings[0];
+        assert_eq!(warning.kind, InventoryWarningKind::NegativeStock);
+        assert_eq!(warning.date.value(), "2026-01-10");
+        assert_eq!(warning.inventory_type, InventoryType::Sales);
+        assert_eq!(warning.change_quantity.value(), 80.0);
+        assert_eq!(warning.balance.value(), -30.0);
+    }
+
+    #[test]
+    fn test_safety_stock_warning() {
+        // 安全在庫 P001: 40
+        // 生産 100 → 売上 70（残高 30 で安全在庫割れ）→ 生産 50（残高 80 で回復）→ 売上 50（再び割れ）
+        let transactions = vec![
+            transaction("2026-01-05", InventoryType::Production, 100.0),
+            transaction("2026-01-10", InventoryType::Sales, 70.0),
+            transaction("2026-01-15", InventoryType::Production, 50.0),
+            transaction("2026-01-20", InventoryType::Sales, 50.0),
+        ];
+        let mut per_product = HashMap::new();
+        per_product.insert("P001".to_string(), 40.0);
+        let policy = SafetyStockPolicy::new(0.0, per_product).unwrap();
+
+        let history =
+            InventoryHistoryService::create_history(transactions, Vec::new(), None, &policy)
+                .unwrap();
+
+        assert_eq!(history.warnings.len(), 2);
+        assert!(
+            history
+                .warnings
+                .iter()
+                .all(|w| w.kind == InventoryWarningKind::BelowSafetyStock)
+        );
+        assert_eq!(history.warnings[0].date.value(), "2026-01-10");
+        assert_eq!(history.warnings[0].threshold, 40.0);
+        assert_eq!(history.warnings[1].date.value(), "2026-01-20");
+    }
+
+    #[test]
+    fn test_safety_stock_policy_negative() {
+        let result = SafetyStockPolicy::new(-1.0, HashMap::new());
+        assert!(result.is_err());
+    }
+
+    fn formula(material_code: &str, ratio: f64) -> FormulaEntry {
+        FormulaEntry::new(
+            ProductCode::new(material_code.to_string()).unwrap(),
+            ConsumptionRatio::new(ratio).unwrap(),
+        )
+    }
+
+    fn production(product_code: &str, quantity: f64) -> Production {
+        Production::new(
+            TransactionDate::new("2026-01-15".to_string()).unwrap(),
+            ProductCode::new(product_code.to_string()).unwrap(),
+            Quantity::new(quantity).unwrap(),
+            YieldRate::new(1.0).unwrap(),
+            Amount::zero(),
+            Amount::zero(),
+        )
+    }
+
+    fn direct_purchase(code: &str, unit_price: f64, freight: f64) -> Vec<Purchase> {
+        vec![Purchase::new(
+            TransactionDate::new("2026-01-10".to_string()).unwrap(),
+            ProductCode::new(code.to_string()).unwrap(),
+            format!("材料{}", code),
+            Amount::new(unit_price).unwrap(),
+            Quantity::new(1000.0).unwrap(),
+            FreightCode::DirectPrice(freight),
+        )]
+    }
+
+    #[test]
+    fn test_multi_level_formula() {
+        // P001 = 中間品B001 × 0.5 + 材料M001 × 0.5
+        // B001 = 材料M002 × 0.4 + 材料M003 × 0.6
+        // 生産数量 1000kg → B001 500kg（M002 200kg, M003 300kg）, M001 500kg
+        let mut formulas = HashMap::new();
+        formulas.insert(
+            "P001".to_string(),
+            vec![formula("B001", 0.5), formula("M001", 0.5)],
+        );
+        formulas.insert(
+            "B001".to_string(),
+            vec![formula("M002", 0.4), formula("M003", 0.6)],
+        );
+
+        let mut purchases = HashMap::new();
+        purchases.insert("M001".to_string(), direct_purchase("M001", 10.0, 1.0));
+        purchases.insert("M002".to_string(), direct_purchase("M002", 20.0, 2.0));
+        purchases.insert("M003".to_string(), direct_purchase("M003", 30.0, 0.0));
+
+        let result = MaterialCostCalculationService::calculate_material_consumption(
+            &production("P001", 1000.0),
+            &MockFormulaRepository { formulas },
+            &MockPurchaseRepository { purchases },
+            &MockFreightMasterRepository {
+                freight_masters: HashMap::new(),
+            },
+        )
+        .unwrap();
+
+        // 購入材料まで展開した消費
+        let quantities: Vec<(&str, f64)> = result
+            .consumptions
+            .iter()
+            .map(|c| (c.material_code.value(), c.quantity.value()))
+            .collect();
+        assert_eq!(
+            quantities,
+            vec![("M002", 200.0), ("M003", 300.0), ("M001", 500.0)]
+        );
+
+        // 中間品 B001: 材料費 20×200 + 30×300 = 13,000円, 運賃 2×200 = 400円
+        assert_eq!(result.intermediates.len(), 1);
+        let intermediate = &result.intermediates[0];
+        assert_eq!(intermediate.material_code.value(), "B001");
+        assert_eq!(intermediate.level, 1);
+        assert_eq!(intermediate.quantity.value(), 500.0);
+        assert_eq!(intermediate.material_cost.value(), 13000.0);
+        assert_eq!(intermediate.freight_cost.value(), 400.0);
+
+        // 部品展開ツリー
+        assert_eq!(result.explosion.len(), 2);
+        assert_eq!(result.explosion[0].children.len(), 2);
+        assert!(result.explosion[1].children.is_empty());
+
+        // 原砂金額 = 13,000 + 10×500 = 18,000円, 運賃 = 400 + 500 = 900円
+        let raw_material_cost = MaterialCostCalculationService::calculate_raw_material_cost(
+            &result.consumptions,
+            &RoundingPolicy::default(),
+        );
+        assert_eq!(raw_material_cost.value(), 18000.0);
+        assert_eq!(result.total_freight_cost.value(), 900.0);
+    }
+
+    #[test]
+    fn test_planned_material_issues_from_formulas() {
+        // 後の生産を先に渡しても生産日順、中間品は購入材料まで展開する
+        // 配合のない P999 の生産は除く
+        let mut formulas = HashMap::new();
+        formulas.insert(
+            "P001".to_string(),
+            vec![formula("B001", 0.5), formula("M001", 0.5)],
+        );
+        formulas.insert("B001".to_string(), vec![formula("M002", 1.0)]);
+        let mut later = production("P001", 100.0);
+        later.production_date = TransactionDate::new("2026-01-20".to_string()).unwrap();
+
+        let issues = InventoryHistoryService::planned_material_issues(
+            &[later, production("P999", 10.0), production("P001", 200.0)],
+            &MockFormulaRepository { formulas },
+        );
+
+        let issues: Vec<(&str, &str, f64)> = issues
+            .iter()
+            .map(|t| (t.date.value(), t.product_code.value(), t.quantity.value()))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                ("2026-01-15", "M002", 100.0),
+                ("2026-01-15", "M001", 100.0),
+                ("2026-01-20", "M002", 50.0),
+                ("2026-01-20", "M001", 50.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rounding_policy_applied_per_component() {
+        // 原砂金額 1,000.5 → 四捨五入 1,001、原砂歩留金額 1,001 × 0.95 = 950.95 → 四捨五入 951
+        // 材料運賃 120.9 → 切り捨て 120、材料費 951 + 1.5 + 0 + 120 = 1,072.5 → 偶数丸め 1,072
+        let policy = RoundingPolicy::new(
+            RoundingMode::HalfUp,
+            RoundingMode::HalfUp,
+            RoundingMode::Truncate,
+            RoundingMode::HalfEven,
+            0,
+        )
+        .unwrap();
+
+        let raw = Amount::new(1000.5).unwrap().round(RoundingMode::HalfUp, 0);
+        assert_eq!(raw.value(), 1001.0);
+        let yield_cost = MaterialCostCalculationService::calculate_yield_cost(
+            &raw,
+            &YieldRate::new(0.95).unwrap(),
+            YieldMethod::Multiply,
+            &policy,
+        )
+        .unwrap();
+        assert_eq!(yield_cost.value(), 951.0);
+        let freight = MaterialCostCalculationService::calculate_freight_cost(
+            &Amount::new(120.9).unwrap(),
+            &policy,
+        );
+        assert_eq!(freight.value(), 120.0);
+        // 凝集剤・粘土処理は材料費の端数処理: 2.5 → 偶数丸め 2
+        let coagulant = MaterialCostCalculationService::calculate_processing_cost(
+            &Amount::new(2.5).unwrap(),
+            &policy,
+        );
+        assert_eq!(coagulant.value(), 2.0);
+        let total = MaterialCostCalculationService::calculate_total_material_cost(
+            &yield_cost,
+            &Amount::new(1.5).unwrap(),
+            &Amount::zero(),
+            &freight,
+            &policy,
+        );
+        assert_eq!(total.value(), 1072.0);
+    }
+
+    #[test]
+    fn test_yield_cost_divide() {
+        // 原砂金額 9,500円 ÷ 歩留率 0.95 = 10,000円
+        let yield_cost = MaterialCostCalculationService::calculate_yield_cost(
+            &Amount::new(9500.0).unwrap(),
+            &YieldRate::new(0.95).unwrap(),
+            YieldMethod::Divide,
+            &RoundingPolicy::default(),
+        )
+        .unwrap();
+        assert_eq!(yield_cost.value(), 10000.0);
+
+        let zero_yield = MaterialCostCalculationService::calculate_yield_cost(
+            &Amount::new(9500.0).unwrap(),
+            &YieldRate::new(0.0).unwrap(),
+            YieldMethod::Divide,
+            &RoundingPolicy::default(),
+        );
+        assert!(zero_yield.is_err());
+    }
+
+    #[test]
+    fn test_yield_policy_precedence() {
+        // 商品別 > 工場別 > 既定値
+        let policy = YieldPolicy::new(
+            YieldMethod::Multiply,
+            HashMap::from([("第2工場".to_string(), YieldMethod::Divide)]),
+            HashMap::from([("P002".to_string(), YieldMethod::Multiply)]),
+        );
+        let production = |code: &str| {
+            Production::new(
+                TransactionDate::new("2026-01-10".to_string()).unwrap(),
+                ProductCode::new(code.to_string()).unwrap(),
+                Quantity::new(100.0).unwrap(),
+                YieldRate::new(0.9).unwrap(),
+                Amount::zero(),
+                Amount::zero(),
+            )
+        };
+
+        assert_eq!(
+            policy.method_for(&production("P001")),
+            YieldMethod::Multiply
+        );
+        assert_eq!(
+            policy.method_for(&production("P001").with_plant("第2工場".to_string())),
+            YieldMethod::Divide
+        );
+        assert_eq!(
+            policy.method_for(&production("P002").with_plant("第2工場".to_string())),
+            YieldMethod::Multiply
+        );
+    }
+
+    #[test]
+    fn test_rounding_policy_digits_out_of_range() {
+        let result = RoundingPolicy::new(
+            RoundingMode::HalfUp,
+            RoundingMode::HalfUp,
+            RoundingMode::HalfUp,
+            RoundingMode::HalfUp,
+            5,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_formula_cycle_reports_path() {
+        // P001 → B001 → B002 → B001 の循環
+        let mut formulas = HashMap::new();
+        formulas.insert("P001".to_string(), vec![formula("B001", 1.0)]);
+        formulas.insert("B001".to_string(), vec![formula("B002", 1.0)]);
+        formulas.insert("B002".to_string(), vec![formula("B001", 1.0)]);
+
+        let result = MaterialCostCalculationService::calculate_material_consumption(
+            &production("P001", 100.0),
+            &MockFormulaRepository { formulas },
+            &MockPurchaseRepository {
+                purchases: HashMap::new(),
+            },
+            &MockFreightMasterRepository {
+                freight_masters: HashMap::new(),
+            },
+        );
+
+        assert_eq!(
+            result.unwrap_err(),
+            DomainError::FormulaCycle {
+                path: vec![
+                    "P001".to_string(),
+                    "B001".to_string(),
+                    "B002".to_string(),
+                    "B001".to_string(),
+                ],
+            }
+        );
+    }
 }
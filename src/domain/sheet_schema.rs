@@ -1,4 +1,4 @@
-use color_eyre::{Result, eyre::eyre};
+use super::error::{DomainError, Result};
 use std::collections::HashMap;
 
 /// 列位置を表す値オブジェクト
@@ -15,29 +15,121 @@ impl ColumnIndex {
     }
 }
 
+/// シート名・列名の対応表
+///
+/// 既定のシート名・列名（論理名）を、工場ごとのテンプレートのシート名・列名に読み替える。
+/// 列は既定の列名、別名の順に探す。
+#[derive(Debug, Clone, Default)]
+pub struct SchemaMapping {
+    sheets: HashMap<String, String>, // 既定のシート名 → テンプレートのシート名
+    columns: HashMap<(String, String), Vec<String>>, // (既定のシート名, 既定の列名) → 列名の別名
+}
+
+impl SchemaMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// シート名を読み替える
+    pub fn with_sheet(mut self, sheet: &str, actual: String) -> Self {
+        self.sheets.insert(sheet.to_string(), actual);
+        self
+    }
+
+    /// 列名の別名を追加する
+    pub fn with_column_aliases(mut self, sheet: &str, column: &str, aliases: Vec<String>) -> Self {
+        self.columns
+            .entry((sheet.to_string(), column.to_string()))
+            .or_default()
+            .extend(aliases);
+        self
+    }
+
+    /// テンプレートのシート名（読み替えがない場合は既定のシート名）
+    pub fn sheet_name(&self, sheet: &str) -> String {
+        self.sheets
+            .get(sheet)
+            .cloned()
+            .unwrap_or_else(|| sheet.to_string())
+    }
+
+    /// 見出し行から列を探す（既定の列名 → 別名の順）
+    pub fn find_column(&self, sheet: &str, column: &str, headers: &[String]) -> Option<usize> {
+        let aliases = self
+            .columns
+            .get(&(sheet.to_string(), column.to_string()))
+            .map(|a| a.as_slice())
+            .unwrap_or_default();
+
+        std::iter::once(column)
+            .chain(aliases.iter().map(|a| a.as_str()))
+            .find_map(|name| headers.iter().position(|h| h.trim() == name.trim()))
+    }
+}
+
+/// 対応表で列を探すシートの見出し行
+struct HeaderRow<'a> {
+    headers: &'a [String],
+    mapping: &'a SchemaMapping,
+    sheet: &'static str,
+}
+
+impl<'a> HeaderRow<'a> {
+    fn new(headers: &'a [String], mapping: &'a SchemaMapping, sheet: &'static str) -> Self {
+        Self {
+            headers,
+            mapping,
+            sheet,
+        }
+    }
+
+    fn find(&self, column: &str) -> Option<ColumnIndex> {
+        self.mapping
+            .find_column(self.sheet, column, self.headers)
+            .map(ColumnIndex::new)
+    }
+
+    /// 必須列がすべてあるか（ない列はまとめてエラーにする）
+    fn require(&self, columns: &[&str]) -> Result<()> {
+        let missing: Vec<&str> = columns
+            .iter()
+            .copied()
+            .filter(|column| self.find(column).is_none())
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(DomainError::missing_columns(
+                &self.mapping.sheet_name(self.sheet),
+                &missing,
+            ))
+        }
+    }
+
+    fn column(&self, column: &str) -> ColumnIndex {
+        self.find(column).expect("必須列は require で確認済み")
+    }
+}
+
 /// 【入庫】生産シートのスキーマ
 #[derive(Debug, Clone)]
 pub struct ProductionSheetSchema {
+    sheet_name: String,
     col_production_date: ColumnIndex,
     col_product_code: ColumnIndex,
     col_quantity: ColumnIndex,
     col_yield_rate: ColumnIndex,
     col_coagulant: ColumnIndex,
     col_clay_treatment: ColumnIndex,
-    col_freight: ColumnIndex,
+    col_plant: Option<ColumnIndex>, // 任意列
 }
 
 impl ProductionSheetSchema {
-    pub fn from_headers(headers: &[String]) -> Result<Self> {
-        let mut header_map: HashMap<&str, usize> = HashMap::new();
-        for (idx, header) in headers.iter().enumerate() {
-            let trimmed = header.trim();
-            if !trimmed.is_empty() {
-                header_map.insert(trimmed, idx);
-            }
-        }
+    pub const SHEET: &'static str = "【入庫】生産";
 
-        let required_headers = [
+    pub fn from_headers(headers: &[String], mapping: &SchemaMapping) -> Result<Self> {
+        let header_row = HeaderRow::new(headers, mapping, Self::SHEET);
+        header_row.require(&[
             "生産日",
             "商品コード",
             "生産品番",
@@ -46,33 +138,25 @@ impl ProductionSheetSchema {
             "凝集剤",
             "粘土処理",
             "材料運賃",
-        ];
-
-        let mut missing = Vec::new();
-        for &header in &required_headers {
-            if !header_map.contains_key(header) {
-                missing.push(header);
-            }
-        }
-
-        if !missing.is_empty() {
-            return Err(eyre!(
-                "【入庫】生産シートに必須カラムが見つかりません: {:?}",
-                missing
-            ));
-        }
+        ])?;
 
         Ok(Self {
-            col_production_date: ColumnIndex::new(*header_map.get("生産日").unwrap()),
-            col_product_code: ColumnIndex::new(*header_map.get("商品コード").unwrap()),
-            col_quantity: ColumnIndex::new(*header_map.get("生産数量").unwrap()),
-            col_yield_rate: ColumnIndex::new(*header_map.get("歩留率").unwrap()),
-            col_coagulant: ColumnIndex::new(*header_map.get("凝集剤").unwrap()),
-            col_clay_treatment: ColumnIndex::new(*header_map.get("粘土処理").unwrap()),
-            col_freight: ColumnIndex::new(*header_map.get("材料運賃").unwrap()),
+            sheet_name: mapping.sheet_name(Self::SHEET),
+            col_production_date: header_row.column("生産日"),
+            col_product_code: header_row.column("商品コード"),
+            col_quantity: header_row.column("生産数量"),
+            col_yield_rate: header_row.column("歩留率"),
+            col_coagulant: header_row.column("凝集剤"),
+            col_clay_treatment: header_row.column("粘土処理"),
+            col_plant: header_row.find("工場"),
         })
     }
 
+    /// テンプレートのシート名
+    pub fn sheet_name(&self) -> &str {
+        &self.sheet_name
+    }
+
     pub fn production_date(&self) -> ColumnIndex {
         self.col_production_date
     }
@@ -97,58 +181,107 @@ impl ProductionSheetSchema {
         self.col_clay_treatment
     }
 
-    pub fn freight(&self) -> ColumnIndex {
+    pub fn plant(&self) -> Option<ColumnIndex> {
+        self.col_plant
+    }
+}
+
+/// 【入庫】生産シートの書き込み列のスキーマ（列がない項目は書き込まない）
+#[derive(Debug, Clone)]
+pub struct ProductionOutputSchema {
+    col_raw_material_cost: Option<ColumnIndex>,
+    col_yield_cost: Option<ColumnIndex>,
+    col_coagulant: Option<ColumnIndex>,
+    col_clay_treatment: Option<ColumnIndex>,
+    col_freight: Option<ColumnIndex>,
+    col_total_material_cost: Option<ColumnIndex>,
+    col_yield_formula: Option<ColumnIndex>,
+}
+
+impl ProductionOutputSchema {
+    pub fn from_headers(headers: &[String], mapping: &SchemaMapping) -> Self {
+        let header_row = HeaderRow::new(headers, mapping, ProductionSheetSchema::SHEET);
+        Self {
+            col_raw_material_cost: header_row.find("原砂金額"),
+            col_yield_cost: header_row.find("原砂歩留金額"),
+            col_coagulant: header_row.find("凝集剤"),
+            col_clay_treatment: header_row.find("粘土処理"),
+            col_freight: header_row.find("材料運賃"),
+            col_total_material_cost: header_row.find("材料費"),
+            col_yield_formula: header_row.find("歩留計算式"),
+        }
+    }
+
+    pub fn raw_material_cost(&self) -> Option<ColumnIndex> {
+        self.col_raw_material_cost
+    }
+
+    pub fn yield_cost(&self) -> Option<ColumnIndex> {
+        self.col_yield_cost
+    }
+
+    pub fn coagulant(&self) -> Option<ColumnIndex> {
+        self.col_coagulant
+    }
+
+    pub fn clay_treatment(&self) -> Option<ColumnIndex> {
+        self.col_clay_treatment
+    }
+
+    pub fn freight(&self) -> Option<ColumnIndex> {
         self.col_freight
     }
+
+    pub fn total_material_cost(&self) -> Option<ColumnIndex> {
+        self.col_total_material_cost
+    }
+
+    pub fn yield_formula(&self) -> Option<ColumnIndex> {
+        self.col_yield_formula
+    }
 }
 
 /// 【入庫】仕入シートのスキーマ
 #[derive(Debug, Clone)]
 pub struct PurchaseSheetSchema {
+    sheet_name: String,
     col_purchase_date: ColumnIndex,
     col_product_code: ColumnIndex,
     col_product_name: ColumnIndex,
     col_unit_price: ColumnIndex,
     col_quantity: ColumnIndex,
     col_freight: ColumnIndex,
+    col_unit: Option<ColumnIndex>, // 任意列（列がない・空欄の場合は kg）
+    col_shipment: Option<ColumnIndex>, // 任意列（便番号）
+    col_shipment_freight: Option<ColumnIndex>, // 任意列（便の運賃総額）
 }
 
 impl PurchaseSheetSchema {
-    pub fn from_headers(headers: &[String]) -> Result<Self> {
-        let mut header_map: HashMap<&str, usize> = HashMap::new();
-        for (idx, header) in headers.iter().enumerate() {
-            let trimmed = header.trim();
-            if !trimmed.is_empty() {
-                header_map.insert(trimmed, idx);
-            }
-        }
-
-        let required_headers = ["仕入日", "商品コード", "商品", "仕入単価", "数量", "運賃"];
-
-        let mut missing = Vec::new();
-        for &header in &required_headers {
-            if !header_map.contains_key(header) {
-                missing.push(header);
-            }
-        }
+    pub const SHEET: &'static str = "【入庫】仕入";
 
-        if !missing.is_empty() {
-            return Err(eyre!(
-                "【入庫】仕入シートに必須カラムが見つかりません: {:?}",
-                missing
-            ));
-        }
+    pub fn from_headers(headers: &[String], mapping: &SchemaMapping) -> Result<Self> {
+        let header_row = HeaderRow::new(headers, mapping, Self::SHEET);
+        header_row.require(&["仕入日", "商品コード", "商品", "仕入単価", "数量", "運賃"])?;
 
         Ok(Self {
-            col_purchase_date: ColumnIndex::new(*header_map.get("仕入日").unwrap()),
-            col_product_code: ColumnIndex::new(*header_map.get("商品コード").unwrap()),
-            col_product_name: ColumnIndex::new(*header_map.get("商品").unwrap()),
-            col_unit_price: ColumnIndex::new(*header_map.get("仕入単価").unwrap()),
-            col_quantity: ColumnIndex::new(*header_map.get("数量").unwrap()),
-            col_freight: ColumnIndex::new(*header_map.get("運賃").unwrap()),
+            sheet_name: mapping.sheet_name(Self::SHEET),
+            col_purchase_date: header_row.column("仕入日"),
+            col_product_code: header_row.column("商品コード"),
+            col_product_name: header_row.column("商品"),
+            col_unit_price: header_row.column("仕入単価"),
+            col_quantity: header_row.column("数量"),
+            col_freight: header_row.column("運賃"),
+            col_unit: header_row.find("単位"),
+            col_shipment: header_row.find("便番号"),
+            col_shipment_freight: header_row.find("運賃総額"),
         })
     }
 
+    /// テンプレートのシート名
+    pub fn sheet_name(&self) -> &str {
+        &self.sheet_name
+    }
+
     pub fn purchase_date(&self) -> ColumnIndex {
         self.col_purchase_date
     }
@@ -172,11 +305,24 @@ impl PurchaseSheetSchema {
     pub fn freight(&self) -> ColumnIndex {
         self.col_freight
     }
+
+    pub fn unit(&self) -> Option<ColumnIndex> {
+        self.col_unit
+    }
+
+    pub fn shipment(&self) -> Option<ColumnIndex> {
+        self.col_shipment
+    }
+
+    pub fn shipment_freight(&self) -> Option<ColumnIndex> {
+        self.col_shipment_freight
+    }
 }
 
 /// 【出庫】売上シートのスキーマ
 #[derive(Debug, Clone)]
 pub struct SalesSheetSchema {
+    sheet_name: String,
     col_sales_date: ColumnIndex,
     col_product_code: ColumnIndex,
     col_product_name: ColumnIndex,
@@ -184,39 +330,26 @@ pub struct SalesSheetSchema {
 }
 
 impl SalesSheetSchema {
-    pub fn from_headers(headers: &[String]) -> Result<Self> {
-        let mut header_map: HashMap<&str, usize> = HashMap::new();
-        for (idx, header) in headers.iter().enumerate() {
-            let trimmed = header.trim();
-            if !trimmed.is_empty() {
-                header_map.insert(trimmed, idx);
-            }
-        }
+    pub const SHEET: &'static str = "【出庫】売上";
 
-        let required_headers = ["売上日", "商品コード", "商品名", "数量"];
-
-        let mut missing = Vec::new();
-        for &header in &required_headers {
-            if !header_map.contains_key(header) {
-                missing.push(header);
-            }
-        }
-
-        if !missing.is_empty() {
-            return Err(eyre!(
-                "【出庫】売上シートに必須カラムが見つかりません: {:?}",
-                missing
-            ));
-        }
+    pub fn from_headers(headers: &[String], mapping: &SchemaMapping) -> Result<Self> {
+        let header_row = HeaderRow::new(headers, mapping, Self::SHEET);
+        header_row.require(&["売上日", "商品コード", "商品名", "数量"])?;
 
         Ok(Self {
-            col_sales_date: ColumnIndex::new(*header_map.get("売上日").unwrap()),
-            col_product_code: ColumnIndex::new(*header_map.get("商品コード").unwrap()),
-            col_product_name: ColumnIndex::new(*header_map.get("商品名").unwrap()),
-            col_quantity: ColumnIndex::new(*header_map.get("数量").unwrap()),
+            sheet_name: mapping.sheet_name(Self::SHEET),
+            col_sales_date: header_row.column("売上日"),
+            col_product_code: header_row.column("商品コード"),
+            col_product_name: header_row.column("商品名"),
+            col_quantity: header_row.column("数量"),
         })
     }
 
+    /// テンプレートのシート名
+    pub fn sheet_name(&self) -> &str {
+        &self.sheet_name
+    }
+
     pub fn sales_date(&self) -> ColumnIndex {
         self.col_sales_date
     }
@@ -233,3 +366,400 @@ impl SalesSheetSchema {
         self.col_quantity
     }
 }
+
+/// 【期首】在庫シートのスキーマ
+#[derive(Debug, Clone)]
+pub struct OpeningBalanceSheetSchema {
+    sheet_name: String,
+    col_product_code: ColumnIndex,
+    col_product_name: Option<ColumnIndex>,
+    col_quantity: ColumnIndex,
+    col_amount: Option<ColumnIndex>,
+}
+
+impl OpeningBalanceSheetSchema {
+    pub const SHEET: &'static str = "【期首】在庫";
+
+    pub fn from_headers(headers: &[String], mapping: &SchemaMapping) -> Result<Self> {
+        let header_row = HeaderRow::new(headers, mapping, Self::SHEET);
+        header_row.require(&["商品コード", "期首数量"])?;
+
+        Ok(Self {
+            sheet_name: mapping.sheet_name(Self::SHEET),
+            col_product_code: header_row.column("商品コード"),
+            col_product_name: header_row.find("商品名"),
+            col_quantity: header_row.column("期首数量"),
+            col_amount: header_row.find("期首金額"),
+        })
+    }
+
+    /// テンプレートのシート名
+    pub fn sheet_name(&self) -> &str {
+        &self.sheet_name
+    }
+
+    pub fn product_code(&self) -> ColumnIndex {
+        self.col_product_code
+    }
+
+    pub fn product_name(&self) -> Option<ColumnIndex> {
+        self.col_product_name
+    }
+
+    pub fn quantity(&self) -> ColumnIndex {
+        self.col_quantity
+    }
+
+    pub fn amount(&self) -> Option<ColumnIndex> {
+        self.col_amount
+    }
+}
+
+/// 配合マスタシートのスキーマ
+#[derive(Debug, Clone)]
+pub struct FormulaSheetSchema {
+    sheet_name: String,
+    col_product_code: ColumnIndex,
+    col_material_code: ColumnIndex,
+    col_consumption_ratio: ColumnIndex,
+    col_valid_from: Option<ColumnIndex>, // 任意列（ない場合は無期限）
+    col_valid_to: Option<ColumnIndex>,   // 任意列（ない場合は無期限）
+}
+
+impl FormulaSheetSchema {
+    pub const SHEET: &'static str = "配合マスタ";
+
+    pub fn from_headers(headers: &[String], mapping: &SchemaMapping) -> Result<Self> {
+        let header_row = HeaderRow::new(headers, mapping, Self::SHEET);
+        header_row.require(&["製造商品コード", "材料商品コード", "消費比率"])?;
+
+        Ok(Self {
+            sheet_name: mapping.sheet_name(Self::SHEET),
+            col_product_code: header_row.column("製造商品コード"),
+            col_material_code: header_row.column("材料商品コード"),
+            col_consumption_ratio: header_row.column("消費比率"),
+            col_valid_from: header_row.find("有効開始日"),
+            col_valid_to: header_row.find("有効終了日"),
+        })
+    }
+
+    /// テンプレートのシート名
+    pub fn sheet_name(&self) -> &str {
+        &self.sheet_name
+    }
+
+    pub fn product_code(&self) -> ColumnIndex {
+        self.col_product_code
+    }
+
+    pub fn material_code(&self) -> ColumnIndex {
+        self.col_material_code
+    }
+
+    pub fn consumption_ratio(&self) -> ColumnIndex {
+        self.col_consumption_ratio
+    }
+
+    pub fn valid_from(&self) -> Option<ColumnIndex> {
+        self.col_valid_from
+    }
+
+    pub fn valid_to(&self) -> Option<ColumnIndex> {
+        self.col_valid_to
+    }
+}
+
+/// 運賃マスタシートのスキーマ
+#[derive(Debug, Clone)]
+pub struct FreightMasterSheetSchema {
+    sheet_name: String,
+    col_freight_code: ColumnIndex,
+    col_pattern_name: ColumnIndex,
+    col_kg_unit_price: ColumnIndex,
+    col_valid_from: ColumnIndex,
+    col_valid_to: ColumnIndex,
+    col_zone: Option<ColumnIndex>, // 任意列
+}
+
+impl FreightMasterSheetSchema {
+    pub const SHEET: &'static str = "運賃マスタ";
+
+    pub fn from_headers(headers: &[String], mapping: &SchemaMapping) -> Result<Self> {
+        let header_row = HeaderRow::new(headers, mapping, Self::SHEET);
+        header_row.require(&[
+            "運賃コード",
+            "パターン名",
+            "Kg単価",
+            "有効開始日",
+            "有効終了日",
+        ])?;
+
+        Ok(Self {
+            sheet_name: mapping.sheet_name(Self::SHEET),
+            col_freight_code: header_row.column("運賃コード"),
+            col_pattern_name: header_row.column("パターン名"),
+            col_kg_unit_price: header_row.column("Kg単価"),
+            col_valid_from: header_row.column("有効開始日"),
+            col_valid_to: header_row.column("有効終了日"),
+            col_zone: header_row.find("地区"),
+        })
+    }
+
+    /// テンプレートのシート名
+    pub fn sheet_name(&self) -> &str {
+        &self.sheet_name
+    }
+
+    pub fn freight_code(&self) -> ColumnIndex {
+        self.col_freight_code
+    }
+
+    pub fn pattern_name(&self) -> ColumnIndex {
+        self.col_pattern_name
+    }
+
+    pub fn kg_unit_price(&self) -> ColumnIndex {
+        self.col_kg_unit_price
+    }
+
+    pub fn valid_from(&self) -> ColumnIndex {
+        self.col_valid_from
+    }
+
+    pub fn valid_to(&self) -> ColumnIndex {
+        self.col_valid_to
+    }
+
+    pub fn zone(&self) -> Option<ColumnIndex> {
+        self.col_zone
+    }
+}
+
+/// 運賃表シートのスキーマ
+#[derive(Debug, Clone)]
+pub struct FreightRateSheetSchema {
+    sheet_name: String,
+    col_pattern_name: ColumnIndex,
+    col_min_quantity: ColumnIndex,
+    col_kg_unit_price: ColumnIndex,
+    col_minimum_charge: Option<ColumnIndex>, // 任意列
+}
+
+impl FreightRateSheetSchema {
+    pub const SHEET: &'static str = "運賃表";
+
+    pub fn from_headers(headers: &[String], mapping: &SchemaMapping) -> Result<Self> {
+        let header_row = HeaderRow::new(headers, mapping, Self::SHEET);
+        header_row.require(&["パターン名", "下限重量", "Kg単価"])?;
+
+        Ok(Self {
+            sheet_name: mapping.sheet_name(Self::SHEET),
+            col_pattern_name: header_row.column("パターン名"),
+            col_min_quantity: header_row.column("下限重量"),
+            col_kg_unit_price: header_row.column("Kg単価"),
+            col_minimum_charge: header_row.find("最低運賃"),
+        })
+    }
+
+    /// テンプレートのシート名
+    pub fn sheet_name(&self) -> &str {
+        &self.sheet_name
+    }
+
+    pub fn pattern_name(&self) -> ColumnIndex {
+        self.col_pattern_name
+    }
+
+    pub fn min_quantity(&self) -> ColumnIndex {
+        self.col_min_quantity
+    }
+
+    pub fn kg_unit_price(&self) -> ColumnIndex {
+        self.col_kg_unit_price
+    }
+
+    pub fn minimum_charge(&self) -> Option<ColumnIndex> {
+        self.col_minimum_charge
+    }
+}
+
+/// 運賃地区割増シートのスキーマ
+#[derive(Debug, Clone)]
+pub struct ZoneSurchargeSheetSchema {
+    sheet_name: String,
+    col_pattern_name: ColumnIndex,
+    col_zone: ColumnIndex,
+    col_surcharge: ColumnIndex,
+}
+
+impl ZoneSurchargeSheetSchema {
+    pub const SHEET: &'static str = "運賃地区割増";
+
+    pub fn from_headers(headers: &[String], mapping: &SchemaMapping) -> Result<Self> {
+        let header_row = HeaderRow::new(headers, mapping, Self::SHEET);
+        header_row.require(&["パターン名", "地区", "割増Kg単価"])?;
+
+        Ok(Self {
+            sheet_name: mapping.sheet_name(Self::SHEET),
+            col_pattern_name: header_row.column("パターン名"),
+            col_zone: header_row.column("地区"),
+            col_surcharge: header_row.column("割増Kg単価"),
+        })
+    }
+
+    /// テンプレートのシート名
+    pub fn sheet_name(&self) -> &str {
+        &self.sheet_name
+    }
+
+    pub fn pattern_name(&self) -> ColumnIndex {
+        self.col_pattern_name
+    }
+
+    pub fn zone(&self) -> ColumnIndex {
+        self.col_zone
+    }
+
+    pub fn surcharge(&self) -> ColumnIndex {
+        self.col_surcharge
+    }
+}
+
+/// 単位換算マスタシートのスキーマ
+#[derive(Debug, Clone)]
+pub struct UnitConversionSheetSchema {
+    sheet_name: String,
+    col_product_code: Option<ColumnIndex>, // 任意列（ない・空欄の場合は全材料に共通）
+    col_unit: ColumnIndex,
+    col_kg_per_unit: ColumnIndex,
+}
+
+impl UnitConversionSheetSchema {
+    pub const SHEET: &'static str = "単位換算マスタ";
+
+    pub fn from_headers(headers: &[String], mapping: &SchemaMapping) -> Result<Self> {
+        let header_row = HeaderRow::new(headers, mapping, Self::SHEET);
+        header_row.require(&["単位", "換算kg"])?;
+
+        Ok(Self {
+            sheet_name: mapping.sheet_name(Self::SHEET),
+            col_product_code: header_row.find("商品コード"),
+            col_unit: header_row.column("単位"),
+            col_kg_per_unit: header_row.column("換算kg"),
+        })
+    }
+
+    /// テンプレートのシート名
+    pub fn sheet_name(&self) -> &str {
+        &self.sheet_name
+    }
+
+    pub fn product_code(&self) -> Option<ColumnIndex> {
+        self.col_product_code
+    }
+
+    pub fn unit(&self) -> ColumnIndex {
+        self.col_unit
+    }
+
+    pub fn kg_per_unit(&self) -> ColumnIndex {
+        self.col_kg_per_unit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_mapping_finds_column_by_alias() {
+        let mapping = SchemaMapping::new().with_column_aliases(
+            "【出庫】売上",
+            "商品コード",
+            vec!["品番".to_string()],
+        );
+
+        let schema = SalesSheetSchema::from_headers(
+            &headers(&["売上日", "品番", "商品名", "数量"]),
+            &mapping,
+        )
+        .unwrap();
+        assert_eq!(schema.product_code().value(), 1);
+    }
+
+    #[test]
+    fn test_mapping_prefers_standard_column_name() {
+        let mapping = SchemaMapping::new().with_column_aliases(
+            "【期首】在庫",
+            "期首数量",
+            vec!["在庫数".to_string()],
+        );
+
+        let schema = OpeningBalanceSheetSchema::from_headers(
+            &headers(&["在庫数", "商品コード", "期首数量"]),
+            &mapping,
+        )
+        .unwrap();
+        assert_eq!(schema.quantity().value(), 2);
+    }
+
+    #[test]
+    fn test_missing_columns_reported_with_template_sheet_name() {
+        let mapping = SchemaMapping::new().with_sheet("【出庫】売上", "出荷実績".to_string());
+
+        let error =
+            SalesSheetSchema::from_headers(&headers(&["売上日", "数量"]), &mapping).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "出荷実績シートに必須カラムが見つかりません: 商品コード, 商品名"
+        );
+    }
+
+    #[test]
+    fn test_formula_schema_lists_all_missing_columns() {
+        let error =
+            FormulaSheetSchema::from_headers(&headers(&["製造商品コード"]), &SchemaMapping::new())
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "配合マスタシートに必須カラムが見つかりません: 材料商品コード, 消費比率"
+        );
+    }
+
+    #[test]
+    fn test_freight_master_schema_optional_zone_by_alias() {
+        let mapping = SchemaMapping::new().with_column_aliases(
+            "運賃マスタ",
+            "地区",
+            vec!["配送先".to_string()],
+        );
+
+        let schema = FreightMasterSheetSchema::from_headers(
+            &headers(&[
+                "運賃コード",
+                "パターン名",
+                "Kg単価",
+                "有効開始日",
+                "有効終了日",
+                "配送先",
+            ]),
+            &mapping,
+        )
+        .unwrap();
+        assert_eq!(schema.zone().map(|c| c.value()), Some(5));
+    }
+
+    #[test]
+    fn test_production_output_schema_skips_missing_columns() {
+        let schema = ProductionOutputSchema::from_headers(
+            &headers(&["生産日", "材料費"]),
+            &SchemaMapping::new(),
+        );
+        assert_eq!(schema.total_material_cost().map(|c| c.value()), Some(1));
+        assert!(schema.raw_material_cost().is_none());
+    }
+}
@@ -0,0 +1,196 @@
+use super::entities::*;
+use super::error::{DomainError, Result};
+use super::value_objects::*;
+use rust_decimal::Decimal;
+
+/// 単位換算ドメインサービス
+///
+/// 仕入の数量・単価を基準単位（kg）に正規化する。材料費計算・在庫計算は kg のみを扱う。
+pub struct UnitConversionService;
+
+impl UnitConversionService {
+    /// 1単位あたりの kg
+    ///
+    /// 材料別の換算、全材料に共通の換算（商品コード空欄）、標準の換算（kg・t）の順に探す。
+    pub fn kg_per_unit(
+        conversions: &[UnitConversion],
+        product_code: &ProductCode,
+        unit: Unit,
+    ) -> Result<Decimal> {
+        let specific = conversions
+            .iter()
+            .find(|c| c.product_code.is_some() && c.applies_to(product_code, unit));
+        let common = || {
+            conversions
+                .iter()
+                .find(|c| c.product_code.is_none() && c.applies_to(product_code, unit))
+        };
+
+        specific
+            .or_else(common)
+            .map(|c| c.kg_per_unit.decimal())
+            .or_else(|| unit.standard_kg())
+            .ok_or_else(|| DomainError::UnitConversionNotFound {
+                code: product_code.value().to_string(),
+                unit: unit.as_str().to_string(),
+            })
+    }
+
+    /// 数量を基準単位（kg）に換算
+    pub fn to_kilograms(
+        conversions: &[UnitConversion],
+        product_code: &ProductCode,
+        quantity: &Quantity,
+    ) -> Result<Quantity> {
+        if quantity.unit() == Unit::Kilogram {
+            return Ok(*quantity);
+        }
+        let kg_per_unit = Self::kg_per_unit(conversions, product_code, quantity.unit())?;
+        quantity.to_kilograms(kg_per_unit)
+    }
+
+    /// 仕入を基準単位（kg）に正規化
+    ///
+    /// 単価と直接指定の運賃Kg単価（仕入の単位あたり）は kg あたりに換算する。
+    /// 運賃コード・ロット運賃はもともと kg あたり・ロットの総額のため換算しない。
+    pub fn normalize_purchase(
+        conversions: &[UnitConversion],
+        purchase: Purchase,
+    ) -> Result<Purchase> {
+        if purchase.unit() == Unit::Kilogram {
+            return Ok(purchase);
+        }
+        let kg_per_unit = Self::kg_per_unit(conversions, &purchase.product_code, purchase.unit())?;
+        let quantity = purchase.quantity.to_kilograms(kg_per_unit)?;
+        let per_kg = |amount: Amount| {
+            amount.divide_by(kg_per_unit).ok_or_else(|| {
+                DomainError::invalid_format("換算kg", &kg_per_unit.to_string(), "0より大きい数値")
+            })
+        };
+        let unit_price = per_kg(purchase.unit_price)?;
+        let freight_code = match purchase.freight_code {
+            FreightCode::DirectPrice(price) => {
+                FreightCode::DirectPrice(per_kg(Amount::new(price)?)?.value())
+            }
+            other => other,
+        };
+
+        Ok(Purchase {
+            unit_price,
+            quantity,
+            freight_code,
+            ..purchase
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(value: &str) -> ProductCode {
+        ProductCode::new(value.to_string()).unwrap()
+    }
+
+    fn bag(product_code: Option<&str>, kg: f64) -> UnitConversion {
+        UnitConversion::new(
+            product_code.map(code),
+            Unit::Bag,
+            Quantity::new(kg).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn purchase(unit_price: f64, quantity: Quantity) -> Purchase {
+        Purchase::new(
+            TransactionDate::new("2026-01-10".to_string()).unwrap(),
+            code("M001"),
+            "材料M001".to_string(),
+            Amount::new(unit_price).unwrap(),
+            quantity,
+            FreightCode::DirectPrice(0.0),
+        )
+    }
+
+    #[test]
+    fn test_material_specific_bag_weight_preferred() {
+        let conversions = vec![bag(None, 20.0), bag(Some("M001"), 25.0)];
+
+        let m001 = UnitConversionService::kg_per_unit(&conversions, &code("M001"), Unit::Bag);
+        let m002 = UnitConversionService::kg_per_unit(&conversions, &code("M002"), Unit::Bag);
+
+        assert_eq!(m001.unwrap(), Decimal::from(25));
+        assert_eq!(m002.unwrap(), Decimal::from(20));
+    }
+
+    #[test]
+    fn test_tonne_uses_standard_conversion() {
+        let kg = UnitConversionService::kg_per_unit(&[], &code("M001"), Unit::Tonne).unwrap();
+        assert_eq!(kg, Decimal::from(1000));
+    }
+
+    #[test]
+    fn test_bag_without_conversion_is_error() {
+        let result = UnitConversionService::kg_per_unit(&[], &code("M001"), Unit::Bag);
+        assert!(matches!(
+            result,
+            Err(DomainError::UnitConversionNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_normalize_purchase_per_tonne() {
+        // 2t @ 30,000円/t → 2,000kg @ 30円/kg
+        let normalized = UnitConversionService::normalize_purchase(
+            &[],
+            purchase(30000.0, Quantity::in_unit(2.0, Unit::Tonne).unwrap()),
+        )
+        .unwrap();
+
+        assert_eq!(normalized.unit(), Unit::Kilogram);
+        assert_eq!(normalized.quantity.value(), 2000.0);
+        assert_eq!(normalized.unit_price.value(), 30.0);
+    }
+
+    #[test]
+    fn test_normalize_purchase_converts_direct_freight_price() {
+        // 運賃 250円/袋（1袋25kg） → 10円/kg
+        let mut bags = purchase(500.0, Quantity::in_unit(10.0, Unit::Bag).unwrap());
+        bags.freight_code = FreightCode::DirectPrice(250.0);
+
+        let normalized =
+            UnitConversionService::normalize_purchase(&[bag(Some("M001"), 25.0)], bags).unwrap();
+
+        assert_eq!(normalized.freight_code.as_direct_price(), Some(10.0));
+    }
+
+    #[test]
+    fn test_normalize_purchase_with_invalid_conversion_is_error() {
+        // 換算kgが0以下の場合は単価0円にせずエラー
+        let mut conversion = bag(Some("M001"), 25.0);
+        conversion.kg_per_unit = Quantity::zero();
+
+        let result = UnitConversionService::normalize_purchase(
+            &[conversion],
+            purchase(500.0, Quantity::in_unit(10.0, Unit::Bag).unwrap()),
+        );
+
+        assert!(matches!(
+            result,
+            Err(DomainError::InvalidFormat { ref field, .. }) if field == "換算kg"
+        ));
+    }
+
+    #[test]
+    fn test_normalize_purchase_per_bag() {
+        // 10袋 @ 500円/袋（1袋25kg） → 250kg @ 20円/kg
+        let normalized = UnitConversionService::normalize_purchase(
+            &[bag(Some("M001"), 25.0)],
+            purchase(500.0, Quantity::in_unit(10.0, Unit::Bag).unwrap()),
+        )
+        .unwrap();
+
+        assert_eq!(normalized.quantity.value(), 250.0);
+        assert_eq!(normalized.unit_price.value(), 20.0);
+    }
+}
@@ -0,0 +1,687 @@
+use super::entities::*;
+use super::error::{DomainError, MasterKind, Result};
+use super::repositories::*;
+use super::services::{CostedProduction, MaterialConsumption, RoundingPolicy};
+use super::value_objects::*;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// 月次受払表の1行（商品別）
+///
+/// 金額はマイナス在庫の場合に負になるため、Amount ではなく Decimal で保持する（材料費の端数処理を適用済み）。
+#[derive(Debug, Clone)]
+pub struct MonthlyInventoryReport {
+    pub month: String, // YYYY-MM
+    pub product_code: ProductCode,
+    pub product_name: String,
+    pub opening_quantity: InventoryBalance,
+    pub opening_amount: Decimal,
+    pub production_quantity: Quantity,
+    pub purchase_quantity: Quantity,
+    pub receipt_amount: Decimal,
+    pub sales_quantity: Quantity,
+    pub material_issue_quantity: Quantity,
+    pub issue_amount: Decimal,
+    pub closing_quantity: InventoryBalance,
+    pub closing_amount: Decimal,
+    pub unit_price: Option<Amount>, // 月末単価（求められない場合はNone）
+}
+
+/// 単価が求められず金額0で評価した受払（商品・月ごと）
+#[derive(Debug, Clone)]
+pub struct MissingPrice {
+    pub month: String, // YYYY-MM
+    pub product_code: ProductCode,
+    pub product_name: String,
+    pub quantity: Decimal, // 金額0で評価した数量の合計
+}
+
+/// 月次受払表の作成結果
+#[derive(Debug, Clone)]
+pub struct MonthlyInventoryValuation {
+    pub reports: Vec<MonthlyInventoryReport>,
+    pub missing_prices: Vec<MissingPrice>,
+}
+
+/// 商品ごとの月中の受払
+#[derive(Default)]
+struct MonthlyMovement {
+    carry_forward: Decimal,
+    production: Decimal,
+    purchase: Decimal,
+    purchase_amount: Decimal,   // 仕入単価で評価した仕入受入金額
+    unpriced_purchase: Decimal, // 仕入データに単価がない仕入受入数量（月末単価で評価）
+    sales: Decimal,
+    material_issue: Decimal,
+    costed_issue: Decimal,        // 材料費計算で評価済みの材料出庫数量
+    costed_issue_amount: Decimal, // 材料費計算の材料出庫金額
+}
+
+/// 前月から繰り越す残高
+struct CarriedBalance {
+    product_name: String,
+    quantity: Decimal,
+    amount: Option<Decimal>, // 期首金額が未入力の場合は当月単価で評価
+}
+
+/// 単価が見つからないことを表すエラーか（それ以外の読み取りエラーは月次受払表のエラーとする）
+fn is_price_missing(e: &DomainError) -> bool {
+    matches!(
+        e,
+        DomainError::NoPurchaseBefore { .. }
+            | DomainError::PriceNotFound { .. }
+            | DomainError::MasterNotFound {
+                master: MasterKind::Purchase,
+                ..
+            }
+    )
+}
+
+/// 月次受払表作成ドメインサービス
+pub struct InventoryValuationService;
+
+impl InventoryValuationService {
+    /// 期首在庫・トランザクション・材料費計算の結果から月次受払表を作成
+    ///
+    /// - 仕入受入は仕入データの仕入単価（仕入日以前で最も新しい仕入）で評価する
+    /// - 材料出庫は材料費計算の材料金額（単価計算方法を適用した単価）を使う
+    /// - 生産受入・売上払出・期末単価は月末時点の材料単価（単価計算方法を適用）で評価し、
+    ///   仕入がない商品は期首在庫の単価を使う
+    /// - 期首・受入・払出金額は月ごとの合計に材料費の端数処理を適用する
+    /// - 期末金額 = 期首金額 + 受入金額 − 払出金額
+    ///
+    /// 対象月は期首日付（指定がなければ最初の受払の月、受払もなければ当月）から最後の受払の月まで。
+    /// 単価が求められず金額0で評価した受払は `missing_prices` に返す。
+    pub fn create_monthly_report<P>(
+        transactions: &[InventoryTransaction],
+        costed_productions: &[CostedProduction],
+        opening_balances: &[OpeningBalance],
+        period_start: Option<&TransactionDate>,
+        purchase_repo: &P,
+        rounding: &RoundingPolicy,
+    ) -> Result<MonthlyInventoryValuation>
+    where
+        P: PurchaseRepository,
+    {
+        let costed_issues: Vec<(&TransactionDate, &MaterialConsumption)> = costed_productions
+            .iter()
+            .flat_map(|c| {
+                c.result
+                    .consumptions
+                    .iter()
+                    .filter(|m| !m.quantity.decimal().is_zero())
+                    .map(move |m| (&c.production.production_date, m))
+            })
+            .collect();
+
+        let dates = transactions
+            .iter()
+            .map(|t| &t.date)
+            .chain(costed_issues.iter().map(|(date, _)| *date));
+        let first_date = match period_start {
+            Some(date) => date.clone(),
+            None => dates
+                .clone()
+                .min()
+                .cloned()
+                .unwrap_or_else(TransactionDate::today),
+        };
+        let last_date = dates.max().cloned().unwrap_or_else(|| first_date.clone());
+
+        // 月ごと・商品ごとの受払
+        let mut movements: BTreeMap<String, BTreeMap<String, MonthlyMovement>> = BTreeMap::new();
+        let mut product_names: BTreeMap<String, String> = BTreeMap::new();
+        let mut purchase_histories: BTreeMap<String, Vec<Purchase>> = BTreeMap::new();
+        for transaction in transactions {
+            let code = transaction.product_code.value().to_string();
+            product_names
+                .entry(code.clone())
+                .or_insert_with(|| transaction.product_name.clone());
+
+            transaction.quantity.ensure_unit(Unit::Kilogram)?;
+            let quantity = transaction.quantity.decimal();
+
+            // 仕入受入の仕入単価（仕入日以前で最も新しい仕入）
+            let purchase_price = if transaction.inventory_type == InventoryType::Purchase {
+                if !purchase_histories.contains_key(&code) {
+                    let history = match purchase_repo.find_history(&transaction.product_code) {
+                        Ok(history) => history,
+                        Err(e) if is_price_missing(&e) => Vec::new(),
+                        Err(e) => return Err(e),
+                    };
+                    purchase_histories.insert(code.clone(), history);
+                }
+                purchase_histories[&code]
+                    .iter()
+                    .rev()
+                    .find(|p| p.purchase_date <= transaction.date)
+                    .map(|p| p.unit_price)
+            } else {
+                None
+            };
+
+            let movement = movements
+                .entry(transaction.date.month_label())
+                .or_default()
+                .entry(code)
+                .or_default();
+            match transaction.inventory_type {
+                InventoryType::CarryForward => movement.carry_forward += quantity,
+                InventoryType::Production => movement.production += quantity,
+                InventoryType::Purchase => {
+                    movement.purchase += quantity;
+                    match purchase_price {
+                        Some(price) => movement.purchase_amount += quantity * price.decimal(),
+                        None => movement.unpriced_purchase += quantity,
+                    }
+                }
+                InventoryType::Sales => movement.sales += quantity,
+                InventoryType::MaterialIssue => movement.material_issue += quantity,
+            }
+        }
+        for (date, consumption) in &costed_issues {
+            let code = consumption.material_code.value().to_string();
+            product_names
+                .entry(code.clone())
+                .or_insert_with(|| consumption.material_name.clone());
+
+            consumption.quantity.ensure_unit(Unit::Kilogram)?;
+            let movement = movements
+                .entry(date.month_label())
+                .or_default()
+                .entry(code)
+                .or_default();
+            movement.material_issue += consumption.quantity.decimal();
+            movement.costed_issue += consumption.quantity.decimal();
+            movement.costed_issue_amount += consumption.total_cost.decimal();
+        }
+
+        let mut carried: BTreeMap<String, CarriedBalance> = opening_balances
+            .iter()
+            .map(|o| {
+                (
+                    o.product_code.value().to_string(),
+                    CarriedBalance {
+                        product_name: o.product_name.clone(),
+                        quantity: o.quantity.decimal(),
+                        amount: o.amount.map(|a| a.decimal()),
+                    },
+                )
+            })
+            .collect();
+        let opening_prices: BTreeMap<String, Amount> = opening_balances
+            .iter()
+            .filter_map(|o| Some((o.product_code.value().to_string(), o.unit_price()?)))
+            .collect();
+
+        let mut reports = Vec::new();
+        let mut missing_prices = Vec::new();
+        let mut month = first_date.first_day_of_month();
+        while month <= last_date {
+            let month_label = month.month_label();
+            let month_end = month.last_day_of_month();
+            let empty = BTreeMap::new();
+            let month_movements = movements.get(&month_label).unwrap_or(&empty);
+
+            // 繰越残高のある商品と当月に受払のある商品
+            let codes: BTreeSet<String> = carried
+                .iter()
+                .filter(|(_, b)| !b.quantity.is_zero())
+                .map(|(code, _)| code.clone())
+                .chain(month_movements.keys().cloned())
+                .collect();
+
+            for code in codes {
+                let product_code = ProductCode::new(code.clone())?;
+                let default_movement = MonthlyMovement::default();
+                let movement = month_movements.get(&code).unwrap_or(&default_movement);
+                let previous = carried.get(&code);
+
+                let product_name = previous
+                    .map(|b| b.product_name.clone())
+                    .or_else(|| product_names.get(&code).cloned())
+                    .unwrap_or_else(|| code.clone());
+
+                let unit_price = match purchase_repo.find_price_as_of(&product_code, &month_end) {
+                    Ok(purchase) => Some(purchase.unit_price),
+                    Err(e) if is_price_missing(&e) => None,
+                    Err(e) => return Err(e),
+                }
+                .or_else(|| opening_prices.get(&code).copied());
+
+                // 月末単価で評価（単価がなければ金額0とし、数量を記録）
+                let mut unpriced = Decimal::ZERO;
+                let mut valuate = |quantity: Decimal| match unit_price {
+                    Some(price) => quantity * price.decimal(),
+                    None => {
+                        unpriced += quantity.abs();
+                        Decimal::ZERO
+                    }
+                };
+
+                // 繰越トランザクションは期首数量に含める
+                let previous_quantity = previous.map(|b| b.quantity).unwrap_or_default();
+                let opening_quantity = previous_quantity + movement.carry_forward;
+                let opening_amount = rounding.round_inventory_amount(
+                    match previous.and_then(|b| b.amount) {
+                        Some(amount) => amount,
+                        None => valuate(previous_quantity),
+                    } + valuate(movement.carry_forward),
+                );
+
+                let receipt_quantity = movement.production + movement.purchase;
+                let issue_quantity = movement.sales + movement.material_issue;
+                let closing_quantity = opening_quantity + receipt_quantity - issue_quantity;
+
+                let receipt_amount = rounding.round_inventory_amount(
+                    movement.purchase_amount
+                        + valuate(movement.unpriced_purchase)
+                        + valuate(movement.production),
+                );
+                let issue_amount = rounding.round_inventory_amount(
+                    movement.costed_issue_amount + valuate(issue_quantity - movement.costed_issue),
+                );
+                let closing_amount = opening_amount + receipt_amount - issue_amount;
+
+                if !unpriced.is_zero() {
+                    missing_prices.push(MissingPrice {
+                        month: month_label.clone(),
+                        product_code: product_code.clone(),
+                        product_name: product_name.clone(),
+                        quantity: unpriced,
+                    });
+                }
+
+                reports.push(MonthlyInventoryReport {
+                    month: month_label.clone(),
+                    product_code,
+                    product_name: product_name.clone(),
+                    opening_quantity: InventoryBalance::from_decimal(opening_quantity),
+                    opening_amount,
+                    production_quantity: Quantity::from_decimal(movement.production)?,
+                    purchase_quantity: Quantity::from_decimal(movement.purchase)?,
+                    receipt_amount,
+                    sales_quantity: Quantity::from_decimal(movement.sales)?,
+                    material_issue_quantity: Quantity::from_decimal(movement.material_issue)?,
+                    issue_amount,
+                    closing_quantity: InventoryBalance::from_decimal(closing_quantity),
+                    closing_amount,
+                    unit_price,
+                });
+
+                carried.insert(
+                    code,
+                    CarriedBalance {
+                        product_name,
+                        quantity: closing_quantity,
+                        amount: Some(closing_amount),
+                    },
+                );
+            }
+
+            month = month.next_month();
+        }
+
+        Ok(MonthlyInventoryValuation {
+            reports,
+            missing_prices,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::error::DomainError;
+    use crate::domain::services::MaterialCostResult;
+    use std::collections::HashMap;
+
+    struct MockPurchaseRepository {
+        purchases: HashMap<String, Vec<Purchase>>,
+    }
+
+    impl PurchaseRepository for MockPurchaseRepository {
+        fn find_price_as_of(
+            &self,
+            product_code: &ProductCode,
+            date: &TransactionDate,
+        ) -> Result<Purchase> {
+            self.find_history(product_code)?
+                .into_iter()
+                .rev()
+                .find(|p| p.purchase_date <= *date)
+                .ok_or_else(|| DomainError::NoPurchaseBefore {
+                    code: product_code.value().to_string(),
+                    date: date.value().to_string(),
+                })
+        }
+
+        fn find_history(&self, product_code: &ProductCode) -> Result<Vec<Purchase>> {
+            Ok(self
+                .purchases
+                .get(product_code.value())
+                .cloned()
+                .unwrap_or_default())
+        }
+
+        fn find_all(&self) -> Result<Vec<Purchase>> {
+            Ok(self.purchases.values().flatten().cloned().collect())
+        }
+    }
+
+    fn purchase(date: &str, unit_price: f64, quantity: f64) -> Purchase {
+        Purchase::new(
+            TransactionDate::new(date.to_string()).unwrap(),
+            ProductCode::new("M001".to_string()).unwrap(),
+            "材料A".to_string(),
+            Amount::new(unit_price).unwrap(),
+            Quantity::new(quantity).unwrap(),
+            FreightCode::DirectPrice(0.0),
+        )
+    }
+
+    fn transaction(
+        date: &str,
+        inventory_type: InventoryType,
+        quantity: f64,
+    ) -> InventoryTransaction {
+        InventoryTransaction::new(
+            TransactionDate::new(date.to_string()).unwrap(),
+            inventory_type,
+            ProductCode::new("M001".to_string()).unwrap(),
+            "材料A".to_string(),
+            Quantity::new(quantity).unwrap(),
+        )
+    }
+
+    fn repository() -> MockPurchaseRepository {
+        let mut purchases = HashMap::new();
+        purchases.insert(
+            "M001".to_string(),
+            vec![
+                purchase("2026-01-10", 100.0, 200.0),
+                purchase("2026-02-10", 120.0, 100.0),
+            ],
+        );
+        MockPurchaseRepository { purchases }
+    }
+
+    fn costed_production(date: &str, quantity: f64, unit_price: f64) -> CostedProduction {
+        let production = Production::new(
+            TransactionDate::new(date.to_string()).unwrap(),
+            ProductCode::new("P001".to_string()).unwrap(),
+            Quantity::new(quantity).unwrap(),
+            YieldRate::new(1.0).unwrap(),
+            Amount::zero(),
+            Amount::zero(),
+        );
+        let consumption = MaterialConsumption {
+            material_code: ProductCode::new("M001".to_string()).unwrap(),
+            material_name: "材料A".to_string(),
+            quantity: Quantity::new(quantity).unwrap(),
+            unit_price: Amount::new(unit_price).unwrap(),
+            purchase_date: TransactionDate::new(date.to_string()).unwrap(),
+            total_cost: Amount::new(quantity * unit_price).unwrap(),
+            freight_cost: Amount::zero(),
+            purchase_quantity: Quantity::zero(),
+            freight_code_str: String::new(),
+            freight_kg_price: 0.0,
+            lot_allocations: Vec::new(),
+            lot_shortage: Quantity::zero(),
+        };
+        CostedProduction {
+            production,
+            result: MaterialCostResult {
+                consumptions: vec![consumption],
+                intermediates: Vec::new(),
+                explosion: Vec::new(),
+                total_freight_cost: Amount::zero(),
+            },
+        }
+    }
+
+    fn opening(quantity: f64, amount: Option<f64>) -> OpeningBalance {
+        OpeningBalance::new(
+            ProductCode::new("M001".to_string()).unwrap(),
+            "材料A".to_string(),
+            Quantity::new(quantity).unwrap(),
+            amount.map(|a| Amount::new(a).unwrap()),
+        )
+    }
+
+    #[test]
+    fn test_monthly_report() {
+        // 期首 50kg / 4,500円
+        // 1月: 仕入 200kg（仕入単価 100円）、材料出庫 150kg（月末単価 100円）
+        // 2月: 仕入 100kg（仕入単価 120円）、材料出庫 80kg（月末単価 120円）
+        let transactions = vec![
+            transaction("2026-01-10", InventoryType::Purchase, 200.0),
+            transaction("2026-01-20", InventoryType::MaterialIssue, 150.0),
+            transaction("2026-02-10", InventoryType::Purchase, 100.0),
+            transaction("2026-02-20", InventoryType::MaterialIssue, 80.0),
+        ];
+        let opening_balances = vec![opening(50.0, Some(4500.0))];
+
+        let valuation = InventoryValuationService::create_monthly_report(
+            &transactions,
+            &[],
+            &opening_balances,
+            None,
+            &repository(),
+            &RoundingPolicy::default(),
+        )
+        .unwrap();
+        let reports = valuation.reports;
+
+        assert_eq!(reports.len(), 2);
+        assert!(valuation.missing_prices.is_empty());
+
+        let january = &reports[0];
+        assert_eq!(january.month, "2026-01");
+        assert_eq!(january.opening_quantity.value(), 50.0);
+        assert_eq!(january.opening_amount, Decimal::from(4500));
+        assert_eq!(january.purchase_quantity.value(), 200.0);
+        assert_eq!(january.receipt_amount, Decimal::from(20000));
+        assert_eq!(january.material_issue_quantity.value(), 150.0);
+        assert_eq!(january.issue_amount, Decimal::from(15000));
+        assert_eq!(january.closing_quantity.value(), 100.0);
+        // 4,500 + 20,000 − 15,000
+        assert_eq!(january.closing_amount, Decimal::from(9500));
+
+        let february = &reports[1];
+        assert_eq!(february.month, "2026-02");
+        assert_eq!(february.opening_quantity.value(), 100.0);
+        assert_eq!(february.opening_amount, Decimal::from(9500));
+        assert_eq!(february.receipt_amount, Decimal::from(12000));
+        assert_eq!(february.issue_amount, Decimal::from(9600));
+        assert_eq!(february.closing_quantity.value(), 120.0);
+        assert_eq!(february.closing_amount, Decimal::from(11900));
+    }
+
+    #[test]
+    fn test_monthly_report_values_receipts_at_purchase_cost() {
+        // 1月に 100円 と 130円 の仕入（月末単価は 130円）
+        // 受入金額は仕入ごとの仕入単価: 100×100 + 100×130 = 23,000
+        let mut purchases = HashMap::new();
+        purchases.insert(
+            "M001".to_string(),
+            vec![
+                purchase("2026-01-05", 100.0, 100.0),
+                purchase("2026-01-25", 130.0, 100.0),
+            ],
+        );
+        let repo = MockPurchaseRepository { purchases };
+        let transactions = vec![
+            transaction("2026-01-05", InventoryType::Purchase, 100.0),
+            transaction("2026-01-25", InventoryType::Purchase, 100.0),
+        ];
+
+        let reports = InventoryValuationService::create_monthly_report(
+            &transactions,
+            &[],
+            &[],
+            None,
+            &repo,
+            &RoundingPolicy::default(),
+        )
+        .unwrap()
+        .reports;
+
+        assert_eq!(reports[0].receipt_amount, Decimal::from(23000));
+        assert_eq!(reports[0].closing_amount, Decimal::from(23000));
+        assert_eq!(reports[0].unit_price.map(|p| p.value()), Some(130.0));
+    }
+
+    #[test]
+    fn test_monthly_report_values_issues_at_material_cost() {
+        // 材料出庫は材料費計算の金額（単価 90円）を使い、月末単価（100円）では評価しない
+        let transactions = vec![transaction("2026-01-10", InventoryType::Purchase, 200.0)];
+        let costed = vec![costed_production("2026-01-20", 150.0, 90.0)];
+
+        let reports = InventoryValuationService::create_monthly_report(
+            &transactions,
+            &costed,
+            &[],
+            None,
+            &repository(),
+            &RoundingPolicy::default(),
+        )
+        .unwrap()
+        .reports;
+
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].material_issue_quantity.value(), 150.0);
+        assert_eq!(reports[0].issue_amount, Decimal::from(13500));
+        assert_eq!(reports[0].closing_quantity.value(), 50.0);
+        // 20,000 − 13,500
+        assert_eq!(reports[0].closing_amount, Decimal::from(6500));
+    }
+
+    #[test]
+    fn test_monthly_report_carries_balance_through_empty_month() {
+        // 2月に受払がなくても残高のある商品は出力する
+        let transactions = vec![
+            transaction("2026-01-10", InventoryType::Purchase, 200.0),
+            transaction("2026-03-05", InventoryType::MaterialIssue, 50.0),
+        ];
+
+        let reports = InventoryValuationService::create_monthly_report(
+            &transactions,
+            &[],
+            &[],
+            None,
+            &repository(),
+            &RoundingPolicy::default(),
+        )
+        .unwrap()
+        .reports;
+
+        let months: Vec<&str> = reports.iter().map(|r| r.month.as_str()).collect();
+        assert_eq!(months, vec!["2026-01", "2026-02", "2026-03"]);
+        assert_eq!(reports[1].opening_quantity.value(), 200.0);
+        assert_eq!(reports[1].closing_quantity.value(), 200.0);
+        assert_eq!(reports[2].closing_quantity.value(), 150.0);
+    }
+
+    #[test]
+    fn test_monthly_report_without_unit_price() {
+        // 仕入・期首金額のない製品は単価なし（金額0）として報告する
+        let transactions = vec![InventoryTransaction::new(
+            TransactionDate::new("2026-01-10".to_string()).unwrap(),
+            InventoryType::Production,
+            ProductCode::new("P001".to_string()).unwrap(),
+            "製品A".to_string(),
+            Quantity::new(100.0).unwrap(),
+        )];
+
+        let valuation = InventoryValuationService::create_monthly_report(
+            &transactions,
+            &[],
+            &[],
+            None,
+            &repository(),
+            &RoundingPolicy::default(),
+        )
+        .unwrap();
+        let reports = valuation.reports;
+
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].unit_price.is_none());
+        assert_eq!(reports[0].production_quantity.value(), 100.0);
+        assert_eq!(reports[0].closing_amount, Decimal::from(0));
+
+        assert_eq!(valuation.missing_prices.len(), 1);
+        let missing = &valuation.missing_prices[0];
+        assert_eq!(missing.month, "2026-01");
+        assert_eq!(missing.product_code.value(), "P001");
+        assert_eq!(missing.quantity, Decimal::from(100));
+    }
+
+    #[test]
+    fn test_monthly_report_opening_balance_only() {
+        // 受払がなくても期首在庫の行を期首日付の月で出力する
+        let period_start = TransactionDate::new("2026-04-01".to_string()).unwrap();
+
+        let valuation = InventoryValuationService::create_monthly_report(
+            &[],
+            &[],
+            &[opening(50.0, Some(4500.0))],
+            Some(&period_start),
+            &repository(),
+            &RoundingPolicy::default(),
+        )
+        .unwrap();
+
+        assert_eq!(valuation.reports.len(), 1);
+        let report = &valuation.reports[0];
+        assert_eq!(report.month, "2026-04");
+        assert_eq!(report.opening_quantity.value(), 50.0);
+        assert_eq!(report.opening_amount, Decimal::from(4500));
+        assert_eq!(report.closing_quantity.value(), 50.0);
+        assert_eq!(report.closing_amount, Decimal::from(4500));
+    }
+
+    #[test]
+    fn test_monthly_report_no_transactions() {
+        let reports = InventoryValuationService::create_monthly_report(
+            &[],
+            &[],
+            &[],
+            None,
+            &repository(),
+            &RoundingPolicy::default(),
+        )
+        .unwrap()
+        .reports;
+        assert!(reports.is_empty());
+    }
+
+    #[test]
+    fn test_monthly_report_amounts_use_material_cost_rounding() {
+        // 材料費の端数処理（切り捨て、円未満）を期首・払出金額に適用
+        // 期首 50kg / 1,000.55円 → 1,000円、材料出庫 3kg × 33.3333円 = 99.9999円 → 99円
+        let rounding = RoundingPolicy::new(
+            RoundingMode::HalfUp,
+            RoundingMode::HalfUp,
+            RoundingMode::HalfUp,
+            RoundingMode::Truncate,
+            0,
+        )
+        .unwrap();
+
+        let reports = InventoryValuationService::create_monthly_report(
+            &[],
+            &[costed_production("2026-01-20", 3.0, 33.3333)],
+            &[opening(50.0, Some(1000.55))],
+            None,
+            &repository(),
+            &rounding,
+        )
+        .unwrap()
+        .reports;
+
+        assert_eq!(reports[0].opening_amount, Decimal::from(1000));
+        assert_eq!(reports[0].issue_amount, Decimal::from(99));
+        assert_eq!(reports[0].closing_amount, Decimal::from(901));
+    }
+}
@@ -1,21 +1,32 @@
 mod amount;
 mod consumption_ratio;
+mod decimal;
+mod freight_allocation_method;
 mod freight_code;
 mod inventory_balance;
 mod inventory_type;
 mod pattern_name;
+mod pricing_method;
 mod product_code;
 mod quantity;
+mod rounding_mode;
 mod transaction_date;
+mod unit;
+mod yield_method;
 mod yield_rate;
 
 pub use amount::Amount;
 pub use consumption_ratio::ConsumptionRatio;
+pub use freight_allocation_method::FreightAllocationMethod;
 pub use freight_code::FreightCode;
 pub use inventory_balance::InventoryBalance;
 pub use inventory_type::InventoryType;
 pub use pattern_name::PatternName;
+pub use pricing_method::PricingMethod;
 pub use product_code::ProductCode;
 pub use quantity::Quantity;
+pub use rounding_mode::RoundingMode;
 pub use transaction_date::TransactionDate;
+pub use unit::Unit;
+pub use yield_method::YieldMethod;
 pub use yield_rate::YieldRate;
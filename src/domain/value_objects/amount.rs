@@ -1,22 +1,38 @@
-use color_eyre::{Result, eyre::eyre};
+use super::RoundingMode;
+use super::decimal::{decimal_from_f64, decimal_to_f64, round_to_scale};
+use crate::domain::error::{DomainError, Result};
+use rust_decimal::Decimal;
 
 /// 金額（小計）
-#[derive(Debug, Clone, Copy)]
-pub struct Amount(f64);
+///
+/// 小数第4位までの固定小数点で保持する。乗除算の結果は小数第4位に四捨五入する。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Amount(Decimal);
 
 impl Amount {
+    /// 保持する小数点以下の桁数
+    pub const SCALE: u32 = 4;
+
     pub fn new(value: f64) -> Result<Self> {
-        if value < 0.0 {
-            return Err(eyre!("金額が負の値です: {}", value));
+        Self::from_decimal(decimal_from_f64("金額", value, Self::SCALE)?)
+    }
+
+    pub fn from_decimal(value: Decimal) -> Result<Self> {
+        if value.is_sign_negative() && !value.is_zero() {
+            return Err(DomainError::negative("金額", decimal_to_f64(value)));
         }
-        Ok(Self(value))
+        Ok(Self(round_to_scale(value, Self::SCALE)))
     }
 
     pub fn zero() -> Self {
-        Self(0.0)
+        Self(Decimal::ZERO)
     }
 
     pub fn value(&self) -> f64 {
+        decimal_to_f64(self.0)
+    }
+
+    pub fn decimal(&self) -> Decimal {
         self.0
     }
 
@@ -24,12 +40,22 @@ impl Amount {
         Amount(self.0 + other.0)
     }
 
-    pub fn multiply(&self, ratio: f64) -> Amount {
-        Amount(self.0 * ratio)
+    /// 金額 × 係数（小数第4位に四捨五入）
+    pub fn multiply(&self, factor: Decimal) -> Amount {
+        Amount(round_to_scale(self.0 * factor, Self::SCALE))
+    }
+
+    /// 金額 ÷ 除数（小数第4位に四捨五入、除数が0以下の場合はNone）
+    pub fn divide_by(&self, divisor: Decimal) -> Option<Amount> {
+        if divisor <= Decimal::ZERO {
+            return None;
+        }
+        Some(Amount(round_to_scale(self.0 / divisor, Self::SCALE)))
     }
 
-    pub fn divide_by(&self, divisor: f64) -> Amount {
-        Amount(self.0 / divisor)
+    /// 小数点以下 digits 桁に端数処理（digits = 0 で円未満を処理）
+    pub fn round(&self, mode: RoundingMode, digits: u32) -> Amount {
+        Amount(self.0.round_dp_with_strategy(digits, mode.strategy()))
     }
 }
 
@@ -66,14 +92,52 @@ mod tests {
     #[test]
     fn test_amount_multiply() {
         let amount = Amount::new(100.0).unwrap();
-        let result = amount.multiply(2.5);
+        let result = amount.multiply(Decimal::new(25, 1));
         assert_eq!(result.value(), 250.0);
     }
 
     #[test]
     fn test_amount_divide() {
         let amount = Amount::new(100.0).unwrap();
-        let result = amount.divide_by(4.0);
+        let result = amount.divide_by(Decimal::from(4)).unwrap();
         assert_eq!(result.value(), 25.0);
     }
+
+    #[test]
+    fn test_amount_divide_by_zero() {
+        let amount = Amount::new(100.0).unwrap();
+        assert!(amount.divide_by(Decimal::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_amount_sum_is_exact() {
+        // f64 では 0.1 を10回足しても 1.0 にならない
+        let total = (0..10).fold(Amount::zero(), |acc, _| acc.add(&Amount::new(0.1).unwrap()));
+        assert_eq!(total.decimal(), Decimal::ONE);
+    }
+
+    #[test]
+    fn test_amount_rounding_half_away_from_zero() {
+        // 100 ÷ 3 = 33.33333... → 33.3333
+        let amount = Amount::new(100.0).unwrap();
+        assert_eq!(
+            amount.divide_by(Decimal::from(3)).unwrap().decimal(),
+            Decimal::new(333333, 4)
+        );
+        // 0.00005 → 0.0001
+        let half = Amount::new(1.0).unwrap().multiply(Decimal::new(5, 5));
+        assert_eq!(half.decimal(), Decimal::new(1, 4));
+    }
+
+    #[test]
+    fn test_amount_round_by_mode() {
+        let amount = Amount::new(1234.5).unwrap();
+        assert_eq!(amount.round(RoundingMode::HalfUp, 0).value(), 1235.0);
+        assert_eq!(amount.round(RoundingMode::HalfEven, 0).value(), 1234.0);
+        assert_eq!(amount.round(RoundingMode::Truncate, 0).value(), 1234.0);
+
+        let amount = Amount::new(1233.01).unwrap();
+        assert_eq!(amount.round(RoundingMode::Ceiling, 0).value(), 1234.0);
+        assert_eq!(amount.round(RoundingMode::Truncate, 1).value(), 1233.0);
+    }
 }
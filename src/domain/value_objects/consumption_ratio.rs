@@ -1,18 +1,31 @@
-use color_eyre::{Result, eyre::eyre};
+use super::decimal::{decimal_from_f64, decimal_to_f64, round_to_scale};
+use crate::domain::error::{DomainError, Result};
+use rust_decimal::Decimal;
 
-/// 消費比率
-#[derive(Debug, Clone, Copy)]
-pub struct ConsumptionRatio(f64);
+/// 消費比率（小数第6位までの固定小数点）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConsumptionRatio(Decimal);
 
 impl ConsumptionRatio {
+    /// 保持する小数点以下の桁数
+    pub const SCALE: u32 = 6;
+
     pub fn new(value: f64) -> Result<Self> {
-        if value < 0.0 {
-            return Err(eyre!("消費比率が負の値です: {}", value));
+        Self::from_decimal(decimal_from_f64("消費比率", value, Self::SCALE)?)
+    }
+
+    pub fn from_decimal(value: Decimal) -> Result<Self> {
+        if value.is_sign_negative() && !value.is_zero() {
+            return Err(DomainError::negative("消費比率", decimal_to_f64(value)));
         }
-        Ok(Self(value))
+        Ok(Self(round_to_scale(value, Self::SCALE)))
     }
 
     pub fn value(&self) -> f64 {
+        decimal_to_f64(self.0)
+    }
+
+    pub fn decimal(&self) -> Decimal {
         self.0
     }
 }
@@ -0,0 +1,20 @@
+use crate::domain::error::{DomainError, Result};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// 値オブジェクト内部の端数処理（指定桁数に四捨五入）
+pub fn round_to_scale(value: Decimal, scale: u32) -> Decimal {
+    value.round_dp_with_strategy(scale, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// f64 を指定桁数の固定小数点に変換（Excelのセル値など、入力は最短表現の10進数として扱う）
+pub fn decimal_from_f64(field: &str, value: f64, scale: u32) -> Result<Decimal> {
+    Decimal::from_f64(value)
+        .map(|d| round_to_scale(d, scale))
+        .ok_or_else(|| DomainError::invalid_format(field, &value.to_string(), "数値"))
+}
+
+/// 表示・出力用に f64 に変換
+pub fn decimal_to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or_default()
+}
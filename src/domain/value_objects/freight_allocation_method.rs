@@ -0,0 +1,58 @@
+use crate::domain::error::{DomainError, Result};
+
+/// 便の運賃総額を仕入ロットに配賦する基準
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FreightAllocationMethod {
+    Weight, // 重量（仕入数量）按分
+    Value,  // 金額（仕入単価 × 数量）按分
+    Equal,  // 均等割り
+}
+
+impl FreightAllocationMethod {
+    pub fn new(value: String) -> Result<Self> {
+        match value.trim() {
+            "weight" | "重量按分" => Ok(FreightAllocationMethod::Weight),
+            "value" | "金額按分" => Ok(FreightAllocationMethod::Value),
+            "equal" | "均等割り" => Ok(FreightAllocationMethod::Equal),
+            other => Err(DomainError::invalid_format(
+                "運賃の配賦基準",
+                other,
+                "weight, value, equal",
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            FreightAllocationMethod::Weight => "重量按分",
+            FreightAllocationMethod::Value => "金額按分",
+            FreightAllocationMethod::Equal => "均等割り",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocation_method_from_config_key() {
+        assert_eq!(
+            FreightAllocationMethod::new("weight".to_string()).unwrap(),
+            FreightAllocationMethod::Weight
+        );
+        assert_eq!(
+            FreightAllocationMethod::new(" equal ".to_string()).unwrap(),
+            FreightAllocationMethod::Equal
+        );
+        assert_eq!(
+            FreightAllocationMethod::new("金額按分".to_string()).unwrap(),
+            FreightAllocationMethod::Value
+        );
+    }
+
+    #[test]
+    fn test_allocation_method_invalid() {
+        assert!(FreightAllocationMethod::new("distance".to_string()).is_err());
+    }
+}
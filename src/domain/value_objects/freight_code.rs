@@ -1,10 +1,12 @@
-use color_eyre::{Result, eyre::eyre};
+use super::Amount;
+use crate::domain::error::{DomainError, Result};
 
-/// 運賃コード（T01形式）または直接のKg単価（数値）
+/// 運賃コード（T01形式）、直接のKg単価（数値）、または仕入ロットの運賃額
 #[derive(Debug, Clone)]
 pub enum FreightCode {
-    Code(String),     // T01形式のコード
-    DirectPrice(f64), // 直接指定されたKg単価
+    Code(String),       // T01形式のコード
+    DirectPrice(f64),   // 直接指定されたKg単価
+    LotFreight(Amount), // 仕入ロットが負担する運賃額（便の運賃総額を配賦）
 }
 
 impl FreightCode {
@@ -12,13 +14,13 @@ impl FreightCode {
         let trimmed = value.trim();
 
         if trimmed.is_empty() {
-            return Err(eyre!("運賃コードまたは単価が空です"));
+            return Err(DomainError::empty("運賃コードまたは単価"));
         }
 
         // 数値かどうかチェック
         if let Ok(price) = trimmed.parse::<f64>() {
             if price < 0.0 {
-                return Err(eyre!("運賃単価が負の値です: {}", price));
+                return Err(DomainError::negative("運賃単価", price));
             }
             return Ok(FreightCode::DirectPrice(price));
         }
@@ -31,16 +33,19 @@ impl FreightCode {
             }
         }
 
-        Err(eyre!(
-            "運賃コードの形式が不正です: '{}'\n  有効な形式: T01～T99 または数値 (例: T01, 150.5)",
-            trimmed
+        Err(DomainError::invalid_format(
+            "運賃コード",
+            trimmed,
+            "T01～T99 または数値 (例: T01, 150.5)",
         ))
     }
 
+    #[allow(dead_code)] // 公開APIとして残す（現在はテストのみで使用）
     pub fn is_code(&self) -> bool {
         matches!(self, FreightCode::Code(_))
     }
 
+    #[allow(dead_code)]
     pub fn is_direct_price(&self) -> bool {
         matches!(self, FreightCode::DirectPrice(_))
     }
@@ -1,16 +1,26 @@
-use color_eyre::Result;
+use super::Quantity;
+use super::decimal::{decimal_to_f64, round_to_scale};
+use rust_decimal::Decimal;
 
-/// 在庫残高（負の値も許容）
-#[derive(Debug, Clone, Copy)]
-pub struct InventoryBalance(f64);
+/// 在庫残高（負の値も許容、数量と同じ桁数の固定小数点）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InventoryBalance(Decimal);
 
 impl InventoryBalance {
-    pub fn new(value: f64) -> Result<Self> {
-        // 在庫残高は負の値も許容（マイナス在庫）
-        Ok(Self(value))
+    /// 在庫残高は負の値も許容（マイナス在庫）
+    pub fn from_decimal(value: Decimal) -> Self {
+        Self(round_to_scale(value, Quantity::SCALE))
+    }
+
+    pub fn zero() -> Self {
+        Self(Decimal::ZERO)
     }
 
     pub fn value(&self) -> f64 {
+        decimal_to_f64(self.0)
+    }
+
+    pub fn decimal(&self) -> Decimal {
         self.0
     }
 }
@@ -21,19 +31,19 @@ mod tests {
 
     #[test]
     fn test_inventory_balance_positive() {
-        let balance = InventoryBalance::new(100.0).unwrap();
+        let balance = InventoryBalance::from_decimal(Decimal::from(100));
         assert_eq!(balance.value(), 100.0);
     }
 
     #[test]
     fn test_inventory_balance_zero() {
-        let balance = InventoryBalance::new(0.0).unwrap();
+        let balance = InventoryBalance::zero();
         assert_eq!(balance.value(), 0.0);
     }
 
     #[test]
     fn test_inventory_balance_negative() {
-        let balance = InventoryBalance::new(-50.0).unwrap();
+        let balance = InventoryBalance::from_decimal(Decimal::from(-50));
         assert_eq!(balance.value(), -50.0);
     }
 }
@@ -1,17 +1,38 @@
+use crate::domain::error::{DomainError, Result};
+
 /// 在庫区分
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InventoryType {
+    CarryForward,
     Production,
     Purchase,
     Sales,
+    MaterialIssue,
 }
 
 impl InventoryType {
+    pub fn new(value: String) -> Result<Self> {
+        match value.trim() {
+            "carry_forward" | "繰越" => Ok(InventoryType::CarryForward),
+            "production" | "生産" => Ok(InventoryType::Production),
+            "purchase" | "仕入" => Ok(InventoryType::Purchase),
+            "sales" | "売上" => Ok(InventoryType::Sales),
+            "material_issue" | "材料出庫" => Ok(InventoryType::MaterialIssue),
+            other => Err(DomainError::invalid_format(
+                "在庫区分",
+                other,
+                "繰越, 生産, 仕入, 売上, 材料出庫",
+            )),
+        }
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
+            InventoryType::CarryForward => "繰越",
             InventoryType::Production => "生産",
             InventoryType::Purchase => "仕入",
             InventoryType::Sales => "売上",
+            InventoryType::MaterialIssue => "材料出庫",
         }
     }
 }
@@ -38,6 +59,39 @@ mod tests {
         assert_eq!(inv_type.as_str(), "売上");
     }
 
+    #[test]
+    fn test_inventory_type_material_issue() {
+        let inv_type = InventoryType::MaterialIssue;
+        assert_eq!(inv_type.as_str(), "材料出庫");
+    }
+
+    #[test]
+    fn test_inventory_type_carry_forward() {
+        let inv_type = InventoryType::CarryForward;
+        assert_eq!(inv_type.as_str(), "繰越");
+    }
+
+    #[test]
+    fn test_inventory_type_from_name() {
+        for inv_type in [
+            InventoryType::CarryForward,
+            InventoryType::Production,
+            InventoryType::Purchase,
+            InventoryType::Sales,
+            InventoryType::MaterialIssue,
+        ] {
+            assert_eq!(
+                InventoryType::new(inv_type.as_str().to_string()).unwrap(),
+                inv_type
+            );
+        }
+        assert_eq!(
+            InventoryType::new("sales".to_string()).unwrap(),
+            InventoryType::Sales
+        );
+        assert!(InventoryType::new("返品".to_string()).is_err());
+    }
+
     #[test]
     fn test_inventory_type_equality() {
         let inv1 = InventoryType::Production;
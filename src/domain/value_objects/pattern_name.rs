@@ -1,4 +1,4 @@
-use color_eyre::{Result, eyre::eyre};
+use crate::domain::error::{DomainError, Result};
 
 /// パターン名（重複を許さない）
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -8,7 +8,7 @@ impl PatternName {
     pub fn new(name: String) -> Result<Self> {
         let trimmed = name.trim();
         if trimmed.is_empty() {
-            return Err(eyre!("パターン名が空です"));
+            return Err(DomainError::empty("パターン名"));
         }
         Ok(Self(trimmed.to_string()))
     }
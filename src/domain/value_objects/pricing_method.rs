@@ -0,0 +1,66 @@
+use crate::domain::error::{DomainError, Result};
+
+/// 材料単価の計算方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PricingMethod {
+    LatestPrice,     // 最終仕入原価法
+    MovingAverage,   // 移動平均法
+    PeriodicAverage, // 総平均法
+}
+
+impl PricingMethod {
+    pub fn new(value: String) -> Result<Self> {
+        match value.trim() {
+            "latest" | "最終仕入原価法" => Ok(PricingMethod::LatestPrice),
+            "moving_average" | "移動平均法" => Ok(PricingMethod::MovingAverage),
+            "periodic_average" | "総平均法" => Ok(PricingMethod::PeriodicAverage),
+            other => Err(DomainError::invalid_format(
+                "単価計算方法",
+                other,
+                "latest, moving_average, periodic_average",
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            PricingMethod::LatestPrice => "最終仕入原価法",
+            PricingMethod::MovingAverage => "移動平均法",
+            PricingMethod::PeriodicAverage => "総平均法",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pricing_method_from_config_key() {
+        assert_eq!(
+            PricingMethod::new("latest".to_string()).unwrap(),
+            PricingMethod::LatestPrice
+        );
+        assert_eq!(
+            PricingMethod::new("moving_average".to_string()).unwrap(),
+            PricingMethod::MovingAverage
+        );
+        assert_eq!(
+            PricingMethod::new(" periodic_average ".to_string()).unwrap(),
+            PricingMethod::PeriodicAverage
+        );
+    }
+
+    #[test]
+    fn test_pricing_method_from_japanese_name() {
+        let method = PricingMethod::new("移動平均法".to_string()).unwrap();
+        assert_eq!(method, PricingMethod::MovingAverage);
+        assert_eq!(method.as_str(), "移動平均法");
+    }
+
+    #[test]
+    fn test_pricing_method_invalid() {
+        let result = PricingMethod::new("fifo".to_string());
+        assert!(result.is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use color_eyre::{Result, eyre::eyre};
+use crate::domain::error::{DomainError, Result};
 
 /// 商品コード
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -7,7 +7,7 @@ pub struct ProductCode(String);
 impl ProductCode {
     pub fn new(code: String) -> Result<Self> {
         if code.trim().is_empty() {
-            return Err(eyre!("商品コードが空です"));
+            return Err(DomainError::empty("商品コード"));
         }
         Ok(Self(code.trim().to_string()))
     }
@@ -1,19 +1,94 @@
-use color_eyre::{Result, eyre::eyre};
+use super::decimal::{decimal_from_f64, decimal_to_f64, round_to_scale};
+use super::{ConsumptionRatio, Unit};
+use crate::domain::error::{DomainError, Result};
+use rust_decimal::Decimal;
 
 /// 数量
-#[derive(Debug, Clone, Copy)]
-pub struct Quantity(f64);
+///
+/// 小数第6位までの固定小数点と単位で保持する。単位の異なる数量同士は計算できない。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    value: Decimal,
+    unit: Unit,
+}
 
 impl Quantity {
+    /// 保持する小数点以下の桁数
+    pub const SCALE: u32 = 6;
+
+    /// 基準単位（kg）の数量
     pub fn new(value: f64) -> Result<Self> {
-        if value < 0.0 {
-            return Err(eyre!("数量が負の値です: {}", value));
+        Self::in_unit(value, Unit::Kilogram)
+    }
+
+    /// 指定した単位の数量
+    pub fn in_unit(value: f64, unit: Unit) -> Result<Self> {
+        let value = decimal_from_f64("数量", value, Self::SCALE)?;
+        Self::from_decimal_in_unit(value, unit)
+    }
+
+    /// 指定した単位の数量（固定小数点から）
+    pub fn from_decimal_in_unit(value: Decimal, unit: Unit) -> Result<Self> {
+        Ok(Self::from_decimal(value)?.with_unit(unit))
+    }
+
+    /// 基準単位（kg）の数量
+    pub fn from_decimal(value: Decimal) -> Result<Self> {
+        if value.is_sign_negative() && !value.is_zero() {
+            return Err(DomainError::negative("数量", decimal_to_f64(value)));
+        }
+        Ok(Self {
+            value: round_to_scale(value, Self::SCALE),
+            unit: Unit::Kilogram,
+        })
+    }
+
+    fn with_unit(mut self, unit: Unit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    pub fn zero() -> Self {
+        Self {
+            value: Decimal::ZERO,
+            unit: Unit::Kilogram,
         }
-        Ok(Self(value))
     }
 
     pub fn value(&self) -> f64 {
-        self.0
+        decimal_to_f64(self.value)
+    }
+
+    pub fn decimal(&self) -> Decimal {
+        self.value
+    }
+
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    /// 単位が一致しない場合はエラー
+    pub fn ensure_unit(&self, unit: Unit) -> Result<()> {
+        if self.unit != unit {
+            return Err(DomainError::IncompatibleUnits {
+                expected: unit.as_str().to_string(),
+                actual: self.unit.as_str().to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// 数量 × 消費比率（小数第6位に四捨五入）
+    pub fn multiply(&self, ratio: &ConsumptionRatio) -> Quantity {
+        Self {
+            value: round_to_scale(self.value * ratio.decimal(), Self::SCALE),
+            unit: self.unit,
+        }
+    }
+
+    /// 1単位あたりの kg を掛けて基準単位（kg）に換算
+    pub fn to_kilograms(self, kg_per_unit: Decimal) -> Result<Quantity> {
+        Self::from_decimal(self.value * kg_per_unit)
     }
 }
 
@@ -44,4 +119,36 @@ mod tests {
         let qty = Quantity::new(123.456).unwrap();
         assert_eq!(qty.value(), 123.456);
     }
+
+    #[test]
+    fn test_quantity_multiply_ratio() {
+        // 1250 × 0.03 = 37.5（f64 では 37.49999...）
+        let qty = Quantity::new(1250.0).unwrap();
+        let ratio = ConsumptionRatio::new(0.03).unwrap();
+        assert_eq!(qty.multiply(&ratio).decimal(), Decimal::new(375, 1));
+    }
+
+    #[test]
+    fn test_quantity_default_unit_is_kilogram() {
+        assert_eq!(Quantity::new(1.0).unwrap().unit(), Unit::Kilogram);
+        assert_eq!(Quantity::zero().unit(), Unit::Kilogram);
+    }
+
+    #[test]
+    fn test_quantity_ensure_unit() {
+        let tonnes = Quantity::in_unit(1.5, Unit::Tonne).unwrap();
+        assert!(tonnes.ensure_unit(Unit::Tonne).is_ok());
+        assert!(matches!(
+            tonnes.ensure_unit(Unit::Kilogram),
+            Err(DomainError::IncompatibleUnits { .. })
+        ));
+    }
+
+    #[test]
+    fn test_quantity_to_kilograms() {
+        let tonnes = Quantity::in_unit(1.25, Unit::Tonne).unwrap();
+        let kg = tonnes.to_kilograms(Decimal::from(1000)).unwrap();
+        assert_eq!(kg.value(), 1250.0);
+        assert_eq!(kg.unit(), Unit::Kilogram);
+    }
 }
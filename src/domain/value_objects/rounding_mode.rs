@@ -0,0 +1,75 @@
+use crate::domain::error::{DomainError, Result};
+use rust_decimal::RoundingStrategy;
+
+/// 端数処理の方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    HalfUp,   // 四捨五入
+    HalfEven, // 偶数丸め（銀行丸め）
+    Truncate, // 切り捨て
+    Ceiling,  // 切り上げ
+}
+
+impl RoundingMode {
+    pub fn new(value: String) -> Result<Self> {
+        match value.trim() {
+            "half_up" | "四捨五入" => Ok(RoundingMode::HalfUp),
+            "half_even" | "偶数丸め" => Ok(RoundingMode::HalfEven),
+            "truncate" | "切り捨て" => Ok(RoundingMode::Truncate),
+            "ceiling" | "切り上げ" => Ok(RoundingMode::Ceiling),
+            other => Err(DomainError::invalid_format(
+                "端数処理",
+                other,
+                "half_up, half_even, truncate, ceiling",
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            RoundingMode::HalfUp => "四捨五入",
+            RoundingMode::HalfEven => "偶数丸め",
+            RoundingMode::Truncate => "切り捨て",
+            RoundingMode::Ceiling => "切り上げ",
+        }
+    }
+
+    /// 固定小数点演算の丸め方
+    pub fn strategy(&self) -> RoundingStrategy {
+        match self {
+            RoundingMode::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            RoundingMode::HalfEven => RoundingStrategy::MidpointNearestEven,
+            RoundingMode::Truncate => RoundingStrategy::ToZero,
+            RoundingMode::Ceiling => RoundingStrategy::AwayFromZero,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rounding_mode_from_config_key() {
+        assert_eq!(
+            RoundingMode::new("half_even".to_string()).unwrap(),
+            RoundingMode::HalfEven
+        );
+        assert_eq!(
+            RoundingMode::new(" truncate ".to_string()).unwrap(),
+            RoundingMode::Truncate
+        );
+    }
+
+    #[test]
+    fn test_rounding_mode_from_japanese_name() {
+        let mode = RoundingMode::new("切り上げ".to_string()).unwrap();
+        assert_eq!(mode, RoundingMode::Ceiling);
+        assert_eq!(mode.as_str(), "切り上げ");
+    }
+
+    #[test]
+    fn test_rounding_mode_invalid() {
+        assert!(RoundingMode::new("floor".to_string()).is_err());
+    }
+}
@@ -1,30 +1,39 @@
-use color_eyre::{Result, eyre::eyre};
+use crate::domain::error::{DomainError, Result};
+use chrono::{Datelike, NaiveDate};
+use std::cmp::Ordering;
 
-/// 日付（文字列として保持）
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct TransactionDate(String);
+/// 日付（入力された文字列を保持し、比較は暦日で行う）
+#[derive(Debug, Clone)]
+pub struct TransactionDate {
+    raw: String,
+    date: NaiveDate,
+}
 
 impl TransactionDate {
     pub fn new(date: String) -> Result<Self> {
         let trimmed = date.trim();
 
         if trimmed.is_empty() {
-            return Err(eyre!("日付が空です"));
+            return Err(DomainError::empty("日付"));
         }
 
         // 日付形式のバリデーション
         // YYYY-MM-DD, YYYY/MM/DD, YYYY.MM.DD などの形式をサポート
-        if !Self::is_valid_date_format(trimmed) {
-            return Err(eyre!(
-                "日付の形式が不正です: '{}'\n  有効な形式: YYYY-MM-DD, YYYY/MM/DD, YYYY.MM.DD (例: 2024-01-15)",
-                trimmed
+        let Some(date) = Self::parse_date(trimmed) else {
+            return Err(DomainError::invalid_format(
+                "日付",
+                trimmed,
+                "YYYY-MM-DD, YYYY/MM/DD, YYYY.MM.DD (例: 2024-01-15)",
             ));
-        }
+        };
 
-        Ok(Self(trimmed.to_string()))
+        Ok(Self {
+            raw: trimmed.to_string(),
+            date,
+        })
     }
 
-    fn is_valid_date_format(date_str: &str) -> bool {
+    fn parse_date(date_str: &str) -> Option<NaiveDate> {
         // 区切り文字を検出
         let separator = if date_str.contains('-') {
             '-'
@@ -33,41 +42,41 @@ impl TransactionDate {
         } else if date_str.contains('.') {
             '.'
         } else {
-            return false;
+            return None;
         };
 
         let parts: Vec<&str> = date_str.split(separator).collect();
         if parts.len() != 3 {
-            return false;
+            return None;
         }
 
         // 年月日を解析
         let year: i32 = match parts[0].parse() {
             Ok(y) => y,
-            Err(_) => return false,
+            Err(_) => return None,
         };
         let month: u32 = match parts[1].parse() {
             Ok(m) => m,
-            Err(_) => return false,
+            Err(_) => return None,
         };
         let day: u32 = match parts[2].parse() {
             Ok(d) => d,
-            Err(_) => return false,
+            Err(_) => return None,
         };
 
         // 年の範囲チェック（1900-2100）
         if !(1900..=2100).contains(&year) {
-            return false;
+            return None;
         }
 
         // 月の範囲チェック
         if !(1..=12).contains(&month) {
-            return false;
+            return None;
         }
 
         // 日の範囲チェック
         if day < 1 {
-            return false;
+            return None;
         }
 
         // 月ごとの最大日数チェック
@@ -82,14 +91,86 @@ impl TransactionDate {
                     28
                 }
             }
-            _ => return false,
+            _ => return None,
         };
 
-        day <= max_day
+        if day > max_day {
+            return None;
+        }
+
+        NaiveDate::from_ymd_opt(year, month, day)
     }
 
     pub fn value(&self) -> &str {
-        &self.0
+        &self.raw
+    }
+
+    /// 今日の日付（YYYY-MM-DD形式）
+    pub fn today() -> TransactionDate {
+        let date = chrono::Local::now().date_naive();
+        Self {
+            raw: date.format("%Y-%m-%d").to_string(),
+            date,
+        }
+    }
+
+    /// 同じ月の1日（YYYY-MM-01形式）
+    pub fn first_day_of_month(&self) -> TransactionDate {
+        let date = self.date.with_day(1).unwrap_or(self.date);
+        Self {
+            raw: date.format("%Y-%m-%d").to_string(),
+            date,
+        }
+    }
+
+    /// 同じ月の末日（YYYY-MM-DD形式）
+    pub fn last_day_of_month(&self) -> TransactionDate {
+        let next = self.next_month();
+        let date = next.date.pred_opt().unwrap_or(self.date);
+        Self {
+            raw: date.format("%Y-%m-%d").to_string(),
+            date,
+        }
+    }
+
+    /// 翌月の1日（YYYY-MM-01形式）
+    pub fn next_month(&self) -> TransactionDate {
+        let (year, month) = if self.date.month() == 12 {
+            (self.date.year() + 1, 1)
+        } else {
+            (self.date.year(), self.date.month() + 1)
+        };
+        let date = NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(self.date);
+        Self {
+            raw: date.format("%Y-%m-%d").to_string(),
+            date,
+        }
+    }
+
+    /// 年月（YYYY-MM形式）
+    pub fn month_label(&self) -> String {
+        self.date.format("%Y-%m").to_string()
+    }
+}
+
+// 区切り文字の違い（2024-01-15 と 2024/01/15 など）は同じ日付として扱う
+impl PartialEq for TransactionDate {
+    fn eq(&self, other: &Self) -> bool {
+        self.date == other.date
+    }
+}
+
+impl Eq for TransactionDate {}
+
+impl PartialOrd for TransactionDate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TransactionDate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.date.cmp(&other.date)
     }
 }
 
@@ -157,4 +238,30 @@ mod tests {
         let date2 = TransactionDate::new("2024-02-20".to_string()).unwrap();
         assert!(date1 < date2);
     }
+
+    #[test]
+    fn test_transaction_date_first_day_of_month() {
+        let date = TransactionDate::new("2024/02/15".to_string()).unwrap();
+        assert_eq!(date.first_day_of_month().value(), "2024-02-01");
+    }
+
+    #[test]
+    fn test_transaction_date_month_boundaries() {
+        let date = TransactionDate::new("2024/02/15".to_string()).unwrap();
+        assert_eq!(date.last_day_of_month().value(), "2024-02-29");
+        assert_eq!(date.month_label(), "2024-02");
+
+        let december = TransactionDate::new("2024-12-31".to_string()).unwrap();
+        assert_eq!(december.next_month().value(), "2025-01-01");
+        assert_eq!(december.last_day_of_month().value(), "2024-12-31");
+    }
+
+    #[test]
+    fn test_transaction_date_ordering_mixed_separators() {
+        let date1 = TransactionDate::new("2024/1/5".to_string()).unwrap();
+        let date2 = TransactionDate::new("2024-01-15".to_string()).unwrap();
+        let date3 = TransactionDate::new("2024.01.15".to_string()).unwrap();
+        assert!(date1 < date2);
+        assert_eq!(date2, date3);
+    }
 }
@@ -0,0 +1,68 @@
+use crate::domain::error::{DomainError, Result};
+use rust_decimal::Decimal;
+
+/// 数量の単位
+///
+/// 在庫・配合・運賃は基準単位（kg）で計算する。kg 以外の単位は単位換算で kg に正規化する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Kilogram, // kg（基準単位）
+    Tonne,    // t
+    Bag,      // 袋（重量は材料ごとに単位換算マスタで定義）
+}
+
+impl Unit {
+    pub fn new(value: String) -> Result<Self> {
+        match value.trim() {
+            "" | "kg" | "KG" | "Kg" | "キロ" => Ok(Unit::Kilogram),
+            "t" | "T" | "トン" => Ok(Unit::Tonne),
+            "袋" => Ok(Unit::Bag),
+            other => Err(DomainError::invalid_format("単位", other, "kg, t, 袋")),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Unit::Kilogram => "kg",
+            Unit::Tonne => "t",
+            Unit::Bag => "袋",
+        }
+    }
+
+    /// 材料によらない 1単位あたりの kg（袋は材料ごとに異なるためNone）
+    pub fn standard_kg(&self) -> Option<Decimal> {
+        match self {
+            Unit::Kilogram => Some(Decimal::ONE),
+            Unit::Tonne => Some(Decimal::from(1000)),
+            Unit::Bag => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_from_label() {
+        assert_eq!(Unit::new("kg".to_string()).unwrap(), Unit::Kilogram);
+        assert_eq!(Unit::new(" t ".to_string()).unwrap(), Unit::Tonne);
+        assert_eq!(Unit::new("袋".to_string()).unwrap(), Unit::Bag);
+    }
+
+    #[test]
+    fn test_unit_empty_is_kilogram() {
+        assert_eq!(Unit::new(String::new()).unwrap(), Unit::Kilogram);
+    }
+
+    #[test]
+    fn test_unit_invalid() {
+        assert!(Unit::new("箱".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_unit_standard_kg() {
+        assert_eq!(Unit::Tonne.standard_kg(), Some(Decimal::from(1000)));
+        assert_eq!(Unit::Bag.standard_kg(), None);
+    }
+}
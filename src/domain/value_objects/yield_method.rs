@@ -0,0 +1,66 @@
+use crate::domain::error::{DomainError, Result};
+
+/// 歩留率の解釈（原砂歩留金額の計算式）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YieldMethod {
+    Multiply, // 原砂金額 × 歩留率
+    Divide,   // 原砂金額 ÷ 歩留率（歩留率 = 産出量 ÷ 投入量）
+}
+
+impl YieldMethod {
+    pub fn new(value: String) -> Result<Self> {
+        match value.trim() {
+            "multiply" | "乗算" => Ok(YieldMethod::Multiply),
+            "divide" | "除算" => Ok(YieldMethod::Divide),
+            other => Err(DomainError::invalid_format(
+                "歩留率の計算方法",
+                other,
+                "multiply, divide",
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            YieldMethod::Multiply => "乗算",
+            YieldMethod::Divide => "除算",
+        }
+    }
+
+    /// ログ・出力に記録する計算式
+    pub fn formula(&self) -> &str {
+        match self {
+            YieldMethod::Multiply => "原砂金額 × 歩留率",
+            YieldMethod::Divide => "原砂金額 ÷ 歩留率",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yield_method_from_config_key() {
+        assert_eq!(
+            YieldMethod::new("multiply".to_string()).unwrap(),
+            YieldMethod::Multiply
+        );
+        assert_eq!(
+            YieldMethod::new(" divide ".to_string()).unwrap(),
+            YieldMethod::Divide
+        );
+    }
+
+    #[test]
+    fn test_yield_method_from_japanese_name() {
+        let method = YieldMethod::new("除算".to_string()).unwrap();
+        assert_eq!(method, YieldMethod::Divide);
+        assert_eq!(method.formula(), "原砂金額 ÷ 歩留率");
+    }
+
+    #[test]
+    fn test_yield_method_invalid() {
+        assert!(YieldMethod::new("subtract".to_string()).is_err());
+    }
+}
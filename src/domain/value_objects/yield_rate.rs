@@ -1,21 +1,37 @@
-use color_eyre::{Result, eyre::eyre};
+use super::decimal::{decimal_from_f64, decimal_to_f64, round_to_scale};
+use crate::domain::error::{DomainError, Result};
+use rust_decimal::Decimal;
 
-/// 歩留率
-#[derive(Debug, Clone, Copy)]
-pub struct YieldRate(f64);
+/// 歩留率（小数第6位までの固定小数点）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct YieldRate(Decimal);
 
 impl YieldRate {
+    /// 保持する小数点以下の桁数
+    pub const SCALE: u32 = 6;
+
     pub fn new(value: f64) -> Result<Self> {
-        if !(0.0..=1.0).contains(&value) {
-            return Err(eyre!(
-                "歩留率は0.0から1.0の範囲である必要があります: {}",
-                value
-            ));
+        Self::from_decimal(decimal_from_f64("歩留率", value, Self::SCALE)?)
+    }
+
+    pub fn from_decimal(value: Decimal) -> Result<Self> {
+        let value = round_to_scale(value, Self::SCALE);
+        if value < Decimal::ZERO || value > Decimal::ONE {
+            return Err(DomainError::OutOfRange {
+                field: "歩留率".to_string(),
+                value: decimal_to_f64(value),
+                min: 0.0,
+                max: 1.0,
+            });
         }
         Ok(Self(value))
     }
 
     pub fn value(&self) -> f64 {
+        decimal_to_f64(self.0)
+    }
+
+    pub fn decimal(&self) -> Decimal {
         self.0
     }
 }
@@ -27,19 +43,19 @@ mod tests {
     #[test]
     fn test_yield_rate_valid() {
         let rate = YieldRate::new(0.95).unwrap();
-        assert_eq!(rate.value(), 0.95);
+        assert_eq!(rate.decimal(), Decimal::new(95, 2));
     }
 
     #[test]
     fn test_yield_rate_zero() {
         let rate = YieldRate::new(0.0).unwrap();
-        assert_eq!(rate.value(), 0.0);
+        assert_eq!(rate.decimal(), Decimal::ZERO);
     }
 
     #[test]
     fn test_yield_rate_one() {
         let rate = YieldRate::new(1.0).unwrap();
-        assert_eq!(rate.value(), 1.0);
+        assert_eq!(rate.decimal(), Decimal::ONE);
     }
 
     #[test]
@@ -1 +1,3 @@
+pub mod csv_repositories;
 pub mod excel_repositories;
+pub mod sqlite_repositories;
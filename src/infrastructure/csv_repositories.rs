@@ -0,0 +1,564 @@
+use super::excel_repositories::*;
+use crate::domain::diagnostics::{Diagnostic, Diagnostics};
+use crate::domain::entities::*;
+use crate::domain::error::{DomainError, Result};
+use crate::domain::repositories::*;
+use crate::domain::sheet_schema::*;
+use crate::domain::value_objects::*;
+use calamine::Data;
+use std::path::{Path, PathBuf};
+
+/// CSVファイルの文字コード
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvEncoding {
+    Auto,     // UTF-8 として読めない場合は Shift_JIS
+    Utf8,     // UTF-8（BOM付きも可）
+    ShiftJis, // Shift_JIS（Windows-31J）
+}
+
+impl CsvEncoding {
+    pub fn new(value: String) -> Result<Self> {
+        match value.trim() {
+            "auto" | "自動" => Ok(CsvEncoding::Auto),
+            "utf-8" | "utf8" | "UTF-8" => Ok(CsvEncoding::Utf8),
+            "shift_jis" | "sjis" | "cp932" | "Shift_JIS" => Ok(CsvEncoding::ShiftJis),
+            other => Err(DomainError::invalid_format(
+                "文字コード",
+                other,
+                "auto, utf-8, shift_jis",
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            CsvEncoding::Auto => "自動判定",
+            CsvEncoding::Utf8 => "UTF-8",
+            CsvEncoding::ShiftJis => "Shift_JIS",
+        }
+    }
+
+    /// ファイルの内容を文字列に変換（変換できない場合は理由を返す）
+    fn decode(&self, bytes: &[u8]) -> std::result::Result<String, String> {
+        let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        match self {
+            CsvEncoding::Auto => match std::str::from_utf8(bytes) {
+                Ok(text) => Ok(text.to_string()),
+                Err(_) => CsvEncoding::ShiftJis.decode(bytes),
+            },
+            CsvEncoding::Utf8 => std::str::from_utf8(bytes)
+                .map(str::to_string)
+                .map_err(|e| format!("UTF-8 として読み取れません: {}", e)),
+            CsvEncoding::ShiftJis => {
+                let (text, _, had_errors) = encoding_rs::SHIFT_JIS.decode(bytes);
+                if had_errors {
+                    Err("Shift_JIS として読み取れない文字があります".to_string())
+                } else {
+                    Ok(text.into_owned())
+                }
+            }
+        }
+    }
+}
+
+/// CSVの各行をセルに変換（見出し行を含む、空欄は空セル）
+fn parse_records(text: &str) -> std::result::Result<Vec<Vec<Data>>, csv::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        rows.push(
+            record?
+                .iter()
+                .map(|field| {
+                    if field.trim().is_empty() {
+                        Data::Empty
+                    } else {
+                        Data::String(field.to_string())
+                    }
+                })
+                .collect(),
+        );
+    }
+    Ok(rows)
+}
+
+fn row_slices(rows: &[Vec<Data>]) -> Vec<&[Data]> {
+    rows.iter().map(Vec::as_slice).collect()
+}
+
+/// CSVフォルダ（シートごとに `<シート名>.csv`）
+///
+/// シート名は対応表で読み替えたテンプレートのシート名を使う。
+pub struct CsvSource {
+    dir: PathBuf,
+    encoding: CsvEncoding,
+}
+
+impl CsvSource {
+    pub fn new(dir: &str, encoding: CsvEncoding) -> Self {
+        Self {
+            dir: PathBuf::from(dir),
+            encoding,
+        }
+    }
+
+    fn path(&self, sheet_name: &str) -> PathBuf {
+        self.dir.join(format!("{}.csv", sheet_name))
+    }
+
+    /// シートのCSVを読み取る
+    fn read(&self, sheet_name: &str) -> Result<Vec<Vec<Data>>> {
+        let unreadable = |reason: String| DomainError::SheetUnreadable {
+            sheet: sheet_name.to_string(),
+            reason,
+        };
+        let path = self.path(sheet_name);
+        let bytes =
+            std::fs::read(&path).map_err(|e| unreadable(format!("{}: {}", path.display(), e)))?;
+        let text = self
+            .encoding
+            .decode(&bytes)
+            .map_err(|reason| unreadable(format!("{}: {}", path.display(), reason)))?;
+        parse_records(&text).map_err(|e| unreadable(format!("{}: {}", path.display(), e)))
+    }
+
+    /// 任意のシートのCSVを読み取る（ファイルがない場合は空）
+    fn read_optional(&self, sheet_name: &str) -> Result<Vec<Vec<Data>>> {
+        if self.path(sheet_name).exists() {
+            self.read(sheet_name)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// CSVベースの配合マスタリポジトリ
+pub struct CsvFormulaRepository {
+    inner: ExcelFormulaRepository,
+}
+
+impl CsvFormulaRepository {
+    pub fn new(
+        source: &CsvSource,
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let rows = source.read(&mapping.sheet_name(FormulaSheetSchema::SHEET))?;
+        Ok(Self {
+            inner: ExcelFormulaRepository::from_rows(&row_slices(&rows), mapping, diagnostics)?,
+        })
+    }
+}
+
+impl FormulaRepository for CsvFormulaRepository {
+    fn find_by_product_code_as_of(
+        &self,
+        product_code: &ProductCode,
+        date: &TransactionDate,
+    ) -> Result<Vec<FormulaEntry>> {
+        self.inner.find_by_product_code_as_of(product_code, date)
+    }
+
+    fn has_formula_as_of(&self, product_code: &ProductCode, date: &TransactionDate) -> bool {
+        self.inner.has_formula_as_of(product_code, date)
+    }
+
+    fn find_all_versions(&self) -> Result<Vec<FormulaVersion>> {
+        self.inner.find_all_versions()
+    }
+}
+
+/// CSVベースの運賃マスタリポジトリ
+pub struct CsvFreightMasterRepository {
+    inner: ExcelFreightMasterRepository,
+}
+
+impl CsvFreightMasterRepository {
+    /// 運賃表・運賃地区割増のCSVがある場合は、パターン名の一致する運賃表を紐付ける
+    pub fn new(
+        source: &CsvSource,
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let rates = source.read_optional(&mapping.sheet_name(FreightRateSheetSchema::SHEET))?;
+        let surcharges =
+            source.read_optional(&mapping.sheet_name(ZoneSurchargeSheetSchema::SHEET))?;
+        let rate_tables = ExcelFreightRateTableRepository::from_rows(
+            &row_slices(&rates),
+            &row_slices(&surcharges),
+            mapping,
+            diagnostics,
+        )?
+        .find_all()?;
+
+        let rows = source.read(&mapping.sheet_name(FreightMasterSheetSchema::SHEET))?;
+        Ok(Self {
+            inner: ExcelFreightMasterRepository::from_rows(
+                &row_slices(&rows),
+                &rate_tables,
+                mapping,
+                diagnostics,
+            )?,
+        })
+    }
+}
+
+impl FreightMasterRepository for CsvFreightMasterRepository {
+    fn find_by_code_as_of(
+        &self,
+        freight_code: &str,
+        date: &TransactionDate,
+    ) -> Result<FreightMaster> {
+        self.inner.find_by_code_as_of(freight_code, date)
+    }
+}
+
+/// CSVベースの仕入リポジトリ
+pub struct CsvPurchaseRepository {
+    inner: ExcelPurchaseRepository,
+}
+
+impl CsvPurchaseRepository {
+    /// 仕入の数量・単価は単位換算マスタで基準単位（kg）に正規化する
+    pub fn new(
+        source: &CsvSource,
+        conversions: &[UnitConversion],
+        allocation: FreightAllocationMethod,
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let rows = source.read(&mapping.sheet_name(PurchaseSheetSchema::SHEET))?;
+        Ok(Self {
+            inner: ExcelPurchaseRepository::from_rows(
+                &row_slices(&rows),
+                conversions,
+                allocation,
+                mapping,
+                diagnostics,
+            )?,
+        })
+    }
+}
+
+impl PurchaseRepository for CsvPurchaseRepository {
+    fn find_price_as_of(
+        &self,
+        product_code: &ProductCode,
+        date: &TransactionDate,
+    ) -> Result<Purchase> {
+        self.inner.find_price_as_of(product_code, date)
+    }
+
+    fn find_history(&self, product_code: &ProductCode) -> Result<Vec<Purchase>> {
+        self.inner.find_history(product_code)
+    }
+
+    fn find_all(&self) -> Result<Vec<Purchase>> {
+        PurchaseRepository::find_all(&self.inner)
+    }
+}
+
+/// CSV生産データリポジトリ
+pub struct CsvProductionRepository {
+    inner: ExcelProductionRepository,
+}
+
+impl CsvProductionRepository {
+    /// 行番号はCSVの行番号（見出し行が1行目）
+    pub fn new(
+        source: &CsvSource,
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let rows = source.read(&mapping.sheet_name(ProductionSheetSchema::SHEET))?;
+        Ok(Self {
+            inner: ExcelProductionRepository::from_rows(&row_slices(&rows), mapping, diagnostics)?,
+        })
+    }
+}
+
+impl ProductionRepository for CsvProductionRepository {
+    fn find_all(&self) -> Result<Vec<Production>> {
+        self.inner.find_all()
+    }
+
+    fn source_row(&self, index: usize) -> Option<usize> {
+        self.inner.source_row(index)
+    }
+}
+
+/// CSV入出庫トランザクションリポジトリ
+pub struct CsvInventoryTransactionRepository {
+    inner: ExcelInventoryTransactionRepository,
+}
+
+impl CsvInventoryTransactionRepository {
+    /// 生産・仕入・売上のCSVのうち、あるものを読み取る
+    pub fn new(
+        source: &CsvSource,
+        conversions: &[UnitConversion],
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let production = source.read_optional(&mapping.sheet_name(ProductionSheetSchema::SHEET))?;
+        let purchase = source.read_optional(&mapping.sheet_name(PurchaseSheetSchema::SHEET))?;
+        let sales = source.read_optional(&mapping.sheet_name(SalesSheetSchema::SHEET))?;
+        Ok(Self {
+            inner: ExcelInventoryTransactionRepository::from_rows(
+                &row_slices(&production),
+                &row_slices(&purchase),
+                &row_slices(&sales),
+                conversions,
+                mapping,
+                diagnostics,
+            )?,
+        })
+    }
+}
+
+impl InventoryTransactionRepository for CsvInventoryTransactionRepository {
+    fn find_all_transactions(&self) -> Result<Vec<InventoryTransaction>> {
+        self.inner.find_all_transactions()
+    }
+}
+
+/// CSV期首在庫リポジトリ
+pub struct CsvOpeningBalanceRepository {
+    inner: ExcelOpeningBalanceRepository,
+}
+
+impl CsvOpeningBalanceRepository {
+    /// 【期首】在庫のCSVがない場合は期首在庫なし（すべて0から開始）として扱う
+    pub fn new(
+        source: &CsvSource,
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let rows = source.read_optional(&mapping.sheet_name(OpeningBalanceSheetSchema::SHEET))?;
+        Ok(Self {
+            inner: ExcelOpeningBalanceRepository::from_rows(
+                &row_slices(&rows),
+                mapping,
+                diagnostics,
+            )?,
+        })
+    }
+}
+
+impl OpeningBalanceRepository for CsvOpeningBalanceRepository {
+    fn find_all(&self) -> Result<Vec<OpeningBalance>> {
+        self.inner.find_all()
+    }
+}
+
+/// CSVリポジトリファクトリ
+pub struct CsvRepositoryFactory {
+    pub formula_repo: CsvFormulaRepository,
+    pub freight_repo: CsvFreightMasterRepository,
+    pub purchase_repo: CsvPurchaseRepository,
+    pub production_repo: CsvProductionRepository,
+    pub transaction_repo: CsvInventoryTransactionRepository,
+    pub opening_balance_repo: CsvOpeningBalanceRepository,
+    pub diagnostics: Vec<Diagnostic>, // 蓄積モードで読み飛ばした行の問題
+}
+
+impl CsvRepositoryFactory {
+    /// CSVフォルダからすべてのリポジトリを初期化
+    ///
+    /// シートごとのCSVの見出し行は Excel のシートと同じスキーマで検証する。
+    pub fn from_dir(
+        dir: &str,
+        encoding: CsvEncoding,
+        accumulate: bool,
+        freight_allocation: FreightAllocationMethod,
+        mapping: &SchemaMapping,
+    ) -> Result<Self> {
+        println!(
+            "CSVフォルダを読み取り中: {}（文字コード: {}）",
+            dir,
+            encoding.as_str()
+        );
+        let source = CsvSource::new(dir, encoding);
+        let entries =
+            std::fs::read_dir(source.dir()).map_err(|e| DomainError::InputUnreadable {
+                path: dir.to_string(),
+                reason: format!(
+                    "{}（config.toml の [input] csv_dir が正しいか確認してください）",
+                    e
+                ),
+            })?;
+
+        // CSVファイル名を表示
+        let mut file_names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".csv"))
+            .collect();
+        file_names.sort();
+        println!("\n既存のCSVファイル:");
+        for (i, name) in file_names.iter().enumerate() {
+            println!("  {}. {}", i + 1, name);
+        }
+
+        // リポジトリを初期化
+        println!("\nリポジトリを初期化中...");
+        let mut diagnostics = Diagnostics::new(accumulate);
+        let formula_repo = CsvFormulaRepository::new(&source, mapping, &mut diagnostics)?;
+        let freight_repo = CsvFreightMasterRepository::new(&source, mapping, &mut diagnostics)?;
+        // 仕入の数量・単価を kg に正規化するため、単位換算マスタを先に読み取る
+        let conversion_rows =
+            source.read_optional(&mapping.sheet_name(UnitConversionSheetSchema::SHEET))?;
+        let conversions = ExcelUnitConversionRepository::from_rows(
+            &row_slices(&conversion_rows),
+            mapping,
+            &mut diagnostics,
+        )?
+        .find_all()?;
+        let purchase_repo = CsvPurchaseRepository::new(
+            &source,
+            &conversions,
+            freight_allocation,
+            mapping,
+            &mut diagnostics,
+        )?;
+        let production_repo = CsvProductionRepository::new(&source, mapping, &mut diagnostics)?;
+        let transaction_repo = CsvInventoryTransactionRepository::new(
+            &source,
+            &conversions,
+            mapping,
+            &mut diagnostics,
+        )?;
+        let opening_balance_repo =
+            CsvOpeningBalanceRepository::new(&source, mapping, &mut diagnostics)?;
+        println!("  ✓ リポジトリの初期化完了");
+        if !diagnostics.entries().is_empty() {
+            println!(
+                "  ⚠️  読み飛ばした行の問題: {} 件",
+                diagnostics.entries().len()
+            );
+        }
+
+        Ok(Self {
+            formula_repo,
+            freight_repo,
+            purchase_repo,
+            production_repo,
+            transaction_repo,
+            opening_balance_repo,
+            diagnostics: diagnostics.entries().to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_shift_jis_and_utf8() {
+        let (shift_jis, _, _) = encoding_rs::SHIFT_JIS.encode("商品コード,数量");
+        assert_eq!(
+            CsvEncoding::Auto.decode(&shift_jis).unwrap(),
+            "商品コード,数量"
+        );
+        assert_eq!(
+            CsvEncoding::ShiftJis.decode(&shift_jis).unwrap(),
+            "商品コード,数量"
+        );
+        assert!(CsvEncoding::Utf8.decode(&shift_jis).is_err());
+
+        let utf8_bom = "\u{FEFF}商品コード,数量".as_bytes();
+        assert_eq!(
+            CsvEncoding::Auto.decode(utf8_bom).unwrap(),
+            "商品コード,数量"
+        );
+    }
+
+    #[test]
+    fn test_encoding_from_label() {
+        assert_eq!(
+            CsvEncoding::new("sjis".to_string()).unwrap(),
+            CsvEncoding::ShiftJis
+        );
+        assert_eq!(
+            CsvEncoding::new("utf-8".to_string()).unwrap(),
+            CsvEncoding::Utf8
+        );
+        assert!(CsvEncoding::new("euc-jp".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_parse_records_keeps_quoted_commas_and_empty_cells() {
+        let rows = parse_records("商品コード,商品名,数量\nM001,\"砂,細目\",\n").unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1][1], Data::String("砂,細目".to_string()));
+        assert_eq!(rows[1][2], Data::Empty);
+    }
+
+    #[test]
+    fn test_rows_validated_by_sheet_schema() {
+        // 見出し行の検証は Excel のシートと共通（不足する列をまとめて報告）
+        let rows = parse_records("製造商品コード\nP001\n").unwrap();
+        let mut diagnostics = Diagnostics::new(false);
+
+        let result = ExcelFormulaRepository::from_rows(
+            &row_slices(&rows),
+            &SchemaMapping::new(),
+            &mut diagnostics,
+        );
+
+        assert!(matches!(
+            result,
+            Err(DomainError::MissingColumns { ref columns, .. }) if columns.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_production_rows_keep_source_row_after_skipped_row() {
+        // 蓄積モードで読み飛ばした行があっても、結果は元の行番号に書き戻す
+        let rows = parse_records(
+            "生産日,商品コード,生産品番,生産数量,歩留率,凝集剤,粘土処理,材料運賃\n\
+             2026-01-10,P001,,abc,0.9,,,\n\
+             2026-01-11,P001,,100,0.9,,,\n",
+        )
+        .unwrap();
+        let mut diagnostics = Diagnostics::new(true);
+
+        let repo = ExcelProductionRepository::from_rows(
+            &row_slices(&rows),
+            &SchemaMapping::new(),
+            &mut diagnostics,
+        )
+        .unwrap();
+
+        assert_eq!(repo.find_all().unwrap().len(), 1);
+        assert_eq!(diagnostics.entries().len(), 1);
+        assert_eq!(repo.source_row(0), Some(3));
+        assert_eq!(repo.source_row(1), None);
+    }
+
+    #[test]
+    fn test_missing_dir_is_typed_error() {
+        let result = CsvRepositoryFactory::from_dir(
+            "tests/存在しないフォルダ",
+            CsvEncoding::Auto,
+            false,
+            FreightAllocationMethod::Weight,
+            &SchemaMapping::new(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(DomainError::InputUnreadable { ref path, .. }) if path == "tests/存在しないフォルダ"
+        ));
+    }
+}
@@ -1,24 +1,103 @@
+use crate::domain::diagnostics::{Diagnostic, Diagnostics};
 use crate::domain::entities::*;
+use crate::domain::error::{DomainError, MasterKind, Result};
+use crate::domain::freight_allocation::FreightAllocationService;
 use crate::domain::repositories::*;
 use crate::domain::sheet_schema::*;
+use crate::domain::unit_conversion::UnitConversionService;
 use crate::domain::value_objects::*;
-use calamine::{Data, Reader, Xlsx};
+use calamine::{Data, Range, Reader, Xlsx};
 use chrono::Datelike;
-use color_eyre::{Result, eyre::eyre};
 use std::collections::HashMap;
 
 // 共通ヘルパー関数
-fn find_column_index(header_row: &[Data], column_name: &str, sheet_name: &str) -> Result<usize> {
+/// シートを読み取る
+fn read_sheet(
+    workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>,
+    sheet_name: &str,
+) -> Result<Range<Data>> {
+    workbook
+        .worksheet_range(sheet_name)
+        .map_err(|e| DomainError::SheetUnreadable {
+            sheet: sheet_name.to_string(),
+            reason: e.to_string(),
+        })
+}
+
+/// 任意のシートを読み取る（シートがない場合は空）
+fn read_optional_sheet(
+    workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>,
+    sheet_name: &str,
+) -> Range<Data> {
+    workbook
+        .worksheet_range(sheet_name)
+        .unwrap_or_else(|_| Range::empty())
+}
+
+fn empty_sheet(sheet_name: &str) -> DomainError {
+    DomainError::EmptySheet {
+        sheet: sheet_name.to_string(),
+    }
+}
+
+/// 見出し行の列名
+fn header_strings(header_row: &[Data]) -> Vec<String> {
     header_row
         .iter()
-        .position(|cell| cell.to_string().trim() == column_name)
-        .ok_or_else(|| {
-            eyre!(
-                "{}シート: 列 '{}' が見つかりません",
-                sheet_name,
-                column_name
-            )
-        })
+        .map(|cell| cell.to_string().trim().to_string())
+        .collect()
+}
+
+/// セルの問題（row_idx は0始まりの行インデックス）
+fn cell_error(
+    sheet_name: &str,
+    row_idx: usize,
+    column: &str,
+    message: impl std::fmt::Display,
+) -> Diagnostic {
+    Diagnostic::new(sheet_name, message)
+        .with_row(row_idx + 1)
+        .with_column(column)
+}
+
+/// 行の問題（row_idx は0始まりの行インデックス）
+fn row_error(sheet_name: &str, row_idx: usize, message: impl std::fmt::Display) -> Diagnostic {
+    Diagnostic::new(sheet_name, message).with_row(row_idx + 1)
+}
+
+/// 数値セルを読み取る
+fn parse_number(
+    value: &str,
+    sheet_name: &str,
+    row_idx: usize,
+    column: &str,
+) -> std::result::Result<f64, Diagnostic> {
+    value.parse().map_err(|_| {
+        cell_error(
+            sheet_name,
+            row_idx,
+            column,
+            format!("{}が数値ではありません: '{}'", column, value),
+        )
+    })
+}
+
+/// 任意列の日付を取得（列がない・空欄の場合はNone）
+fn optional_date(row: &[Data], index: Option<usize>) -> Result<Option<TransactionDate>> {
+    let Some(index) = index else {
+        return Ok(None);
+    };
+    let date_str = get_cell_date_string(row, index);
+    if date_str.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(TransactionDate::new(date_str)?))
+    }
+}
+
+/// 任意列の単位を取得（列がない・空欄の場合は kg）
+fn optional_unit(row: &[Data], index: Option<usize>) -> Result<Unit> {
+    Unit::new(index.map(|i| get_cell_string(row, i)).unwrap_or_default())
 }
 
 fn get_cell_string(row: &[Data], index: usize) -> String {
@@ -92,233 +171,577 @@ fn excel_serial_to_date(serial: f64) -> String {
 
 /// Excelベースの配合マスタリポジトリ
 pub struct ExcelFormulaRepository {
-    data: HashMap<String, Vec<FormulaEntry>>,
+    // 製造商品コードごとの版（有効開始日の昇順）
+    data: HashMap<String, Vec<FormulaVersion>>,
 }
 
 impl ExcelFormulaRepository {
-    pub fn new(workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>) -> Result<Self> {
-        let sheet_name = "配合マスタ";
-        let range = workbook.worksheet_range(sheet_name)?;
+    pub fn new(
+        workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>,
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let range = read_sheet(workbook, &mapping.sheet_name(FormulaSheetSchema::SHEET))?;
         let rows: Vec<_> = range.rows().collect();
+        Self::from_rows(&rows, mapping, diagnostics)
+    }
 
+    /// 見出し行から始まる行を読み取る（CSVリポジトリと共通）
+    pub fn from_rows(
+        rows: &[&[Data]],
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let sheet_name = &mapping.sheet_name(FormulaSheetSchema::SHEET);
         if rows.is_empty() {
-            return Err(eyre!("配合マスタシートが空です"));
+            return Err(empty_sheet(sheet_name));
         }
 
-        let header_row = rows[0];
-        let col_product_code = find_column_index(header_row, "製造商品コード", sheet_name)?;
-        let col_material_code = find_column_index(header_row, "材料商品コード", sheet_name)?;
-        let col_consumption_ratio = find_column_index(header_row, "消費比率", sheet_name)?;
-
-        let mut data: HashMap<String, Vec<FormulaEntry>> = HashMap::new();
+        // 有効期間の列は任意（ない場合はすべての行が無期限の1つの版）
+        let schema = FormulaSheetSchema::from_headers(&header_strings(rows[0]), mapping)?;
 
-        for row in rows.iter().skip(1) {
-            let product_code_str = get_cell_string(row, col_product_code);
-            let material_code_str = get_cell_string(row, col_material_code);
-            let consumption_ratio_str = get_cell_string(row, col_consumption_ratio);
+        // 重複チェックのため版の先頭行番号と一緒に保持
+        let mut versions: HashMap<String, Vec<(usize, FormulaVersion)>> = HashMap::new();
 
+        for (row_idx, row) in rows.iter().enumerate().skip(1) {
+            let product_code_str = get_cell_string(row, schema.product_code().value());
             if product_code_str.is_empty()
-                || material_code_str.is_empty()
-                || consumption_ratio_str.is_empty()
+                || get_cell_string(row, schema.material_code().value()).is_empty()
+                || get_cell_string(row, schema.consumption_ratio().value()).is_empty()
             {
                 continue;
             }
 
-            let material_code = ProductCode::new(material_code_str)?;
-            let consumption_ratio = ConsumptionRatio::new(consumption_ratio_str.parse()?)?;
+            let parsed = Self::parse_row(row, row_idx, &schema);
+            let Some((product_code, entry, valid_from, valid_to)) = diagnostics.collect(parsed)?
+            else {
+                continue;
+            };
+
+            let product_versions = versions.entry(product_code_str).or_default();
+            let index = match product_versions
+                .iter()
+                .position(|(_, v)| v.has_period(valid_from.as_ref(), valid_to.as_ref()))
+            {
+                Some(index) => index,
+                None => match FormulaVersion::new(product_code, valid_from, valid_to) {
+                    Ok(version) => {
+                        product_versions.push((row_idx + 1, version));
+                        product_versions.len() - 1
+                    }
+                    Err(e) => {
+                        diagnostics.report(row_error(sheet_name, row_idx, e))?;
+                        continue;
+                    }
+                },
+            };
+
+            product_versions[index].1.add_entry(entry);
+        }
 
-            let entry = FormulaEntry::new(material_code, consumption_ratio);
+        let mut data: HashMap<String, Vec<FormulaVersion>> = HashMap::new();
+
+        for (product_code, mut entries) in versions {
+            entries.sort_by(|(_, a), (_, b)| a.valid_from.cmp(&b.valid_from));
+
+            // 有効開始日順に並べると、重複は隣り合う版同士で必ず現れる
+            // （蓄積モードでは重複した後の版を除外する）
+            let mut accepted: Vec<(usize, FormulaVersion)> = Vec::new();
+            for (row, version) in entries {
+                if let Some((prev_row, prev)) = accepted.last()
+                    && prev.overlaps(&version)
+                {
+                    diagnostics.report(
+                        Diagnostic::new(
+                            sheet_name,
+                            format!(
+                                "製造商品コード '{}' の有効期間が重複しています（{}行目: {}）: {}",
+                                product_code,
+                                prev_row,
+                                prev.period_label(),
+                                version.period_label()
+                            ),
+                        )
+                        .with_row(row),
+                    )?;
+                    continue;
+                }
+                accepted.push((row, version));
+            }
 
-            data.entry(product_code_str).or_default().push(entry);
+            data.insert(
+                product_code,
+                accepted.into_iter().map(|(_, version)| version).collect(),
+            );
         }
 
         Ok(Self { data })
     }
+
+    /// 1行を読み取る（製造商品コード・配合行・有効期間）
+    fn parse_row(
+        row: &[Data],
+        row_idx: usize,
+        schema: &FormulaSheetSchema,
+    ) -> std::result::Result<FormulaRow, Diagnostic> {
+        let sheet_name = schema.sheet_name();
+        let product_code = ProductCode::new(get_cell_string(row, schema.product_code().value()))
+            .map_err(|e| cell_error(sheet_name, row_idx, "製造商品コード", e))?;
+        let material_code = ProductCode::new(get_cell_string(row, schema.material_code().value()))
+            .map_err(|e| cell_error(sheet_name, row_idx, "材料商品コード", e))?;
+        let consumption_ratio = ConsumptionRatio::new(parse_number(
+            &get_cell_string(row, schema.consumption_ratio().value()),
+            sheet_name,
+            row_idx,
+            "消費比率",
+        )?)
+        .map_err(|e| cell_error(sheet_name, row_idx, "消費比率", e))?;
+        let valid_from = optional_date(row, schema.valid_from().map(|c| c.value()))
+            .map_err(|e| cell_error(sheet_name, row_idx, "有効開始日", e))?;
+        let valid_to = optional_date(row, schema.valid_to().map(|c| c.value()))
+            .map_err(|e| cell_error(sheet_name, row_idx, "有効終了日", e))?;
+
+        Ok((
+            product_code,
+            FormulaEntry::new(material_code, consumption_ratio),
+            valid_from,
+            valid_to,
+        ))
+    }
 }
 
+/// 配合マスタの1行（製造商品コード・配合行・有効開始日・有効終了日）
+type FormulaRow = (
+    ProductCode,
+    FormulaEntry,
+    Option<TransactionDate>,
+    Option<TransactionDate>,
+);
+
 impl FormulaRepository for ExcelFormulaRepository {
-    fn find_by_product_code(&self, product_code: &ProductCode) -> Result<Vec<FormulaEntry>> {
-        self.data.get(product_code.value()).cloned().ok_or_else(|| {
-            eyre!(
-                "配合マスタに商品コード '{}' が見つかりません",
-                product_code.value()
-            )
-        })
+    fn find_by_product_code_as_of(
+        &self,
+        product_code: &ProductCode,
+        date: &TransactionDate,
+    ) -> Result<Vec<FormulaEntry>> {
+        let versions =
+            self.data
+                .get(product_code.value())
+                .ok_or_else(|| DomainError::MasterNotFound {
+                    master: MasterKind::Formula,
+                    code: product_code.value().to_string(),
+                })?;
+
+        versions
+            .iter()
+            .find(|v| v.is_valid_on(date))
+            .map(|v| v.entries.clone())
+            .ok_or_else(|| DomainError::NoValidPeriod {
+                master: MasterKind::Formula,
+                code: product_code.value().to_string(),
+                date: date.value().to_string(),
+                periods: versions.iter().map(|v| v.period_label()).collect(),
+            })
+    }
+
+    fn has_formula_as_of(&self, product_code: &ProductCode, date: &TransactionDate) -> bool {
+        self.data
+            .get(product_code.value())
+            .is_some_and(|versions| versions.iter().any(|v| v.is_valid_on(date)))
+    }
+
+    fn find_all_versions(&self) -> Result<Vec<FormulaVersion>> {
+        let mut codes: Vec<&String> = self.data.keys().collect();
+        codes.sort();
+        Ok(codes
+            .into_iter()
+            .flat_map(|code| self.data[code].iter().cloned())
+            .collect())
     }
 }
 
 /// Excelベースの運賃マスタリポジトリ
 pub struct ExcelFreightMasterRepository {
-    data: HashMap<String, FreightMaster>,
+    // 運賃コードごとの有効期間（有効開始日の昇順）
+    data: HashMap<String, Vec<FreightMaster>>,
 }
 
 impl ExcelFreightMasterRepository {
-    pub fn new(workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>) -> Result<Self> {
-        let sheet_name = "運賃マスタ";
-        let range = workbook.worksheet_range(sheet_name)?;
+    /// パターン名の一致する運賃表を各運賃マスタに紐付ける
+    pub fn new(
+        workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>,
+        rate_tables: &[FreightRateTable],
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let range = read_sheet(
+            workbook,
+            &mapping.sheet_name(FreightMasterSheetSchema::SHEET),
+        )?;
         let rows: Vec<_> = range.rows().collect();
+        Self::from_rows(&rows, rate_tables, mapping, diagnostics)
+    }
 
+    /// 見出し行から始まる行を読み取る（CSVリポジトリと共通）
+    pub fn from_rows(
+        rows: &[&[Data]],
+        rate_tables: &[FreightRateTable],
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let sheet_name = &mapping.sheet_name(FreightMasterSheetSchema::SHEET);
         if rows.is_empty() {
-            return Err(eyre!("運賃マスタシートが空です"));
+            return Err(empty_sheet(sheet_name));
         }
 
-        let header_row = rows[0];
-        let col_freight_code = find_column_index(header_row, "運賃コード", sheet_name)?;
-        let col_pattern_name = find_column_index(header_row, "パターン名", sheet_name)?;
-        let col_kg_unit_price = find_column_index(header_row, "Kg単価", sheet_name)?;
-        let col_valid_from = find_column_index(header_row, "有効開始日", sheet_name)?;
-        let col_valid_to = find_column_index(header_row, "有効終了日", sheet_name)?;
+        let schema = FreightMasterSheetSchema::from_headers(&header_strings(rows[0]), mapping)?;
 
-        let mut data: HashMap<String, FreightMaster> = HashMap::new();
+        // 重複チェックのため行番号と一緒に保持
+        let mut periods: HashMap<String, Vec<(usize, FreightMaster)>> = HashMap::new();
 
         for (row_idx, row) in rows.iter().enumerate().skip(1) {
-            let freight_code_str = get_cell_string(row, col_freight_code);
-            let pattern_name_str = get_cell_string(row, col_pattern_name);
-            let kg_unit_price_str = get_cell_string(row, col_kg_unit_price);
-            let valid_from_str = get_cell_date_string(row, col_valid_from);
-            let valid_to_str = get_cell_date_string(row, col_valid_to);
-
-            if freight_code_str.is_empty()
-                || pattern_name_str.is_empty()
-                || kg_unit_price_str.is_empty()
-                || valid_from_str.is_empty()
+            if get_cell_string(row, schema.freight_code().value()).is_empty()
+                || get_cell_string(row, schema.pattern_name().value()).is_empty()
+                || get_cell_string(row, schema.kg_unit_price().value()).is_empty()
+                || get_cell_date_string(row, schema.valid_from().value()).is_empty()
             {
                 continue;
             }
 
-            // パース処理のみ（バリデーションはドメイン層で実施）
-            let pattern_name = PatternName::new(pattern_name_str.clone())
-                .map_err(|e| eyre!("運賃マスタ {}行目: {}", row_idx + 1, e))?;
-
-            let kg_unit_price: f64 = kg_unit_price_str.parse().map_err(|_| {
-                eyre!(
-                    "運賃マスタ {}行目: Kg単価が数値ではありません: '{}'",
-                    row_idx + 1,
-                    kg_unit_price_str
-                )
-            })?;
-
-            let valid_from = TransactionDate::new(valid_from_str)
-                .map_err(|e| eyre!("運賃マスタ {}行目: {}", row_idx + 1, e))?;
-
-            let valid_to = if valid_to_str.is_empty() {
-                None
-            } else {
-                Some(
-                    TransactionDate::new(valid_to_str)
-                        .map_err(|e| eyre!("運賃マスタ {}行目: {}", row_idx + 1, e))?,
-                )
+            let Some(mut freight_master) =
+                diagnostics.collect(Self::parse_row(row, row_idx, &schema))?
+            else {
+                continue;
             };
+            if let Some(table) = rate_tables
+                .iter()
+                .find(|t| t.pattern_name == freight_master.pattern_name)
+            {
+                freight_master = freight_master.with_rate_table(table.clone());
+            }
 
-            let freight_master = FreightMaster::new(
-                freight_code_str.clone(),
-                pattern_name,
-                Amount::new(kg_unit_price)?,
-                valid_from,
-                valid_to,
-            )
-            .map_err(|e| eyre!("運賃マスタ {}行目: {}", row_idx + 1, e))?;
+            periods
+                .entry(freight_master.freight_code.clone())
+                .or_default()
+                .push((row_idx + 1, freight_master));
+        }
+
+        let mut data: HashMap<String, Vec<FreightMaster>> = HashMap::new();
+
+        for (freight_code, mut entries) in periods {
+            entries.sort_by(|(_, a), (_, b)| a.valid_from.cmp(&b.valid_from));
+
+            // 有効開始日順に並べると、重複は隣り合う期間同士で必ず現れる
+            // （蓄積モードでは重複した後の期間を除外する）
+            let mut accepted: Vec<(usize, FreightMaster)> = Vec::new();
+            for (row, master) in entries {
+                if let Some((prev_row, prev)) = accepted.last()
+                    && prev.overlaps(&master)
+                {
+                    diagnostics.report(
+                        Diagnostic::new(
+                            sheet_name,
+                            format!(
+                                "運賃コード '{}' の有効期間が重複しています（{}行目: {}）: {}",
+                                freight_code,
+                                prev_row,
+                                prev.period_label(),
+                                master.period_label()
+                            ),
+                        )
+                        .with_row(row),
+                    )?;
+                    continue;
+                }
+                accepted.push((row, master));
+            }
 
-            data.insert(freight_code_str, freight_master);
+            data.insert(
+                freight_code,
+                accepted.into_iter().map(|(_, master)| master).collect(),
+            );
         }
 
         Ok(Self { data })
     }
+
+    /// 全運賃マスタを運賃コード・有効開始日の順で取得（データベースへの取り込み用）
+    pub fn find_all(&self) -> Result<Vec<FreightMaster>> {
+        let mut codes: Vec<&String> = self.data.keys().collect();
+        codes.sort();
+        Ok(codes
+            .into_iter()
+            .flat_map(|code| self.data[code].iter().cloned())
+            .collect())
+    }
+
+    /// 1行を読み取る（パース処理のみ、バリデーションはドメイン層で実施）
+    fn parse_row(
+        row: &[Data],
+        row_idx: usize,
+        schema: &FreightMasterSheetSchema,
+    ) -> std::result::Result<FreightMaster, Diagnostic> {
+        let sheet_name = schema.sheet_name();
+        let pattern_name = PatternName::new(get_cell_string(row, schema.pattern_name().value()))
+            .map_err(|e| cell_error(sheet_name, row_idx, "パターン名", e))?;
+
+        let kg_unit_price = Amount::new(parse_number(
+            &get_cell_string(row, schema.kg_unit_price().value()),
+            sheet_name,
+            row_idx,
+            "Kg単価",
+        )?)
+        .map_err(|e| cell_error(sheet_name, row_idx, "Kg単価", e))?;
+
+        let valid_from =
+            TransactionDate::new(get_cell_date_string(row, schema.valid_from().value()))
+                .map_err(|e| cell_error(sheet_name, row_idx, "有効開始日", e))?;
+        let valid_to = optional_date(row, Some(schema.valid_to().value()))
+            .map_err(|e| cell_error(sheet_name, row_idx, "有効終了日", e))?;
+
+        let freight_master = FreightMaster::new(
+            get_cell_string(row, schema.freight_code().value()),
+            pattern_name,
+            kg_unit_price,
+            valid_from,
+            valid_to,
+        )
+        .map_err(|e| row_error(sheet_name, row_idx, e))?;
+
+        let zone = schema
+            .zone()
+            .map(|col| get_cell_string(row, col.value()))
+            .unwrap_or_default();
+        if zone.is_empty() {
+            Ok(freight_master)
+        } else {
+            Ok(freight_master.with_zone(zone))
+        }
+    }
 }
 
 impl FreightMasterRepository for ExcelFreightMasterRepository {
-    fn find_by_code(&self, freight_code: &str) -> Result<FreightMaster> {
-        self.data
+    fn find_by_code_as_of(
+        &self,
+        freight_code: &str,
+        date: &TransactionDate,
+    ) -> Result<FreightMaster> {
+        let masters = self
+            .data
             .get(freight_code)
+            .ok_or_else(|| DomainError::MasterNotFound {
+                master: MasterKind::FreightMaster,
+                code: freight_code.to_string(),
+            })?;
+
+        masters
+            .iter()
+            .find(|m| m.is_valid_on(date))
             .cloned()
-            .ok_or_else(|| eyre!("運賃マスタに運賃コード '{}' が見つかりません", freight_code))
+            .ok_or_else(|| DomainError::NoValidPeriod {
+                master: MasterKind::FreightMaster,
+                code: freight_code.to_string(),
+                date: date.value().to_string(),
+                periods: masters.iter().map(|m| m.period_label()).collect(),
+            })
     }
 }
 
 /// Excelベースの仕入リポジトリ
 pub struct ExcelPurchaseRepository {
-    data: HashMap<String, Purchase>,
+    // 商品コードごとの仕入履歴（仕入日の昇順）
+    data: HashMap<String, Vec<Purchase>>,
 }
 
 impl ExcelPurchaseRepository {
-    pub fn new(workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>) -> Result<Self> {
-        let sheet_name = "【入庫】仕入";
-        let range = workbook.worksheet_range(sheet_name)?;
+    /// 仕入の数量・単価は単位換算マスタで基準単位（kg）に正規化する
+    ///
+    /// 運賃総額の入力された便は、同じ便番号の仕入ロットに配賦基準で運賃を配賦する。
+    pub fn new(
+        workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>,
+        conversions: &[UnitConversion],
+        allocation: FreightAllocationMethod,
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let range = read_sheet(workbook, &mapping.sheet_name(PurchaseSheetSchema::SHEET))?;
         let rows: Vec<_> = range.rows().collect();
+        Self::from_rows(&rows, conversions, allocation, mapping, diagnostics)
+    }
 
+    /// 見出し行から始まる行を読み取る（CSVリポジトリと共通）
+    pub fn from_rows(
+        rows: &[&[Data]],
+        conversions: &[UnitConversion],
+        allocation: FreightAllocationMethod,
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let sheet_name = &mapping.sheet_name(PurchaseSheetSchema::SHEET);
         if rows.is_empty() {
-            return Err(eyre!("【入庫】仕入シートが空です"));
+            return Err(empty_sheet(sheet_name));
         }
 
-        let headers: Vec<String> = rows[0]
-            .iter()
-            .map(|cell| cell.to_string().trim().to_string())
-            .collect();
+        let headers = header_strings(rows[0]);
 
-        let schema = PurchaseSheetSchema::from_headers(&headers)?;
+        let schema = PurchaseSheetSchema::from_headers(&headers, mapping)?;
 
-        let mut data: HashMap<String, Purchase> = HashMap::new();
+        let mut purchases = Vec::new();
 
         for (row_idx, row) in rows.iter().enumerate().skip(1) {
-            let product_code_str = get_cell_string(row, schema.product_code().value());
-            let product_name = get_cell_string(row, schema.product_name().value());
-            let unit_price_str = get_cell_string(row, schema.unit_price().value());
-            let quantity_str = get_cell_string(row, schema.quantity().value());
-            let freight_str = get_cell_string(row, schema.freight().value());
-
-            if product_code_str.is_empty() || unit_price_str.is_empty() {
+            if get_cell_string(row, schema.product_code().value()).is_empty()
+                || get_cell_string(row, schema.unit_price().value()).is_empty()
+            {
                 continue;
             }
 
-            let unit_price = Amount::new(unit_price_str.parse().map_err(|_| {
-                eyre!(
-                    "【入庫】仕入シート {}行目: 仕入単価が数値ではありません: '{}'",
-                    row_idx + 1,
-                    unit_price_str
-                )
-            })?)?;
-
-            let quantity: f64 = if quantity_str.is_empty() {
-                0.0
-            } else {
-                quantity_str.parse().map_err(|_| {
-                    eyre!(
-                        "【入庫】仕入シート {}行目: 数量が数値ではありません: '{}'",
-                        row_idx + 1,
-                        quantity_str
-                    )
-                })?
-            };
-
-            let freight_code = if freight_str.is_empty() {
-                FreightCode::DirectPrice(0.0)
-            } else {
-                FreightCode::new(freight_str.clone())
-                    .map_err(|e| eyre!("【入庫】仕入シート {}行目: {}", row_idx + 1, e))?
+            let Some(purchase) =
+                diagnostics.collect(Self::parse_row(row, row_idx, &schema, conversions))?
+            else {
+                continue;
             };
+            purchases.push(purchase);
+        }
 
-            let purchase = Purchase::new(
-                product_name,
-                unit_price,
-                Quantity::new(quantity)?,
-                freight_code,
-            );
+        let mut data: HashMap<String, Vec<Purchase>> = HashMap::new();
+        for purchase in FreightAllocationService::allocate(purchases, allocation)? {
+            data.entry(purchase.product_code.value().to_string())
+                .or_default()
+                .push(purchase);
+        }
 
-            data.insert(product_code_str, purchase);
+        // 同日の仕入は行順を保ったまま仕入日順に並べる（安定ソート）
+        for purchases in data.values_mut() {
+            purchases.sort_by(|a, b| a.purchase_date.cmp(&b.purchase_date));
         }
 
         Ok(Self { data })
     }
+
+    /// 1行を読み取る
+    fn parse_row(
+        row: &[Data],
+        row_idx: usize,
+        schema: &PurchaseSheetSchema,
+        conversions: &[UnitConversion],
+    ) -> std::result::Result<Purchase, Diagnostic> {
+        let sheet_name = schema.sheet_name();
+        let purchase_date =
+            TransactionDate::new(get_cell_date_string(row, schema.purchase_date().value()))
+                .map_err(|e| cell_error(sheet_name, row_idx, "仕入日", e))?;
+
+        let product_code = ProductCode::new(get_cell_string(row, schema.product_code().value()))
+            .map_err(|e| cell_error(sheet_name, row_idx, "商品コード", e))?;
+
+        let unit_price = Amount::new(parse_number(
+            &get_cell_string(row, schema.unit_price().value()),
+            sheet_name,
+            row_idx,
+            "仕入単価",
+        )?)
+        .map_err(|e| cell_error(sheet_name, row_idx, "仕入単価", e))?;
+
+        let quantity_str = get_cell_string(row, schema.quantity().value());
+        let quantity = if quantity_str.is_empty() {
+            0.0
+        } else {
+            parse_number(&quantity_str, sheet_name, row_idx, "数量")?
+        };
+        let unit = optional_unit(row, schema.unit().map(|c| c.value()))
+            .map_err(|e| cell_error(sheet_name, row_idx, "単位", e))?;
+        let quantity = Quantity::in_unit(quantity, unit)
+            .map_err(|e| cell_error(sheet_name, row_idx, "数量", e))?;
+
+        let freight_str = get_cell_string(row, schema.freight().value());
+        let freight_code = if freight_str.is_empty() {
+            FreightCode::DirectPrice(0.0)
+        } else {
+            FreightCode::new(freight_str).map_err(|e| cell_error(sheet_name, row_idx, "運賃", e))?
+        };
+
+        // 便の運賃総額が入力されている場合は、運賃列より優先して便単位で配賦する
+        let shipment_freight_str = schema
+            .shipment_freight()
+            .map(|c| get_cell_string(row, c.value()))
+            .unwrap_or_default();
+        let freight_code = if shipment_freight_str.is_empty() {
+            freight_code
+        } else {
+            FreightCode::LotFreight(
+                Amount::new(parse_number(
+                    &shipment_freight_str,
+                    sheet_name,
+                    row_idx,
+                    "運賃総額",
+                )?)
+                .map_err(|e| cell_error(sheet_name, row_idx, "運賃総額", e))?,
+            )
+        };
+
+        let mut purchase = Purchase::new(
+            purchase_date,
+            product_code,
+            get_cell_string(row, schema.product_name().value()),
+            unit_price,
+            quantity,
+            freight_code,
+        );
+        let shipment = schema
+            .shipment()
+            .map(|c| get_cell_string(row, c.value()))
+            .unwrap_or_default();
+        if !shipment.is_empty() {
+            purchase = purchase.with_shipment(shipment);
+        }
+        UnitConversionService::normalize_purchase(conversions, purchase)
+            .map_err(|e| cell_error(sheet_name, row_idx, "単位", e))
+    }
 }
 
 impl PurchaseRepository for ExcelPurchaseRepository {
-    fn find_latest_price(&self, product_code: &ProductCode) -> Result<Purchase> {
-        self.data.get(product_code.value()).cloned().ok_or_else(|| {
-            eyre!(
-                "仕入データに商品コード '{}' が見つかりません",
-                product_code.value()
-            )
-        })
+    fn find_price_as_of(
+        &self,
+        product_code: &ProductCode,
+        date: &TransactionDate,
+    ) -> Result<Purchase> {
+        let purchases = self
+            .data
+            .get(product_code.value())
+            .ok_or_else(|| purchase_not_found(product_code))?;
+
+        // 指定日以前の仕入のうち最も新しいもの（同日なら後の行）を採用
+        purchases
+            .iter()
+            .rev()
+            .find(|p| p.purchase_date <= *date)
+            .cloned()
+            .ok_or_else(|| DomainError::NoPurchaseBefore {
+                code: product_code.value().to_string(),
+                date: date.value().to_string(),
+            })
+    }
+
+    fn find_history(&self, product_code: &ProductCode) -> Result<Vec<Purchase>> {
+        self.data
+            .get(product_code.value())
+            .cloned()
+            .ok_or_else(|| purchase_not_found(product_code))
+    }
+
+    fn find_all(&self) -> Result<Vec<Purchase>> {
+        let mut codes: Vec<&String> = self.data.keys().collect();
+        codes.sort();
+        Ok(codes
+            .into_iter()
+            .flat_map(|code| self.data[code].iter().cloned())
+            .collect())
+    }
+}
+
+fn purchase_not_found(product_code: &ProductCode) -> DomainError {
+    DomainError::MasterNotFound {
+        master: MasterKind::Purchase,
+        code: product_code.value().to_string(),
     }
 }
 
@@ -328,142 +751,185 @@ pub struct ExcelInventoryTransactionRepository {
 }
 
 impl ExcelInventoryTransactionRepository {
-    pub fn new(workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>) -> Result<Self> {
+    /// 数量は単位換算マスタで基準単位（kg）に正規化する
+    pub fn new(
+        workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>,
+        conversions: &[UnitConversion],
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let production =
+            read_optional_sheet(workbook, &mapping.sheet_name(ProductionSheetSchema::SHEET));
+        let purchase =
+            read_optional_sheet(workbook, &mapping.sheet_name(PurchaseSheetSchema::SHEET));
+        let sales = read_optional_sheet(workbook, &mapping.sheet_name(SalesSheetSchema::SHEET));
+        Self::from_rows(
+            &production.rows().collect::<Vec<_>>(),
+            &purchase.rows().collect::<Vec<_>>(),
+            &sales.rows().collect::<Vec<_>>(),
+            conversions,
+            mapping,
+            diagnostics,
+        )
+    }
+
+    /// 生産・仕入・売上それぞれの見出し行から始まる行を読み取る（CSVリポジトリと共通）
+    pub fn from_rows(
+        production_rows: &[&[Data]],
+        purchase_rows: &[&[Data]],
+        sales_rows: &[&[Data]],
+        conversions: &[UnitConversion],
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
         let mut transactions = Vec::new();
 
         // 【入庫】生産シートから読み込み
-        if let Ok(range) = workbook.worksheet_range("【入庫】生産") {
-            let rows: Vec<_> = range.rows().collect();
-            if !rows.is_empty() {
-                let headers: Vec<String> = rows[0]
-                    .iter()
-                    .map(|cell| cell.to_string().trim().to_string())
-                    .collect();
-
-                let schema = ProductionSheetSchema::from_headers(&headers)?;
-
-                for (row_idx, row) in rows.iter().enumerate().skip(1) {
-                    let date_str = get_cell_date_string(row, schema.production_date().value());
-                    let product_code_str = get_cell_string(row, schema.product_code().value());
-                    let quantity_str = get_cell_string(row, schema.quantity().value());
-
-                    if !date_str.is_empty()
-                        && !product_code_str.is_empty()
-                        && !quantity_str.is_empty()
-                    {
-                        let quantity = quantity_str.parse::<f64>().map_err(|_| {
-                            eyre!(
-                                "【入庫】生産シート {}行目: 生産数量が数値ではありません: '{}'",
-                                row_idx + 1,
-                                quantity_str
-                            )
-                        })?;
-
-                        let transaction_date = TransactionDate::new(date_str.clone())
-                            .map_err(|e| eyre!("【入庫】生産シート {}行目: {}", row_idx + 1, e))?;
-
-                        transactions.push(InventoryTransaction::new(
-                            transaction_date,
-                            InventoryType::Production,
-                            ProductCode::new(product_code_str.clone())?,
-                            product_code_str,
-                            Quantity::new(quantity)?,
-                        ));
-                    }
-                }
-            }
+        if let Some(header_row) = production_rows.first() {
+            let schema = ProductionSheetSchema::from_headers(&header_strings(header_row), mapping)?;
+            let columns = TransactionColumns {
+                sheet_name: schema.sheet_name().to_string(),
+                inventory_type: InventoryType::Production,
+                date: (schema.production_date().value(), "生産日"),
+                product_code: schema.product_code().value(),
+                product_name: None,
+                quantity: (schema.quantity().value(), "生産数量"),
+                unit: None,
+            };
+            Self::read_rows(
+                production_rows,
+                &columns,
+                conversions,
+                diagnostics,
+                &mut transactions,
+            )?;
         }
 
         // 【入庫】仕入シートから読み込み
-        if let Ok(range) = workbook.worksheet_range("【入庫】仕入") {
-            let rows: Vec<_> = range.rows().collect();
-            if !rows.is_empty() {
-                let headers: Vec<String> = rows[0]
-                    .iter()
-                    .map(|cell| cell.to_string().trim().to_string())
-                    .collect();
-
-                let schema = PurchaseSheetSchema::from_headers(&headers)?;
-
-                for (row_idx, row) in rows.iter().enumerate().skip(1) {
-                    let date_str = get_cell_date_string(row, schema.purchase_date().value());
-                    let product_code_str = get_cell_string(row, schema.product_code().value());
-                    let product_name = get_cell_string(row, schema.product_name().value());
-                    let quantity_str = get_cell_string(row, schema.quantity().value());
-
-                    if !date_str.is_empty()
-                        && !product_code_str.is_empty()
-                        && !quantity_str.is_empty()
-                    {
-                        let quantity = quantity_str.parse::<f64>().map_err(|_| {
-                            eyre!(
-                                "【入庫】仕入シート {}行目: 数量が数値ではありません: '{}'",
-                                row_idx + 1,
-                                quantity_str
-                            )
-                        })?;
-
-                        let transaction_date = TransactionDate::new(date_str.clone())
-                            .map_err(|e| eyre!("【入庫】仕入シート {}行目: {}", row_idx + 1, e))?;
-
-                        transactions.push(InventoryTransaction::new(
-                            transaction_date,
-                            InventoryType::Purchase,
-                            ProductCode::new(product_code_str)?,
-                            product_name,
-                            Quantity::new(quantity)?,
-                        ));
-                    }
-                }
-            }
+        if let Some(header_row) = purchase_rows.first() {
+            let schema = PurchaseSheetSchema::from_headers(&header_strings(header_row), mapping)?;
+            let columns = TransactionColumns {
+                sheet_name: schema.sheet_name().to_string(),
+                inventory_type: InventoryType::Purchase,
+                date: (schema.purchase_date().value(), "仕入日"),
+                product_code: schema.product_code().value(),
+                product_name: Some(schema.product_name().value()),
+                quantity: (schema.quantity().value(), "数量"),
+                unit: schema.unit().map(|c| c.value()),
+            };
+            Self::read_rows(
+                purchase_rows,
+                &columns,
+                conversions,
+                diagnostics,
+                &mut transactions,
+            )?;
         }
 
         // 【出庫】売上シートから読み込み
-        if let Ok(range) = workbook.worksheet_range("【出庫】売上") {
-            let rows: Vec<_> = range.rows().collect();
-            if !rows.is_empty() {
-                let headers: Vec<String> = rows[0]
-                    .iter()
-                    .map(|cell| cell.to_string().trim().to_string())
-                    .collect();
-
-                let schema = SalesSheetSchema::from_headers(&headers)?;
-
-                for (row_idx, row) in rows.iter().enumerate().skip(1) {
-                    let date_str = get_cell_date_string(row, schema.sales_date().value());
-                    let product_code_str = get_cell_string(row, schema.product_code().value());
-                    let product_name = get_cell_string(row, schema.product_name().value());
-                    let quantity_str = get_cell_string(row, schema.quantity().value());
-
-                    if !date_str.is_empty()
-                        && !product_code_str.is_empty()
-                        && !quantity_str.is_empty()
-                    {
-                        let quantity = quantity_str.parse::<f64>().map_err(|_| {
-                            eyre!(
-                                "【出庫】売上シート {}行目: 数量が数値ではありません: '{}'",
-                                row_idx + 1,
-                                quantity_str
-                            )
-                        })?;
-
-                        let transaction_date = TransactionDate::new(date_str.clone())
-                            .map_err(|e| eyre!("【出庫】売上シート {}行目: {}", row_idx + 1, e))?;
-
-                        transactions.push(InventoryTransaction::new(
-                            transaction_date,
-                            InventoryType::Sales,
-                            ProductCode::new(product_code_str)?,
-                            product_name,
-                            Quantity::new(quantity)?,
-                        ));
-                    }
-                }
-            }
+        if let Some(header_row) = sales_rows.first() {
+            let schema = SalesSheetSchema::from_headers(&header_strings(header_row), mapping)?;
+            let columns = TransactionColumns {
+                sheet_name: schema.sheet_name().to_string(),
+                inventory_type: InventoryType::Sales,
+                date: (schema.sales_date().value(), "売上日"),
+                product_code: schema.product_code().value(),
+                product_name: Some(schema.product_name().value()),
+                quantity: (schema.quantity().value(), "数量"),
+                unit: None,
+            };
+            Self::read_rows(
+                sales_rows,
+                &columns,
+                conversions,
+                diagnostics,
+                &mut transactions,
+            )?;
         }
 
         Ok(Self { transactions })
     }
+
+    /// シートの各行をトランザクションとして読み取る（日付・商品コード・数量が空の行はスキップ）
+    fn read_rows(
+        rows: &[&[Data]],
+        columns: &TransactionColumns,
+        conversions: &[UnitConversion],
+        diagnostics: &mut Diagnostics,
+        transactions: &mut Vec<InventoryTransaction>,
+    ) -> Result<()> {
+        for (row_idx, row) in rows.iter().enumerate().skip(1) {
+            if get_cell_date_string(row, columns.date.0).is_empty()
+                || get_cell_string(row, columns.product_code).is_empty()
+                || get_cell_string(row, columns.quantity.0).is_empty()
+            {
+                continue;
+            }
+
+            if let Some(transaction) =
+                diagnostics.collect(Self::parse_row(row, row_idx, columns, conversions))?
+            {
+                transactions.push(transaction);
+            }
+        }
+        Ok(())
+    }
+
+    /// 1行を読み取る（商品名の列がないシートは商品コードを商品名とする）
+    fn parse_row(
+        row: &[Data],
+        row_idx: usize,
+        columns: &TransactionColumns,
+        conversions: &[UnitConversion],
+    ) -> std::result::Result<InventoryTransaction, Diagnostic> {
+        let sheet_name = columns.sheet_name.as_str();
+        let (quantity_col, quantity_name) = columns.quantity;
+        let unit = optional_unit(row, columns.unit)
+            .map_err(|e| cell_error(sheet_name, row_idx, "単位", e))?;
+        let quantity = Quantity::in_unit(
+            parse_number(
+                &get_cell_string(row, quantity_col),
+                sheet_name,
+                row_idx,
+                quantity_name,
+            )?,
+            unit,
+        )
+        .map_err(|e| cell_error(sheet_name, row_idx, quantity_name, e))?;
+
+        let (date_col, date_name) = columns.date;
+        let transaction_date = TransactionDate::new(get_cell_date_string(row, date_col))
+            .map_err(|e| cell_error(sheet_name, row_idx, date_name, e))?;
+
+        let product_code_str = get_cell_string(row, columns.product_code);
+        let product_name = columns
+            .product_name
+            .map(|col| get_cell_string(row, col))
+            .unwrap_or_else(|| product_code_str.clone());
+        let product_code = ProductCode::new(product_code_str)
+            .map_err(|e| cell_error(sheet_name, row_idx, "商品コード", e))?;
+        let quantity = UnitConversionService::to_kilograms(conversions, &product_code, &quantity)
+            .map_err(|e| cell_error(sheet_name, row_idx, "単位", e))?;
+
+        Ok(InventoryTransaction::new(
+            transaction_date,
+            columns.inventory_type.clone(),
+            product_code,
+            product_name,
+            quantity,
+        ))
+    }
+}
+
+/// 入出庫トランザクションとして読み取るシートの列（列インデックスと列名）
+struct TransactionColumns {
+    sheet_name: String,
+    inventory_type: InventoryType,
+    date: (usize, &'static str),
+    product_code: usize,
+    product_name: Option<usize>,
+    quantity: (usize, &'static str),
+    unit: Option<usize>, // 単位の列（列がない場合は kg）
 }
 
 impl InventoryTransactionRepository for ExcelInventoryTransactionRepository {
@@ -475,123 +941,639 @@ impl InventoryTransactionRepository for ExcelInventoryTransactionRepository {
 /// Excel生産データリポジトリ
 pub struct ExcelProductionRepository {
     productions: Vec<Production>,
+    source_rows: Vec<usize>, // productions と同じ順のシートの行番号
 }
 
 impl ExcelProductionRepository {
-    pub fn new(workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>) -> Result<Self> {
-        let sheet_name = "【入庫】生産";
-        let range = workbook.worksheet_range(sheet_name)?;
+    pub fn new(
+        workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>,
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let range = read_sheet(workbook, &mapping.sheet_name(ProductionSheetSchema::SHEET))?;
         let rows: Vec<_> = range.rows().collect();
+        Self::from_rows(&rows, mapping, diagnostics)
+    }
 
+    /// 見出し行から始まる行を読み取る（CSVリポジトリと共通）
+    pub fn from_rows(
+        rows: &[&[Data]],
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let sheet_name = &mapping.sheet_name(ProductionSheetSchema::SHEET);
         if rows.is_empty() {
-            return Err(eyre!("【入庫】生産シートが空です"));
+            return Err(empty_sheet(sheet_name));
         }
 
-        let headers: Vec<String> = rows[0]
-            .iter()
-            .map(|cell| cell.to_string().trim().to_string())
-            .collect();
+        let headers = header_strings(rows[0]);
 
-        let schema = ProductionSheetSchema::from_headers(&headers)?;
+        let schema = ProductionSheetSchema::from_headers(&headers, mapping)?;
 
         let mut productions = Vec::new();
+        let mut source_rows = Vec::new();
+
+        for (row_idx, row) in rows.iter().enumerate().skip(1) {
+            if let Some(production) = diagnostics.collect(Self::parse_row(row, row_idx, &schema))? {
+                productions.push(production);
+                source_rows.push(row_idx + 1);
+            }
+        }
+
+        Ok(Self {
+            productions,
+            source_rows,
+        })
+    }
+
+    /// 1行を読み取る
+    fn parse_row(
+        row: &[Data],
+        row_idx: usize,
+        schema: &ProductionSheetSchema,
+    ) -> std::result::Result<Production, Diagnostic> {
+        let sheet_name = schema.sheet_name();
+        let production_date = get_cell_date_string(row, schema.production_date().value());
+        let product_code_str = get_cell_string(row, schema.product_code().value());
+        let quantity_str = get_cell_string(row, schema.quantity().value());
+        let yield_rate_str = get_cell_string(row, schema.yield_rate().value());
+        let coagulant_str = get_cell_string(row, schema.coagulant().value());
+        let clay_treatment_str = get_cell_string(row, schema.clay_treatment().value());
+
+        // 必須項目チェック
+        let missing: Vec<&str> = [
+            ("生産日", &production_date),
+            ("商品コード", &product_code_str),
+            ("生産数量", &quantity_str),
+            ("歩留率", &yield_rate_str),
+        ]
+        .iter()
+        .filter(|(_, value)| value.is_empty())
+        .map(|(name, _)| *name)
+        .collect();
+        if !missing.is_empty() {
+            return Err(cell_error(
+                sheet_name,
+                row_idx,
+                &missing.join(", "),
+                "必須データが欠けています",
+            ));
+        }
+
+        let quantity = Quantity::new(parse_number(
+            &quantity_str,
+            sheet_name,
+            row_idx,
+            "生産数量",
+        )?)
+        .map_err(|e| cell_error(sheet_name, row_idx, "生産数量", e))?;
+
+        let yield_rate = YieldRate::new(parse_number(
+            &yield_rate_str,
+            sheet_name,
+            row_idx,
+            "歩留率",
+        )?)
+        .map_err(|e| cell_error(sheet_name, row_idx, "歩留率", e))?;
+
+        let coagulant_cost = if coagulant_str.is_empty() {
+            0.0
+        } else {
+            parse_number(&coagulant_str, sheet_name, row_idx, "凝集剤")?
+        };
+        let coagulant_cost = Amount::new(coagulant_cost)
+            .map_err(|e| cell_error(sheet_name, row_idx, "凝集剤", e))?;
+
+        let clay_treatment_cost = if clay_treatment_str.is_empty() {
+            0.0
+        } else {
+            parse_number(&clay_treatment_str, sheet_name, row_idx, "粘土処理")?
+        };
+        let clay_treatment_cost = Amount::new(clay_treatment_cost)
+            .map_err(|e| cell_error(sheet_name, row_idx, "粘土処理", e))?;
+
+        let production_date = TransactionDate::new(production_date)
+            .map_err(|e| cell_error(sheet_name, row_idx, "生産日", e))?;
+
+        let product_code = ProductCode::new(product_code_str)
+            .map_err(|e| cell_error(sheet_name, row_idx, "商品コード", e))?;
+
+        let production = Production::new(
+            production_date,
+            product_code,
+            quantity,
+            yield_rate,
+            coagulant_cost,
+            clay_treatment_cost,
+        );
+
+        let plant = schema
+            .plant()
+            .map(|col| get_cell_string(row, col.value()))
+            .unwrap_or_default();
+        if plant.is_empty() {
+            Ok(production)
+        } else {
+            Ok(production.with_plant(plant))
+        }
+    }
+}
+
+impl ProductionRepository for ExcelProductionRepository {
+    fn find_all(&self) -> Result<Vec<Production>> {
+        Ok(self.productions.clone())
+    }
+
+    fn source_row(&self, index: usize) -> Option<usize> {
+        self.source_rows.get(index).copied()
+    }
+}
+
+/// Excel期首在庫リポジトリ
+pub struct ExcelOpeningBalanceRepository {
+    opening_balances: Vec<OpeningBalance>,
+}
+
+impl ExcelOpeningBalanceRepository {
+    /// 【期首】在庫シートがない場合は期首在庫なし（すべて0から開始）として扱う
+    pub fn new(
+        workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>,
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let range = read_optional_sheet(
+            workbook,
+            &mapping.sheet_name(OpeningBalanceSheetSchema::SHEET),
+        );
+        let rows: Vec<_> = range.rows().collect();
+        Self::from_rows(&rows, mapping, diagnostics)
+    }
+
+    /// 見出し行から始まる行を読み取る（CSVリポジトリと共通、行がない場合は期首在庫なし）
+    pub fn from_rows(
+        rows: &[&[Data]],
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let sheet_name = &mapping.sheet_name(OpeningBalanceSheetSchema::SHEET);
+        if rows.is_empty() {
+            return Ok(Self {
+                opening_balances: Vec::new(),
+            });
+        }
+
+        let headers = header_strings(rows[0]);
+
+        let schema = OpeningBalanceSheetSchema::from_headers(&headers, mapping)?;
+
+        let mut opening_balances: Vec<OpeningBalance> = Vec::new();
 
         for (row_idx, row) in rows.iter().enumerate().skip(1) {
-            let production_date = get_cell_date_string(row, schema.production_date().value());
             let product_code_str = get_cell_string(row, schema.product_code().value());
             let quantity_str = get_cell_string(row, schema.quantity().value());
-            let yield_rate_str = get_cell_string(row, schema.yield_rate().value());
-            let coagulant_str = get_cell_string(row, schema.coagulant().value());
-            let clay_treatment_str = get_cell_string(row, schema.clay_treatment().value());
-
-            // 必須項目チェック
-            if production_date.is_empty()
-                || product_code_str.is_empty()
-                || quantity_str.is_empty()
-                || yield_rate_str.is_empty()
+
+            if product_code_str.is_empty() || quantity_str.is_empty() {
+                continue;
+            }
+
+            if opening_balances
+                .iter()
+                .any(|o| o.product_code.value() == product_code_str)
             {
-                return Err(eyre!(
-                    "【入庫】生産シートの{}行目に必須データが欠けています\n  生産日: {}\n  商品コード: {}\n  生産数量: {}\n  歩留率: {}",
-                    row_idx + 1,
-                    if production_date.is_empty() {
-                        "空白"
-                    } else {
-                        &production_date
-                    },
-                    if product_code_str.is_empty() {
-                        "空白"
-                    } else {
-                        &product_code_str
-                    },
-                    if quantity_str.is_empty() {
-                        "空白"
-                    } else {
-                        &quantity_str
-                    },
-                    if yield_rate_str.is_empty() {
-                        "空白"
-                    } else {
-                        &yield_rate_str
-                    }
-                ));
+                diagnostics.report(cell_error(
+                    sheet_name,
+                    row_idx,
+                    "商品コード",
+                    format!("商品コード '{}' が重複しています", product_code_str),
+                ))?;
+                continue;
             }
 
-            let quantity: f64 = quantity_str.parse().map_err(|_| {
-                eyre!(
-                    "【入庫】生産シート {}行目: 生産数量が数値ではありません: '{}'",
-                    row_idx + 1,
-                    quantity_str
-                )
-            })?;
+            if let Some(opening_balance) =
+                diagnostics.collect(Self::parse_row(row, row_idx, &schema))?
+            {
+                opening_balances.push(opening_balance);
+            }
+        }
 
-            let yield_rate: f64 = yield_rate_str.parse().map_err(|_| {
-                eyre!(
-                    "【入庫】生産シート {}行目: 歩留率が数値ではありません: '{}'",
-                    row_idx + 1,
-                    yield_rate_str
-                )
-            })?;
+        Ok(Self { opening_balances })
+    }
 
-            let coagulant_cost: f64 = if coagulant_str.is_empty() {
-                0.0
-            } else {
-                coagulant_str.parse().map_err(|_| {
-                    eyre!(
-                        "【入庫】生産シート {}行目: 凝集剤が数値ではありません: '{}'",
-                        row_idx + 1,
-                        coagulant_str
-                    )
-                })?
+    /// 1行を読み取る（商品名が空の場合は商品コードを商品名とする）
+    fn parse_row(
+        row: &[Data],
+        row_idx: usize,
+        schema: &OpeningBalanceSheetSchema,
+    ) -> std::result::Result<OpeningBalance, Diagnostic> {
+        let sheet_name = schema.sheet_name();
+        let product_code_str = get_cell_string(row, schema.product_code().value());
+        let product_name = schema
+            .product_name()
+            .map(|col| get_cell_string(row, col.value()))
+            .unwrap_or_default();
+        let amount_str = schema
+            .amount()
+            .map(|col| get_cell_string(row, col.value()))
+            .unwrap_or_default();
+
+        let quantity = Quantity::new(parse_number(
+            &get_cell_string(row, schema.quantity().value()),
+            sheet_name,
+            row_idx,
+            "期首数量",
+        )?)
+        .map_err(|e| cell_error(sheet_name, row_idx, "期首数量", e))?;
+
+        let amount = if amount_str.is_empty() {
+            None
+        } else {
+            let value = parse_number(&amount_str, sheet_name, row_idx, "期首金額")?;
+            Some(Amount::new(value).map_err(|e| cell_error(sheet_name, row_idx, "期首金額", e))?)
+        };
+
+        let product_name = if product_name.is_empty() {
+            product_code_str.clone()
+        } else {
+            product_name
+        };
+
+        let product_code = ProductCode::new(product_code_str)
+            .map_err(|e| cell_error(sheet_name, row_idx, "商品コード", e))?;
+
+        Ok(OpeningBalance::new(
+            product_code,
+            product_name,
+            quantity,
+            amount,
+        ))
+    }
+}
+
+impl OpeningBalanceRepository for ExcelOpeningBalanceRepository {
+    fn find_all(&self) -> Result<Vec<OpeningBalance>> {
+        Ok(self.opening_balances.clone())
+    }
+}
+
+/// Excel単位換算マスタリポジトリ
+pub struct ExcelUnitConversionRepository {
+    conversions: Vec<UnitConversion>,
+}
+
+impl ExcelUnitConversionRepository {
+    /// 単位換算マスタシートがない場合は標準の換算（kg・t）のみを使う
+    pub fn new(
+        workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>,
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let range = read_optional_sheet(
+            workbook,
+            &mapping.sheet_name(UnitConversionSheetSchema::SHEET),
+        );
+        let rows: Vec<_> = range.rows().collect();
+        Self::from_rows(&rows, mapping, diagnostics)
+    }
+
+    /// 見出し行から始まる行を読み取る（CSVリポジトリと共通、行がない場合は標準の換算のみ）
+    pub fn from_rows(
+        rows: &[&[Data]],
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        if rows.is_empty() {
+            return Ok(Self {
+                conversions: Vec::new(),
+            });
+        }
+
+        let schema = UnitConversionSheetSchema::from_headers(&header_strings(rows[0]), mapping)?;
+
+        let mut conversions: Vec<UnitConversion> = Vec::new();
+
+        for (row_idx, row) in rows.iter().enumerate().skip(1) {
+            if get_cell_string(row, schema.unit().value()).is_empty()
+                || get_cell_string(row, schema.kg_per_unit().value()).is_empty()
+            {
+                continue;
+            }
+
+            let Some(conversion) = diagnostics.collect(Self::parse_row(row, row_idx, &schema))?
+            else {
+                continue;
             };
 
-            let clay_treatment_cost: f64 = if clay_treatment_str.is_empty() {
-                0.0
-            } else {
-                clay_treatment_str.parse().map_err(|_| {
-                    eyre!(
-                        "【入庫】生産シート {}行目: 粘土処理が数値ではありません: '{}'",
-                        row_idx + 1,
-                        clay_treatment_str
-                    )
-                })?
+            if conversions
+                .iter()
+                .any(|c| c.product_code == conversion.product_code && c.unit == conversion.unit)
+            {
+                diagnostics.report(row_error(
+                    schema.sheet_name(),
+                    row_idx,
+                    format!(
+                        "商品コード '{}' の単位 '{}' の換算が重複しています",
+                        conversion
+                            .product_code
+                            .as_ref()
+                            .map(|c| c.value())
+                            .unwrap_or("（共通）"),
+                        conversion.unit.as_str()
+                    ),
+                ))?;
+                continue;
+            }
+            conversions.push(conversion);
+        }
+
+        Ok(Self { conversions })
+    }
+
+    /// 1行を読み取る（商品コードが空欄の場合は全材料に共通の換算）
+    fn parse_row(
+        row: &[Data],
+        row_idx: usize,
+        schema: &UnitConversionSheetSchema,
+    ) -> std::result::Result<UnitConversion, Diagnostic> {
+        let sheet_name = schema.sheet_name();
+        let product_code_str = schema
+            .product_code()
+            .map(|col| get_cell_string(row, col.value()))
+            .unwrap_or_default();
+        let product_code = if product_code_str.is_empty() {
+            None
+        } else {
+            Some(
+                ProductCode::new(product_code_str)
+                    .map_err(|e| cell_error(sheet_name, row_idx, "商品コード", e))?,
+            )
+        };
+
+        let unit = Unit::new(get_cell_string(row, schema.unit().value()))
+            .map_err(|e| cell_error(sheet_name, row_idx, "単位", e))?;
+
+        let kg_per_unit = Quantity::new(parse_number(
+            &get_cell_string(row, schema.kg_per_unit().value()),
+            sheet_name,
+            row_idx,
+            "換算kg",
+        )?)
+        .map_err(|e| cell_error(sheet_name, row_idx, "換算kg", e))?;
+
+        UnitConversion::new(product_code, unit, kg_per_unit)
+            .map_err(|e| row_error(sheet_name, row_idx, e))
+    }
+}
+
+impl UnitConversionRepository for ExcelUnitConversionRepository {
+    fn find_all(&self) -> Result<Vec<UnitConversion>> {
+        Ok(self.conversions.clone())
+    }
+}
+
+/// Excel運賃表リポジトリ
+pub struct ExcelFreightRateTableRepository {
+    tables: Vec<FreightRateTable>,
+}
+
+impl ExcelFreightRateTableRepository {
+    /// 運賃表シート（重量帯・最低運賃）と運賃地区割増シートを読み取る
+    ///
+    /// シートがない場合は運賃表なし（運賃マスタの Kg単価のみ）とする。
+    pub fn new(
+        workbook: &mut Xlsx<std::io::BufReader<std::fs::File>>,
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let rates =
+            read_optional_sheet(workbook, &mapping.sheet_name(FreightRateSheetSchema::SHEET));
+        let surcharges = read_optional_sheet(
+            workbook,
+            &mapping.sheet_name(ZoneSurchargeSheetSchema::SHEET),
+        );
+        Self::from_rows(
+            &rates.rows().collect::<Vec<_>>(),
+            &surcharges.rows().collect::<Vec<_>>(),
+            mapping,
+            diagnostics,
+        )
+    }
+
+    /// 運賃表・運賃地区割増それぞれの見出し行から始まる行を読み取る（CSVリポジトリと共通）
+    pub fn from_rows(
+        rate_rows: &[&[Data]],
+        surcharge_rows: &[&[Data]],
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self> {
+        let mut tables: Vec<FreightRateTable> = Vec::new();
+        Self::read_weight_breaks(rate_rows, mapping, diagnostics, &mut tables)?;
+        Self::read_zone_surcharges(surcharge_rows, mapping, diagnostics, &mut tables)?;
+        Ok(Self { tables })
+    }
+
+    fn table_mut(
+        tables: &mut Vec<FreightRateTable>,
+        pattern_name: PatternName,
+    ) -> &mut FreightRateTable {
+        let position = match tables.iter().position(|t| t.pattern_name == pattern_name) {
+            Some(position) => position,
+            None => {
+                tables.push(FreightRateTable::new(pattern_name));
+                tables.len() - 1
+            }
+        };
+        &mut tables[position]
+    }
+
+    /// 運賃表シート（1行が1つの重量帯、最低運賃はパターンのいずれかの行に入力）
+    fn read_weight_breaks(
+        rows: &[&[Data]],
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+        tables: &mut Vec<FreightRateTable>,
+    ) -> Result<()> {
+        let Some(header_row) = rows.first() else {
+            return Ok(());
+        };
+
+        let schema = FreightRateSheetSchema::from_headers(&header_strings(header_row), mapping)?;
+        let sheet_name = schema.sheet_name();
+
+        for (row_idx, row) in rows.iter().enumerate().skip(1) {
+            if get_cell_string(row, schema.pattern_name().value()).is_empty() {
+                continue;
+            }
+
+            let Some(parsed) =
+                diagnostics.collect(Self::parse_weight_break(row, row_idx, &schema))?
+            else {
+                continue;
             };
+            let table = Self::table_mut(tables, parsed.pattern_name);
+
+            if let Some((min_quantity, kg_unit_price)) = parsed.weight_break {
+                if table.has_weight_break(&min_quantity) {
+                    diagnostics.report(row_error(
+                        sheet_name,
+                        row_idx,
+                        format!(
+                            "パターン名 '{}' の下限重量 {}kg の重量帯が重複しています",
+                            table.pattern_name.value(),
+                            min_quantity.value()
+                        ),
+                    ))?;
+                    continue;
+                }
+                table.add_weight_break(min_quantity, kg_unit_price);
+            }
 
-            productions.push(Production::new(
-                ProductCode::new(product_code_str)?,
-                Quantity::new(quantity)?,
-                YieldRate::new(yield_rate)?,
-                Amount::new(coagulant_cost)?,
-                Amount::new(clay_treatment_cost)?,
-            ));
+            if let Some(minimum_charge) = parsed.minimum_charge {
+                if let Some(existing) = table.minimum_charge()
+                    && existing != minimum_charge
+                {
+                    diagnostics.report(row_error(
+                        sheet_name,
+                        row_idx,
+                        format!(
+                            "パターン名 '{}' に異なる最低運賃が入力されています: {} と {}",
+                            table.pattern_name.value(),
+                            existing.value(),
+                            minimum_charge.value()
+                        ),
+                    ))?;
+                    continue;
+                }
+                table.set_minimum_charge(minimum_charge);
+            }
         }
+        Ok(())
+    }
+
+    /// 1行を読み取る（下限重量・Kg単価が空欄の行は最低運賃のみの行）
+    fn parse_weight_break(
+        row: &[Data],
+        row_idx: usize,
+        schema: &FreightRateSheetSchema,
+    ) -> std::result::Result<RateTableRow, Diagnostic> {
+        let sheet_name = schema.sheet_name();
+        let pattern_name = PatternName::new(get_cell_string(row, schema.pattern_name().value()))
+            .map_err(|e| cell_error(sheet_name, row_idx, "パターン名", e))?;
+
+        let min_quantity_str = get_cell_string(row, schema.min_quantity().value());
+        let kg_unit_price_str = get_cell_string(row, schema.kg_unit_price().value());
+        let weight_break = if min_quantity_str.is_empty() && kg_unit_price_str.is_empty() {
+            None
+        } else {
+            let min_quantity = Quantity::new(parse_number(
+                &min_quantity_str,
+                sheet_name,
+                row_idx,
+                "下限重量",
+            )?)
+            .map_err(|e| cell_error(sheet_name, row_idx, "下限重量", e))?;
+            let kg_unit_price = Amount::new(parse_number(
+                &kg_unit_price_str,
+                sheet_name,
+                row_idx,
+                "Kg単価",
+            )?)
+            .map_err(|e| cell_error(sheet_name, row_idx, "Kg単価", e))?;
+            Some((min_quantity, kg_unit_price))
+        };
+
+        let minimum_charge_str = schema
+            .minimum_charge()
+            .map(|col| get_cell_string(row, col.value()))
+            .unwrap_or_default();
+        let minimum_charge = if minimum_charge_str.is_empty() {
+            None
+        } else {
+            Some(
+                Amount::new(parse_number(
+                    &minimum_charge_str,
+                    sheet_name,
+                    row_idx,
+                    "最低運賃",
+                )?)
+                .map_err(|e| cell_error(sheet_name, row_idx, "最低運賃", e))?,
+            )
+        };
+
+        Ok(RateTableRow {
+            pattern_name,
+            weight_break,
+            minimum_charge,
+        })
+    }
+
+    /// 運賃地区割増シート（パターン名・地区ごとの割増Kg単価）
+    fn read_zone_surcharges(
+        rows: &[&[Data]],
+        mapping: &SchemaMapping,
+        diagnostics: &mut Diagnostics,
+        tables: &mut Vec<FreightRateTable>,
+    ) -> Result<()> {
+        let Some(header_row) = rows.first() else {
+            return Ok(());
+        };
+
+        let schema = ZoneSurchargeSheetSchema::from_headers(&header_strings(header_row), mapping)?;
+        let sheet_name = schema.sheet_name();
+        let col_pattern_name = schema.pattern_name().value();
+        let col_zone = schema.zone().value();
+        let col_surcharge = schema.surcharge().value();
 
-        Ok(Self { productions })
+        for (row_idx, row) in rows.iter().enumerate().skip(1) {
+            let zone = get_cell_string(row, col_zone);
+            if get_cell_string(row, col_pattern_name).is_empty() || zone.is_empty() {
+                continue;
+            }
+
+            let parsed = PatternName::new(get_cell_string(row, col_pattern_name))
+                .map_err(|e| cell_error(sheet_name, row_idx, "パターン名", e))
+                .and_then(|pattern_name| {
+                    let surcharge = Amount::new(parse_number(
+                        &get_cell_string(row, col_surcharge),
+                        sheet_name,
+                        row_idx,
+                        "割増Kg単価",
+                    )?)
+                    .map_err(|e| cell_error(sheet_name, row_idx, "割増Kg単価", e))?;
+                    Ok((pattern_name, surcharge))
+                });
+            let Some((pattern_name, surcharge)) = diagnostics.collect(parsed)? else {
+                continue;
+            };
+
+            let table = Self::table_mut(tables, pattern_name);
+            if table.has_zone(&zone) {
+                diagnostics.report(row_error(
+                    sheet_name,
+                    row_idx,
+                    format!(
+                        "パターン名 '{}' の地区 '{}' の割増が重複しています",
+                        table.pattern_name.value(),
+                        zone
+                    ),
+                ))?;
+                continue;
+            }
+            table.add_zone_surcharge(zone, surcharge);
+        }
+        Ok(())
     }
 }
 
-impl ProductionRepository for ExcelProductionRepository {
-    fn find_all(&self) -> Result<Vec<Production>> {
-        Ok(self.productions.clone())
+/// 運賃表シートの1行（重量帯・最低運賃はいずれも任意）
+struct RateTableRow {
+    pattern_name: PatternName,
+    weight_break: Option<(Quantity, Amount)>,
+    minimum_charge: Option<Amount>,
+}
+
+impl FreightRateTableRepository for ExcelFreightRateTableRepository {
+    fn find_all(&self) -> Result<Vec<FreightRateTable>> {
+        Ok(self.tables.clone())
     }
 }
 
@@ -602,12 +1584,26 @@ pub struct ExcelRepositoryFactory {
     pub purchase_repo: ExcelPurchaseRepository,
     pub production_repo: ExcelProductionRepository,
     pub transaction_repo: ExcelInventoryTransactionRepository,
+    pub opening_balance_repo: ExcelOpeningBalanceRepository,
+    pub rate_table_repo: ExcelFreightRateTableRepository,
+    pub unit_conversion_repo: ExcelUnitConversionRepository,
+    pub diagnostics: Vec<Diagnostic>, // 蓄積モードで読み飛ばした行の問題
 }
 
 impl ExcelRepositoryFactory {
     /// Excelファイルからすべてのリポジトリを初期化
-    pub fn from_file(file_path: &str) -> Result<Self> {
-        use calamine::{Reader, Xlsx, open_workbook};
+    ///
+    /// 蓄積モードでは問題のある行を読み飛ばし、問題を `diagnostics` に記録する。
+    /// 便の運賃総額は `freight_allocation` の基準で仕入ロットに配賦する。
+    /// シート名・列名は `mapping` の対応表で読み替える。
+    pub fn from_file(
+        file_path: &str,
+        accumulate: bool,
+        freight_allocation: FreightAllocationMethod,
+        mapping: &SchemaMapping,
+    ) -> color_eyre::Result<Self> {
+        use calamine::open_workbook;
+        use color_eyre::eyre::eyre;
 
         println!("Excelファイルを読み取り中: {}", file_path);
         let mut workbook = open_workbook::<Xlsx<_>, _>(file_path).map_err(|e| {
@@ -632,12 +1628,45 @@ impl ExcelRepositoryFactory {
 
         // リポジトリを初期化
         println!("\nリポジトリを初期化中...");
-        let formula_repo = ExcelFormulaRepository::new(&mut workbook)?;
-        let freight_repo = ExcelFreightMasterRepository::new(&mut workbook)?;
-        let purchase_repo = ExcelPurchaseRepository::new(&mut workbook)?;
-        let production_repo = ExcelProductionRepository::new(&mut workbook)?;
-        let transaction_repo = ExcelInventoryTransactionRepository::new(&mut workbook)?;
+        let mut diagnostics = Diagnostics::new(accumulate);
+        let formula_repo = ExcelFormulaRepository::new(&mut workbook, mapping, &mut diagnostics)?;
+        // 運賃マスタに運賃パターンの運賃表を紐付けるため、運賃表を先に読み取る
+        let rate_table_repo =
+            ExcelFreightRateTableRepository::new(&mut workbook, mapping, &mut diagnostics)?;
+        let freight_repo = ExcelFreightMasterRepository::new(
+            &mut workbook,
+            &rate_table_repo.find_all()?,
+            mapping,
+            &mut diagnostics,
+        )?;
+        // 仕入の数量・単価を kg に正規化するため、単位換算マスタを先に読み取る
+        let unit_conversion_repo =
+            ExcelUnitConversionRepository::new(&mut workbook, mapping, &mut diagnostics)?;
+        let conversions = unit_conversion_repo.find_all()?;
+        let purchase_repo = ExcelPurchaseRepository::new(
+            &mut workbook,
+            &conversions,
+            freight_allocation,
+            mapping,
+            &mut diagnostics,
+        )?;
+        let production_repo =
+            ExcelProductionRepository::new(&mut workbook, mapping, &mut diagnostics)?;
+        let transaction_repo = ExcelInventoryTransactionRepository::new(
+            &mut workbook,
+            &conversions,
+            mapping,
+            &mut diagnostics,
+        )?;
+        let opening_balance_repo =
+            ExcelOpeningBalanceRepository::new(&mut workbook, mapping, &mut diagnostics)?;
         println!("  ✓ リポジトリの初期化完了");
+        if !diagnostics.entries().is_empty() {
+            println!(
+                "  ⚠️  読み飛ばした行の問題: {} 件",
+                diagnostics.entries().len()
+            );
+        }
 
         Ok(Self {
             formula_repo,
@@ -645,6 +1674,10 @@ impl ExcelRepositoryFactory {
             purchase_repo,
             production_repo,
             transaction_repo,
+            opening_balance_repo,
+            rate_table_repo,
+            unit_conversion_repo,
+            diagnostics: diagnostics.entries().to_vec(),
         })
     }
 }
@@ -0,0 +1,1669 @@
+use crate::domain::diagnostics::Diagnostic;
+use crate::domain::entities::*;
+use crate::domain::error::{DomainError, MasterKind, Result};
+use crate::domain::repositories::*;
+use crate::domain::unit_conversion::UnitConversionService;
+use crate::domain::value_objects::*;
+use crate::infrastructure::excel_repositories::ExcelRepositoryFactory;
+use crate::usecase::dtos::{InventoryHistoryRecordDto, MaterialCostResultDto};
+use crate::usecase::ports::SaveRunResultsOutputPort;
+use rusqlite::types::FromSqlError;
+use rusqlite::{Connection, Row, params};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+
+/// テーブル定義（入力データは取り込みIDつきでマスタのキー・取引の月ごとに置き換え、実行結果は実行IDごとに蓄積）
+///
+/// 数値は REAL（f64）の丸めを避けるため10進数表記の TEXT で保存する。
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS imports (
+    import_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    imported_at TEXT NOT NULL,
+    source TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS import_diagnostics (
+    id INTEGER PRIMARY KEY,
+    import_id INTEGER NOT NULL REFERENCES imports (import_id),
+    sheet TEXT NOT NULL,
+    row_number INTEGER,
+    column_name TEXT,
+    message TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS formula_versions (
+    id INTEGER PRIMARY KEY,
+    import_id INTEGER NOT NULL REFERENCES imports (import_id),
+    product_code TEXT NOT NULL,
+    valid_from TEXT,
+    valid_to TEXT
+);
+CREATE TABLE IF NOT EXISTS formula_entries (
+    id INTEGER PRIMARY KEY,
+    version_id INTEGER NOT NULL REFERENCES formula_versions (id),
+    material_code TEXT NOT NULL,
+    consumption_ratio TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS freight_masters (
+    id INTEGER PRIMARY KEY,
+    import_id INTEGER NOT NULL REFERENCES imports (import_id),
+    freight_code TEXT NOT NULL,
+    pattern_name TEXT NOT NULL,
+    kg_unit_price TEXT NOT NULL,
+    valid_from TEXT NOT NULL,
+    valid_to TEXT,
+    zone TEXT
+);
+CREATE TABLE IF NOT EXISTS freight_rate_tables (
+    pattern_name TEXT PRIMARY KEY,
+    import_id INTEGER NOT NULL REFERENCES imports (import_id),
+    minimum_charge TEXT
+);
+CREATE TABLE IF NOT EXISTS freight_weight_breaks (
+    id INTEGER PRIMARY KEY,
+    pattern_name TEXT NOT NULL REFERENCES freight_rate_tables (pattern_name),
+    min_quantity TEXT NOT NULL,
+    kg_unit_price TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS freight_zone_surcharges (
+    id INTEGER PRIMARY KEY,
+    pattern_name TEXT NOT NULL REFERENCES freight_rate_tables (pattern_name),
+    zone TEXT NOT NULL,
+    kg_surcharge TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS unit_conversions (
+    id INTEGER PRIMARY KEY,
+    import_id INTEGER NOT NULL REFERENCES imports (import_id),
+    product_code TEXT,
+    unit TEXT NOT NULL,
+    kg_per_unit TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS purchases (
+    id INTEGER PRIMARY KEY,
+    import_id INTEGER NOT NULL REFERENCES imports (import_id),
+    period TEXT NOT NULL,
+    purchase_date TEXT NOT NULL,
+    product_code TEXT NOT NULL,
+    product_name TEXT NOT NULL,
+    unit_price TEXT NOT NULL,
+    quantity TEXT NOT NULL,
+    unit TEXT NOT NULL,
+    freight_code TEXT,
+    freight_kg_price TEXT,
+    lot_freight TEXT,
+    shipment_id TEXT
+);
+CREATE TABLE IF NOT EXISTS productions (
+    id INTEGER PRIMARY KEY,
+    import_id INTEGER NOT NULL REFERENCES imports (import_id),
+    period TEXT NOT NULL,
+    production_date TEXT NOT NULL,
+    product_code TEXT NOT NULL,
+    quantity TEXT NOT NULL,
+    yield_rate TEXT NOT NULL,
+    coagulant_cost TEXT NOT NULL,
+    clay_treatment_cost TEXT NOT NULL,
+    plant TEXT
+);
+CREATE TABLE IF NOT EXISTS inventory_transactions (
+    id INTEGER PRIMARY KEY,
+    import_id INTEGER NOT NULL REFERENCES imports (import_id),
+    period TEXT NOT NULL,
+    date TEXT NOT NULL,
+    inventory_type TEXT NOT NULL,
+    product_code TEXT NOT NULL,
+    product_name TEXT NOT NULL,
+    quantity TEXT NOT NULL,
+    unit TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS opening_balances (
+    id INTEGER PRIMARY KEY,
+    import_id INTEGER NOT NULL REFERENCES imports (import_id),
+    product_code TEXT NOT NULL,
+    product_name TEXT NOT NULL,
+    quantity TEXT NOT NULL,
+    amount TEXT
+);
+CREATE TABLE IF NOT EXISTS runs (
+    run_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    executed_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS run_cost_results (
+    run_id INTEGER NOT NULL REFERENCES runs (run_id),
+    row_number INTEGER NOT NULL,
+    production_date TEXT NOT NULL,
+    product_code TEXT NOT NULL,
+    raw_material_cost TEXT NOT NULL,
+    yield_cost TEXT NOT NULL,
+    yield_rate TEXT NOT NULL,
+    yield_formula TEXT NOT NULL,
+    coagulant_cost TEXT NOT NULL,
+    clay_treatment_cost TEXT NOT NULL,
+    freight_cost TEXT NOT NULL,
+    total_material_cost TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS run_inventory_history (
+    run_id INTEGER NOT NULL REFERENCES runs (run_id),
+    seq INTEGER NOT NULL,
+    date TEXT NOT NULL,
+    inventory_type TEXT NOT NULL,
+    product_code TEXT NOT NULL,
+    product_name TEXT NOT NULL,
+    base_quantity TEXT NOT NULL,
+    change_quantity TEXT NOT NULL,
+    balance TEXT NOT NULL
+);
+";
+
+/// 取り込みIDを持つ入力データのテーブル
+const IMPORTED_TABLES: [&str; 8] = [
+    "formula_versions",
+    "freight_masters",
+    "freight_rate_tables",
+    "unit_conversions",
+    "purchases",
+    "productions",
+    "inventory_transactions",
+    "opening_balances",
+];
+
+// 共通ヘルパー関数
+/// ドメインの検証エラーを列の値の変換エラーとして扱う
+fn invalid(error: DomainError) -> rusqlite::Error {
+    FromSqlError::Other(Box::new(error)).into()
+}
+
+/// テーブルの読み取りエラー
+fn unreadable(table: &str) -> impl Fn(rusqlite::Error) -> DomainError + '_ {
+    move |e| DomainError::TableUnreadable {
+        table: table.to_string(),
+        reason: e.to_string(),
+    }
+}
+
+/// クエリの全行を変換する
+fn query<T>(
+    conn: &Connection,
+    sql: &str,
+    map: impl FnMut(&Row<'_>) -> rusqlite::Result<T>,
+) -> rusqlite::Result<Vec<T>> {
+    let mut statement = conn.prepare(sql)?;
+    let rows = statement.query_map([], map)?;
+    rows.collect()
+}
+
+/// 数値を TEXT の列に保存する10進数表記
+fn text(value: Decimal) -> String {
+    value.normalize().to_string()
+}
+
+/// f64 の値（出力用DTO・直接指定の運賃Kg単価）を10進数表記にする
+fn f64_text(value: f64) -> String {
+    Decimal::from_f64(value)
+        .map(text)
+        .unwrap_or_else(|| value.to_string())
+}
+
+fn parse_decimal(value: String) -> rusqlite::Result<Decimal> {
+    Decimal::from_str(&value).map_err(|e| FromSqlError::Other(Box::new(e)).into())
+}
+
+/// 数値の列（TEXT）を10進数として読み取る
+fn decimal(row: &Row<'_>, column: &str) -> rusqlite::Result<Decimal> {
+    parse_decimal(row.get(column)?)
+}
+
+fn optional_decimal(row: &Row<'_>, column: &str) -> rusqlite::Result<Option<Decimal>> {
+    row.get::<_, Option<String>>(column)?
+        .map(parse_decimal)
+        .transpose()
+}
+
+fn optional_date(value: Option<String>) -> rusqlite::Result<Option<TransactionDate>> {
+    value.map(TransactionDate::new).transpose().map_err(invalid)
+}
+
+/// データベースに取り込む入力データ
+pub struct InputData {
+    pub formula_versions: Vec<FormulaVersion>,
+    pub freight_masters: Vec<FreightMaster>,
+    pub rate_tables: Vec<FreightRateTable>,
+    pub unit_conversions: Vec<UnitConversion>,
+    pub purchases: Vec<Purchase>,
+    pub productions: Vec<Production>,
+    pub transactions: Vec<InventoryTransaction>,
+    pub opening_balances: Vec<OpeningBalance>,
+}
+
+impl InputData {
+    /// Excelテンプレートから読み取った入力データ（仕入・入出庫は kg に正規化済み、便運賃は配賦済み）
+    pub fn from_excel(factory: &ExcelRepositoryFactory) -> Result<Self> {
+        Ok(Self {
+            formula_versions: factory.formula_repo.find_all_versions()?,
+            freight_masters: factory.freight_repo.find_all()?,
+            rate_tables: factory.rate_table_repo.find_all()?,
+            unit_conversions: factory.unit_conversion_repo.find_all()?,
+            purchases: factory.purchase_repo.find_all()?,
+            productions: factory.production_repo.find_all()?,
+            transactions: factory.transaction_repo.find_all_transactions()?,
+            opening_balances: factory.opening_balance_repo.find_all()?,
+        })
+    }
+}
+
+/// SQLiteデータベース（入力データと実行ごとの計算結果）
+pub struct SqliteDatabase {
+    conn: Connection,
+    path: String,
+}
+
+impl SqliteDatabase {
+    /// データベースを開く（ファイルがない場合は作成し、テーブルを用意する）
+    pub fn open(path: &str) -> Result<Self> {
+        let input_unreadable = |reason: String| DomainError::InputUnreadable {
+            path: path.to_string(),
+            reason,
+        };
+        let conn = Connection::open(path).map_err(|e| input_unreadable(e.to_string()))?;
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| input_unreadable(format!("テーブルを作成できませんでした: {}", e)))?;
+        Ok(Self {
+            conn,
+            path: path.to_string(),
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// 入力データを取り込み、取り込みIDを返す（実行結果の履歴は残す）
+    ///
+    /// マスタは同じキー（製造商品コード・運賃コード・運賃パターン・単位換算の商品と単位・期首在庫の商品）の
+    /// 既存データを、仕入・生産・入出庫は取り込むデータに含まれる月の既存データを置き換える。
+    /// それ以外のキー・月のデータは残るため、月ごとのファイルを順に取り込んで追加できる。
+    /// 蓄積モードで読み飛ばした行の問題は取り込みIDに紐付けて保存する。
+    pub fn import_input_data(
+        &mut self,
+        data: &InputData,
+        source: &str,
+        diagnostics: &[Diagnostic],
+    ) -> color_eyre::Result<i64> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO imports (imported_at, source) VALUES (?1, ?2)",
+            params![
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                source
+            ],
+        )?;
+        let import_id = tx.last_insert_rowid();
+
+        upsert_formula_versions(&tx, import_id, &data.formula_versions)?;
+        upsert_freight_masters(&tx, import_id, &data.freight_masters)?;
+        upsert_rate_tables(&tx, import_id, &data.rate_tables)?;
+        upsert_unit_conversions(&tx, import_id, &data.unit_conversions)?;
+        upsert_opening_balances(&tx, import_id, &data.opening_balances)?;
+        replace_purchases(&tx, import_id, &data.purchases)?;
+        replace_productions(&tx, import_id, &data.productions)?;
+        replace_transactions(&tx, import_id, &data.transactions)?;
+        insert_import_diagnostics(&tx, import_id, diagnostics)?;
+        tx.commit()?;
+        Ok(import_id)
+    }
+
+    /// 現在の入力データを取り込んだ際に読み飛ばした行の問題（取り込み順）
+    ///
+    /// 後の取り込みですべてのデータが置き換えられた取り込みの問題は含めない。
+    pub fn import_diagnostics(&self) -> Result<Vec<Diagnostic>> {
+        let current_imports = IMPORTED_TABLES
+            .iter()
+            .map(|table| format!("SELECT import_id FROM {}", table))
+            .collect::<Vec<_>>()
+            .join(" UNION ");
+        query(
+            &self.conn,
+            &format!(
+                "SELECT sheet, row_number, column_name, message FROM import_diagnostics
+                 WHERE import_id IN ({}) ORDER BY import_id, id",
+                current_imports
+            ),
+            |row| {
+                Ok(Diagnostic {
+                    sheet: row.get("sheet")?,
+                    row: row.get("row_number")?,
+                    column: row.get("column_name")?,
+                    message: row.get("message")?,
+                })
+            },
+        )
+        .map_err(unreadable("取り込み時の問題"))
+    }
+}
+
+/// 取り込むデータに含まれる月（YYYY-MM）
+fn periods<'a>(dates: impl Iterator<Item = &'a TransactionDate>) -> BTreeSet<String> {
+    dates.map(|date| date.month_label()).collect()
+}
+
+/// 取り込むデータに含まれる月の既存データを削除する
+fn delete_periods(
+    conn: &Connection,
+    table: &str,
+    periods: &BTreeSet<String>,
+) -> rusqlite::Result<()> {
+    let mut delete = conn.prepare(&format!("DELETE FROM {} WHERE period = ?1", table))?;
+    for period in periods {
+        delete.execute(params![period])?;
+    }
+    Ok(())
+}
+
+/// 配合マスタを製造商品コードごとに置き換える（版はすべて入れ替え）
+fn upsert_formula_versions(
+    conn: &Connection,
+    import_id: i64,
+    versions: &[FormulaVersion],
+) -> rusqlite::Result<()> {
+    let product_codes: BTreeSet<&str> = versions.iter().map(|v| v.product_code.value()).collect();
+    for product_code in product_codes {
+        conn.execute(
+            "DELETE FROM formula_entries WHERE version_id IN
+             (SELECT id FROM formula_versions WHERE product_code = ?1)",
+            params![product_code],
+        )?;
+        conn.execute(
+            "DELETE FROM formula_versions WHERE product_code = ?1",
+            params![product_code],
+        )?;
+    }
+
+    let mut insert_version = conn.prepare(
+        "INSERT INTO formula_versions (import_id, product_code, valid_from, valid_to)
+         VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    let mut insert_entry = conn.prepare(
+        "INSERT INTO formula_entries (version_id, material_code, consumption_ratio)
+         VALUES (?1, ?2, ?3)",
+    )?;
+    for version in versions {
+        let version_id = insert_version.insert(params![
+            import_id,
+            version.product_code.value(),
+            version.valid_from.as_ref().map(|d| d.value()),
+            version.valid_to.as_ref().map(|d| d.value()),
+        ])?;
+        // 消費比率の異なる行も保存し、読み込み時の検証で報告する
+        for entry in version.entries.iter().chain(&version.conflicts) {
+            insert_entry.execute(params![
+                version_id,
+                entry.material_code.value(),
+                text(entry.consumption_ratio.decimal()),
+            ])?;
+        }
+    }
+    Ok(())
+}
+
+/// 運賃マスタを運賃コードごとに置き換える（有効期間の行はすべて入れ替え）
+fn upsert_freight_masters(
+    conn: &Connection,
+    import_id: i64,
+    masters: &[FreightMaster],
+) -> rusqlite::Result<()> {
+    let freight_codes: BTreeSet<&str> = masters.iter().map(|m| m.freight_code.as_str()).collect();
+    for freight_code in freight_codes {
+        conn.execute(
+            "DELETE FROM freight_masters WHERE freight_code = ?1",
+            params![freight_code],
+        )?;
+    }
+
+    let mut insert = conn.prepare(
+        "INSERT INTO freight_masters
+         (import_id, freight_code, pattern_name, kg_unit_price, valid_from, valid_to, zone)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?;
+    for master in masters {
+        insert.execute(params![
+            import_id,
+            master.freight_code,
+            master.pattern_name.value(),
+            text(master.kg_unit_price.decimal()),
+            master.valid_from.value(),
+            master.valid_to.as_ref().map(|d| d.value()),
+            master.zone,
+        ])?;
+    }
+    Ok(())
+}
+
+/// 運賃表を運賃パターンごとに置き換える（重量帯・地区割増も入れ替え）
+fn upsert_rate_tables(
+    conn: &Connection,
+    import_id: i64,
+    tables: &[FreightRateTable],
+) -> rusqlite::Result<()> {
+    for table in tables {
+        let pattern_name = table.pattern_name.value();
+        for child in ["freight_weight_breaks", "freight_zone_surcharges"] {
+            conn.execute(
+                &format!("DELETE FROM {} WHERE pattern_name = ?1", child),
+                params![pattern_name],
+            )?;
+        }
+        conn.execute(
+            "DELETE FROM freight_rate_tables WHERE pattern_name = ?1",
+            params![pattern_name],
+        )?;
+    }
+
+    let mut insert_table = conn.prepare(
+        "INSERT INTO freight_rate_tables (pattern_name, import_id, minimum_charge)
+         VALUES (?1, ?2, ?3)",
+    )?;
+    let mut insert_break = conn.prepare(
+        "INSERT INTO freight_weight_breaks (pattern_name, min_quantity, kg_unit_price)
+         VALUES (?1, ?2, ?3)",
+    )?;
+    let mut insert_surcharge = conn.prepare(
+        "INSERT INTO freight_zone_surcharges (pattern_name, zone, kg_surcharge)
+         VALUES (?1, ?2, ?3)",
+    )?;
+    for table in tables {
+        let pattern_name = table.pattern_name.value();
+        insert_table.execute(params![
+            pattern_name,
+            import_id,
+            table.minimum_charge().map(|m| text(m.decimal())),
+        ])?;
+        for (min_quantity, kg_unit_price) in table.weight_breaks() {
+            insert_break.execute(params![
+                pattern_name,
+                text(min_quantity.decimal()),
+                text(kg_unit_price.decimal()),
+            ])?;
+        }
+        for (zone, kg_surcharge) in table.zone_surcharges() {
+            insert_surcharge.execute(params![pattern_name, zone, text(kg_surcharge.decimal())])?;
+        }
+    }
+    Ok(())
+}
+
+/// 単位換算マスタを商品コード（共通は NULL）と単位の組ごとに置き換える
+fn upsert_unit_conversions(
+    conn: &Connection,
+    import_id: i64,
+    conversions: &[UnitConversion],
+) -> rusqlite::Result<()> {
+    let mut delete =
+        conn.prepare("DELETE FROM unit_conversions WHERE product_code IS ?1 AND unit = ?2")?;
+    let mut insert = conn.prepare(
+        "INSERT INTO unit_conversions (import_id, product_code, unit, kg_per_unit)
+         VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for conversion in conversions {
+        let product_code = conversion.product_code.as_ref().map(|c| c.value());
+        delete.execute(params![product_code, conversion.unit.as_str()])?;
+        insert.execute(params![
+            import_id,
+            product_code,
+            conversion.unit.as_str(),
+            text(conversion.kg_per_unit.decimal()),
+        ])?;
+    }
+    Ok(())
+}
+
+/// 期首在庫を商品コードごとに置き換える
+fn upsert_opening_balances(
+    conn: &Connection,
+    import_id: i64,
+    balances: &[OpeningBalance],
+) -> rusqlite::Result<()> {
+    let mut delete = conn.prepare("DELETE FROM opening_balances WHERE product_code = ?1")?;
+    let mut insert = conn.prepare(
+        "INSERT INTO opening_balances (import_id, product_code, product_name, quantity, amount)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for balance in balances {
+        delete.execute(params![balance.product_code.value()])?;
+        insert.execute(params![
+            import_id,
+            balance.product_code.value(),
+            balance.product_name,
+            text(balance.quantity.decimal()),
+            balance.amount.map(|a| text(a.decimal())),
+        ])?;
+    }
+    Ok(())
+}
+
+/// 仕入を月ごとに置き換える
+fn replace_purchases(
+    conn: &Connection,
+    import_id: i64,
+    purchases: &[Purchase],
+) -> rusqlite::Result<()> {
+    delete_periods(
+        conn,
+        "purchases",
+        &periods(purchases.iter().map(|p| &p.purchase_date)),
+    )?;
+
+    let mut insert = conn.prepare(
+        "INSERT INTO purchases
+         (import_id, period, purchase_date, product_code, product_name, unit_price, quantity,
+          unit, freight_code, freight_kg_price, lot_freight, shipment_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+    )?;
+    for purchase in purchases {
+        // 運賃は運賃コード・直接指定のKg単価・ロットの運賃額のいずれか1列に保存
+        let lot_freight = match &purchase.freight_code {
+            FreightCode::LotFreight(amount) => Some(text(amount.decimal())),
+            _ => None,
+        };
+        insert.execute(params![
+            import_id,
+            purchase.purchase_date.month_label(),
+            purchase.purchase_date.value(),
+            purchase.product_code.value(),
+            purchase.product_name,
+            text(purchase.unit_price.decimal()),
+            text(purchase.quantity.decimal()),
+            purchase.unit().as_str(),
+            purchase.freight_code.as_code(),
+            purchase.freight_code.as_direct_price().map(f64_text),
+            lot_freight,
+            purchase.shipment_id,
+        ])?;
+    }
+    Ok(())
+}
+
+/// 生産を月ごとに置き換える
+fn replace_productions(
+    conn: &Connection,
+    import_id: i64,
+    productions: &[Production],
+) -> rusqlite::Result<()> {
+    delete_periods(
+        conn,
+        "productions",
+        &periods(productions.iter().map(|p| &p.production_date)),
+    )?;
+
+    let mut insert = conn.prepare(
+        "INSERT INTO productions
+         (import_id, period, production_date, product_code, quantity, yield_rate,
+          coagulant_cost, clay_treatment_cost, plant)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+    )?;
+    for production in productions {
+        insert.execute(params![
+            import_id,
+            production.production_date.month_label(),
+            production.production_date.value(),
+            production.product_code.value(),
+            text(production.quantity.decimal()),
+            text(production.yield_rate.decimal()),
+            text(production.coagulant_cost.decimal()),
+            text(production.clay_treatment_cost.decimal()),
+            production.plant,
+        ])?;
+    }
+    Ok(())
+}
+
+/// 入出庫を月ごとに置き換える
+fn replace_transactions(
+    conn: &Connection,
+    import_id: i64,
+    transactions: &[InventoryTransaction],
+) -> rusqlite::Result<()> {
+    delete_periods(
+        conn,
+        "inventory_transactions",
+        &periods(transactions.iter().map(|t| &t.date)),
+    )?;
+
+    let mut insert = conn.prepare(
+        "INSERT INTO inventory_transactions
+         (import_id, period, date, inventory_type, product_code, product_name, quantity, unit)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    for transaction in transactions {
+        insert.execute(params![
+            import_id,
+            transaction.date.month_label(),
+            transaction.date.value(),
+            transaction.inventory_type.as_str(),
+            transaction.product_code.value(),
+            transaction.product_name,
+            text(transaction.quantity.decimal()),
+            transaction.quantity.unit().as_str(),
+        ])?;
+    }
+    Ok(())
+}
+
+fn insert_import_diagnostics(
+    conn: &Connection,
+    import_id: i64,
+    diagnostics: &[Diagnostic],
+) -> rusqlite::Result<()> {
+    let mut insert = conn.prepare(
+        "INSERT INTO import_diagnostics (import_id, sheet, row_number, column_name, message)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for diagnostic in diagnostics {
+        insert.execute(params![
+            import_id,
+            diagnostic.sheet,
+            diagnostic.row,
+            diagnostic.column,
+            diagnostic.message,
+        ])?;
+    }
+    Ok(())
+}
+
+impl SaveRunResultsOutputPort for SqliteDatabase {
+    fn save_run(
+        &mut self,
+        results: &[MaterialCostResultDto],
+        history_records: &[InventoryHistoryRecordDto],
+    ) -> color_eyre::Result<i64> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs (executed_at) VALUES (?1)",
+            params![chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()],
+        )?;
+        let run_id = tx.last_insert_rowid();
+
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO run_cost_results
+                 (run_id, row_number, production_date, product_code, raw_material_cost,
+                  yield_cost, yield_rate, yield_formula, coagulant_cost, clay_treatment_cost,
+                  freight_cost, total_material_cost)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            )?;
+            for result in results {
+                insert.execute(params![
+                    run_id,
+                    result.row_number,
+                    result.production_date,
+                    result.product_code,
+                    f64_text(result.raw_material_cost),
+                    f64_text(result.yield_cost),
+                    f64_text(result.yield_rate),
+                    result.yield_formula,
+                    f64_text(result.coagulant_cost),
+                    f64_text(result.clay_treatment_cost),
+                    f64_text(result.freight_cost),
+                    f64_text(result.total_material_cost),
+                ])?;
+            }
+
+            let mut insert = tx.prepare(
+                "INSERT INTO run_inventory_history
+                 (run_id, seq, date, inventory_type, product_code, product_name,
+                  base_quantity, change_quantity, balance)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            for (seq, record) in history_records.iter().enumerate() {
+                insert.execute(params![
+                    run_id,
+                    seq + 1,
+                    record.date,
+                    record.inventory_type,
+                    record.product_code,
+                    record.product_name,
+                    f64_text(record.base_quantity),
+                    f64_text(record.change_quantity),
+                    f64_text(record.balance),
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(run_id)
+    }
+}
+
+/// SQLiteベースの配合マスタリポジトリ
+pub struct SqliteFormulaRepository {
+    // 製造商品コードごとの版（有効開始日の昇順）
+    data: HashMap<String, Vec<FormulaVersion>>,
+}
+
+impl SqliteFormulaRepository {
+    pub fn load(database: &SqliteDatabase) -> Result<Self> {
+        let mut versions: Vec<(i64, FormulaVersion)> = query(
+            &database.conn,
+            "SELECT id, product_code, valid_from, valid_to FROM formula_versions ORDER BY id",
+            |row| {
+                let version = FormulaVersion::new(
+                    ProductCode::new(row.get("product_code")?).map_err(invalid)?,
+                    optional_date(row.get("valid_from")?)?,
+                    optional_date(row.get("valid_to")?)?,
+                )
+                .map_err(invalid)?;
+                Ok((row.get("id")?, version))
+            },
+        )
+        .map_err(unreadable("配合マスタ"))?;
+
+        let entries = query(
+            &database.conn,
+            "SELECT version_id, material_code, consumption_ratio FROM formula_entries ORDER BY id",
+            |row| {
+                let entry = FormulaEntry::new(
+                    ProductCode::new(row.get("material_code")?).map_err(invalid)?,
+                    ConsumptionRatio::from_decimal(decimal(row, "consumption_ratio")?)
+                        .map_err(invalid)?,
+                );
+                Ok((row.get::<_, i64>("version_id")?, entry))
+            },
+        )
+        .map_err(unreadable("配合マスタ"))?;
+
+        for (version_id, entry) in entries {
+            if let Some((_, version)) = versions.iter_mut().find(|(id, _)| *id == version_id) {
+                version.add_entry(entry);
+            }
+        }
+
+        let mut data: HashMap<String, Vec<FormulaVersion>> = HashMap::new();
+        for (_, version) in versions {
+            data.entry(version.product_code.value().to_string())
+                .or_default()
+                .push(version);
+        }
+        for versions in data.values_mut() {
+            versions.sort_by(|a, b| a.valid_from.cmp(&b.valid_from));
+        }
+
+        Ok(Self { data })
+    }
+}
+
+impl FormulaRepository for SqliteFormulaRepository {
+    fn find_by_product_code_as_of(
+        &self,
+        product_code: &ProductCode,
+        date: &TransactionDate,
+    ) -> Result<Vec<FormulaEntry>> {
+        let versions =
+            self.data
+                .get(product_code.value())
+                .ok_or_else(|| DomainError::MasterNotFound {
+                    master: MasterKind::Formula,
+                    code: product_code.value().to_string(),
+                })?;
+
+        versions
+            .iter()
+            .find(|v| v.is_valid_on(date))
+            .map(|v| v.entries.clone())
+            .ok_or_else(|| DomainError::NoValidPeriod {
+                master: MasterKind::Formula,
+                code: product_code.value().to_string(),
+                date: date.value().to_string(),
+                periods: versions.iter().map(|v| v.period_label()).collect(),
+            })
+    }
+
+    fn has_formula_as_of(&self, product_code: &ProductCode, date: &TransactionDate) -> bool {
+        self.data
+            .get(product_code.value())
+            .is_some_and(|versions| versions.iter().any(|v| v.is_valid_on(date)))
+    }
+
+    fn find_all_versions(&self) -> Result<Vec<FormulaVersion>> {
+        let mut codes: Vec<&String> = self.data.keys().collect();
+        codes.sort();
+        Ok(codes
+            .into_iter()
+            .flat_map(|code| self.data[code].iter().cloned())
+            .collect())
+    }
+}
+
+/// SQLiteベースの運賃マスタリポジトリ
+pub struct SqliteFreightMasterRepository {
+    // 運賃コードごとの有効期間（有効開始日の昇順）
+    data: HashMap<String, Vec<FreightMaster>>,
+}
+
+impl SqliteFreightMasterRepository {
+    /// パターン名の一致する運賃表を各運賃マスタに紐付ける
+    pub fn load(database: &SqliteDatabase, rate_tables: &[FreightRateTable]) -> Result<Self> {
+        let masters = query(
+            &database.conn,
+            "SELECT freight_code, pattern_name, kg_unit_price, valid_from, valid_to, zone
+             FROM freight_masters ORDER BY id",
+            |row| {
+                let master = FreightMaster::new(
+                    row.get("freight_code")?,
+                    PatternName::new(row.get("pattern_name")?).map_err(invalid)?,
+                    Amount::from_decimal(decimal(row, "kg_unit_price")?).map_err(invalid)?,
+                    TransactionDate::new(row.get("valid_from")?).map_err(invalid)?,
+                    optional_date(row.get("valid_to")?)?,
+                )
+                .map_err(invalid)?;
+                Ok(match row.get::<_, Option<String>>("zone")? {
+                    Some(zone) => master.with_zone(zone),
+                    None => master,
+                })
+            },
+        )
+        .map_err(unreadable("運賃マスタ"))?;
+
+        let mut data: HashMap<String, Vec<FreightMaster>> = HashMap::new();
+        for mut master in masters {
+            if let Some(table) = rate_tables
+                .iter()
+                .find(|t| t.pattern_name == master.pattern_name)
+            {
+                master = master.with_rate_table(table.clone());
+            }
+            data.entry(master.freight_code.clone())
+                .or_default()
+                .push(master);
+        }
+        for masters in data.values_mut() {
+            masters.sort_by(|a, b| a.valid_from.cmp(&b.valid_from));
+        }
+
+        Ok(Self { data })
+    }
+}
+
+impl FreightMasterRepository for SqliteFreightMasterRepository {
+    fn find_by_code_as_of(
+        &self,
+        freight_code: &str,
+        date: &TransactionDate,
+    ) -> Result<FreightMaster> {
+        let masters = self
+            .data
+            .get(freight_code)
+            .ok_or_else(|| DomainError::MasterNotFound {
+                master: MasterKind::FreightMaster,
+                code: freight_code.to_string(),
+            })?;
+
+        masters
+            .iter()
+            .find(|m| m.is_valid_on(date))
+            .cloned()
+            .ok_or_else(|| DomainError::NoValidPeriod {
+                master: MasterKind::FreightMaster,
+                code: freight_code.to_string(),
+                date: date.value().to_string(),
+                periods: masters.iter().map(|m| m.period_label()).collect(),
+            })
+    }
+}
+
+/// SQLiteベースの仕入リポジトリ
+pub struct SqlitePurchaseRepository {
+    // 商品コードごとの仕入履歴（仕入日の昇順）
+    data: HashMap<String, Vec<Purchase>>,
+}
+
+impl SqlitePurchaseRepository {
+    /// 仕入の数量・単価は単位換算マスタで基準単位（kg）に正規化する
+    pub fn load(database: &SqliteDatabase, conversions: &[UnitConversion]) -> Result<Self> {
+        let purchases = query(
+            &database.conn,
+            "SELECT purchase_date, product_code, product_name, unit_price, quantity, unit,
+                    freight_code, freight_kg_price, lot_freight, shipment_id
+             FROM purchases ORDER BY period, id",
+            |row| {
+                let unit = Unit::new(row.get("unit")?).map_err(invalid)?;
+                let freight_code = match (
+                    row.get::<_, Option<String>>("freight_code")?,
+                    optional_decimal(row, "freight_kg_price")?,
+                    optional_decimal(row, "lot_freight")?,
+                ) {
+                    (_, _, Some(lot_freight)) => {
+                        FreightCode::LotFreight(Amount::from_decimal(lot_freight).map_err(invalid)?)
+                    }
+                    (Some(code), _, _) => FreightCode::Code(code),
+                    (None, kg_price, None) => {
+                        FreightCode::DirectPrice(kg_price.and_then(|p| p.to_f64()).unwrap_or(0.0))
+                    }
+                };
+                let purchase = Purchase::new(
+                    TransactionDate::new(row.get("purchase_date")?).map_err(invalid)?,
+                    ProductCode::new(row.get("product_code")?).map_err(invalid)?,
+                    row.get("product_name")?,
+                    Amount::from_decimal(decimal(row, "unit_price")?).map_err(invalid)?,
+                    Quantity::from_decimal_in_unit(decimal(row, "quantity")?, unit)
+                        .map_err(invalid)?,
+                    freight_code,
+                );
+                let purchase = match row.get::<_, Option<String>>("shipment_id")? {
+                    Some(shipment_id) => purchase.with_shipment(shipment_id),
+                    None => purchase,
+                };
+                UnitConversionService::normalize_purchase(conversions, purchase).map_err(invalid)
+            },
+        )
+        .map_err(unreadable("仕入"))?;
+
+        let mut data: HashMap<String, Vec<Purchase>> = HashMap::new();
+        for purchase in purchases {
+            data.entry(purchase.product_code.value().to_string())
+                .or_default()
+                .push(purchase);
+        }
+
+        // 同日の仕入は登録順を保ったまま仕入日順に並べる（安定ソート）
+        for purchases in data.values_mut() {
+            purchases.sort_by(|a, b| a.purchase_date.cmp(&b.purchase_date));
+        }
+
+        Ok(Self { data })
+    }
+}
+
+impl PurchaseRepository for SqlitePurchaseRepository {
+    fn find_price_as_of(
+        &self,
+        product_code: &ProductCode,
+        date: &TransactionDate,
+    ) -> Result<Purchase> {
+        let purchases = self
+            .data
+            .get(product_code.value())
+            .ok_or_else(|| purchase_not_found(product_code))?;
+
+        // 指定日以前の仕入のうち最も新しいもの（同日なら後に登録したもの）を採用
+        purchases
+            .iter()
+            .rev()
+            .find(|p| p.purchase_date <= *date)
+            .cloned()
+            .ok_or_else(|| DomainError::NoPurchaseBefore {
+                code: product_code.value().to_string(),
+                date: date.value().to_string(),
+            })
+    }
+
+    fn find_history(&self, product_code: &ProductCode) -> Result<Vec<Purchase>> {
+        self.data
+            .get(product_code.value())
+            .cloned()
+            .ok_or_else(|| purchase_not_found(product_code))
+    }
+
+    fn find_all(&self) -> Result<Vec<Purchase>> {
+        let mut codes: Vec<&String> = self.data.keys().collect();
+        codes.sort();
+        Ok(codes
+            .into_iter()
+            .flat_map(|code| self.data[code].iter().cloned())
+            .collect())
+    }
+}
+
+fn purchase_not_found(product_code: &ProductCode) -> DomainError {
+    DomainError::MasterNotFound {
+        master: MasterKind::Purchase,
+        code: product_code.value().to_string(),
+    }
+}
+
+/// SQLite入出庫トランザクションリポジトリ
+pub struct SqliteInventoryTransactionRepository {
+    transactions: Vec<InventoryTransaction>,
+}
+
+impl SqliteInventoryTransactionRepository {
+    /// 数量は単位換算マスタで基準単位（kg）に換算する
+    pub fn load(database: &SqliteDatabase, conversions: &[UnitConversion]) -> Result<Self> {
+        let transactions = query(
+            &database.conn,
+            "SELECT date, inventory_type, product_code, product_name, quantity, unit
+             FROM inventory_transactions ORDER BY period, id",
+            |row| {
+                let product_code = ProductCode::new(row.get("product_code")?).map_err(invalid)?;
+                let unit = Unit::new(row.get("unit")?).map_err(invalid)?;
+                let quantity = UnitConversionService::to_kilograms(
+                    conversions,
+                    &product_code,
+                    &Quantity::from_decimal_in_unit(decimal(row, "quantity")?, unit)
+                        .map_err(invalid)?,
+                )
+                .map_err(invalid)?;
+                Ok(InventoryTransaction::new(
+                    TransactionDate::new(row.get("date")?).map_err(invalid)?,
+                    InventoryType::new(row.get("inventory_type")?).map_err(invalid)?,
+                    product_code,
+                    row.get("product_name")?,
+                    quantity,
+                ))
+            },
+        )
+        .map_err(unreadable("入出庫"))?;
+
+        Ok(Self { transactions })
+    }
+}
+
+impl InventoryTransactionRepository for SqliteInventoryTransactionRepository {
+    fn find_all_transactions(&self) -> Result<Vec<InventoryTransaction>> {
+        Ok(self.transactions.clone())
+    }
+}
+
+/// SQLite生産リポジトリ
+pub struct SqliteProductionRepository {
+    productions: Vec<Production>,
+}
+
+impl SqliteProductionRepository {
+    pub fn load(database: &SqliteDatabase) -> Result<Self> {
+        let productions = query(
+            &database.conn,
+            "SELECT production_date, product_code, quantity, yield_rate, coagulant_cost,
+                    clay_treatment_cost, plant
+             FROM productions ORDER BY period, id",
+            |row| {
+                let mut production = Production::new(
+                    TransactionDate::new(row.get("production_date")?).map_err(invalid)?,
+                    ProductCode::new(row.get("product_code")?).map_err(invalid)?,
+                    Quantity::from_decimal(decimal(row, "quantity")?).map_err(invalid)?,
+                    YieldRate::from_decimal(decimal(row, "yield_rate")?).map_err(invalid)?,
+                    Amount::from_decimal(decimal(row, "coagulant_cost")?).map_err(invalid)?,
+                    Amount::from_decimal(decimal(row, "clay_treatment_cost")?).map_err(invalid)?,
+                );
+                if let Some(plant) = row.get::<_, Option<String>>("plant")? {
+                    production = production.with_plant(plant);
+                }
+                Ok(production)
+            },
+        )
+        .map_err(unreadable("生産"))?;
+
+        Ok(Self { productions })
+    }
+}
+
+impl ProductionRepository for SqliteProductionRepository {
+    fn find_all(&self) -> Result<Vec<Production>> {
+        Ok(self.productions.clone())
+    }
+}
+
+/// SQLite期首在庫リポジトリ
+pub struct SqliteOpeningBalanceRepository {
+    balances: Vec<OpeningBalance>,
+}
+
+impl SqliteOpeningBalanceRepository {
+    pub fn load(database: &SqliteDatabase) -> Result<Self> {
+        let balances = query(
+            &database.conn,
+            "SELECT product_code, product_name, quantity, amount
+             FROM opening_balances ORDER BY id",
+            |row| {
+                Ok(OpeningBalance::new(
+                    ProductCode::new(row.get("product_code")?).map_err(invalid)?,
+                    row.get("product_name")?,
+                    Quantity::from_decimal(decimal(row, "quantity")?).map_err(invalid)?,
+                    optional_decimal(row, "amount")?
+                        .map(Amount::from_decimal)
+                        .transpose()
+                        .map_err(invalid)?,
+                ))
+            },
+        )
+        .map_err(unreadable("期首在庫"))?;
+
+        Ok(Self { balances })
+    }
+}
+
+impl OpeningBalanceRepository for SqliteOpeningBalanceRepository {
+    fn find_all(&self) -> Result<Vec<OpeningBalance>> {
+        Ok(self.balances.clone())
+    }
+}
+
+/// SQLite単位換算マスタリポジトリ
+pub struct SqliteUnitConversionRepository {
+    conversions: Vec<UnitConversion>,
+}
+
+impl SqliteUnitConversionRepository {
+    pub fn load(database: &SqliteDatabase) -> Result<Self> {
+        let conversions = query(
+            &database.conn,
+            "SELECT product_code, unit, kg_per_unit FROM unit_conversions ORDER BY id",
+            |row| {
+                UnitConversion::new(
+                    row.get::<_, Option<String>>("product_code")?
+                        .map(ProductCode::new)
+                        .transpose()
+                        .map_err(invalid)?,
+                    Unit::new(row.get("unit")?).map_err(invalid)?,
+                    Quantity::from_decimal(decimal(row, "kg_per_unit")?).map_err(invalid)?,
+                )
+                .map_err(invalid)
+            },
+        )
+        .map_err(unreadable("単位換算マスタ"))?;
+
+        Ok(Self { conversions })
+    }
+}
+
+impl UnitConversionRepository for SqliteUnitConversionRepository {
+    fn find_all(&self) -> Result<Vec<UnitConversion>> {
+        Ok(self.conversions.clone())
+    }
+}
+
+/// SQLite運賃表リポジトリ
+pub struct SqliteFreightRateTableRepository {
+    tables: Vec<FreightRateTable>,
+}
+
+impl SqliteFreightRateTableRepository {
+    pub fn load(database: &SqliteDatabase) -> Result<Self> {
+        let mut tables = query(
+            &database.conn,
+            "SELECT pattern_name, minimum_charge FROM freight_rate_tables ORDER BY pattern_name",
+            |row| {
+                let mut table = FreightRateTable::new(
+                    PatternName::new(row.get("pattern_name")?).map_err(invalid)?,
+                );
+                if let Some(minimum_charge) = optional_decimal(row, "minimum_charge")? {
+                    table
+                        .set_minimum_charge(Amount::from_decimal(minimum_charge).map_err(invalid)?);
+                }
+                Ok(table)
+            },
+        )
+        .map_err(unreadable("運賃表"))?;
+
+        let weight_breaks = query(
+            &database.conn,
+            "SELECT pattern_name, min_quantity, kg_unit_price FROM freight_weight_breaks ORDER BY id",
+            |row| {
+                Ok((
+                    row.get::<_, String>("pattern_name")?,
+                    Quantity::from_decimal(decimal(row, "min_quantity")?).map_err(invalid)?,
+                    Amount::from_decimal(decimal(row, "kg_unit_price")?).map_err(invalid)?,
+                ))
+            },
+        )
+        .map_err(unreadable("運賃表"))?;
+        for (pattern_name, min_quantity, kg_unit_price) in weight_breaks {
+            if let Some(table) = tables
+                .iter_mut()
+                .find(|t| t.pattern_name.value() == pattern_name)
+            {
+                table.add_weight_break(min_quantity, kg_unit_price);
+            }
+        }
+
+        let surcharges = query(
+            &database.conn,
+            "SELECT pattern_name, zone, kg_surcharge FROM freight_zone_surcharges ORDER BY id",
+            |row| {
+                Ok((
+                    row.get::<_, String>("pattern_name")?,
+                    row.get::<_, String>("zone")?,
+                    Amount::from_decimal(decimal(row, "kg_surcharge")?).map_err(invalid)?,
+                ))
+            },
+        )
+        .map_err(unreadable("運賃地区割増"))?;
+        for (pattern_name, zone, kg_surcharge) in surcharges {
+            if let Some(table) = tables
+                .iter_mut()
+                .find(|t| t.pattern_name.value() == pattern_name)
+            {
+                table.add_zone_surcharge(zone, kg_surcharge);
+            }
+        }
+
+        Ok(Self { tables })
+    }
+}
+
+impl FreightRateTableRepository for SqliteFreightRateTableRepository {
+    fn find_all(&self) -> Result<Vec<FreightRateTable>> {
+        Ok(self.tables.clone())
+    }
+}
+
+/// SQLiteリポジトリファクトリ
+pub struct SqliteRepositoryFactory {
+    pub formula_repo: SqliteFormulaRepository,
+    pub freight_repo: SqliteFreightMasterRepository,
+    pub purchase_repo: SqlitePurchaseRepository,
+    pub production_repo: SqliteProductionRepository,
+    pub transaction_repo: SqliteInventoryTransactionRepository,
+    pub opening_balance_repo: SqliteOpeningBalanceRepository,
+    pub diagnostics: Vec<Diagnostic>, // 取り込み時に読み飛ばした行の問題
+}
+
+impl SqliteRepositoryFactory {
+    /// データベースからすべてのリポジトリを初期化
+    ///
+    /// 取り込み時に検証済みのため、読み飛ばす行はない（不正な値がある場合はエラー）。
+    pub fn from_database(database: &SqliteDatabase) -> Result<Self> {
+        println!("データベースを読み取り中: {}", database.path());
+
+        println!("\nリポジトリを初期化中...");
+        let formula_repo = SqliteFormulaRepository::load(database)?;
+        if formula_repo.data.is_empty() {
+            return Err(DomainError::TableUnreadable {
+                table: "配合マスタ".to_string(),
+                reason: format!(
+                    "{} にデータがありません（import コマンドでExcelテンプレートを取り込んでください）",
+                    database.path()
+                ),
+            });
+        }
+        // 運賃マスタに運賃パターンの運賃表を紐付けるため、運賃表を先に読み取る
+        let rate_tables = SqliteFreightRateTableRepository::load(database)?.find_all()?;
+        let freight_repo = SqliteFreightMasterRepository::load(database, &rate_tables)?;
+        // 仕入・入出庫の数量を kg に換算するため、単位換算マスタを先に読み取る
+        let conversions = SqliteUnitConversionRepository::load(database)?.find_all()?;
+        let purchase_repo = SqlitePurchaseRepository::load(database, &conversions)?;
+        let production_repo = SqliteProductionRepository::load(database)?;
+        let transaction_repo = SqliteInventoryTransactionRepository::load(database, &conversions)?;
+        let opening_balance_repo = SqliteOpeningBalanceRepository::load(database)?;
+        let diagnostics = database.import_diagnostics()?;
+        println!("  ✓ リポジトリの初期化完了");
+
+        Ok(Self {
+            formula_repo,
+            freight_repo,
+            purchase_repo,
+            production_repo,
+            transaction_repo,
+            opening_balance_repo,
+            diagnostics,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> TransactionDate {
+        TransactionDate::new(value.to_string()).unwrap()
+    }
+
+    fn code(value: &str) -> ProductCode {
+        ProductCode::new(value.to_string()).unwrap()
+    }
+
+    fn amount(value: f64) -> Amount {
+        Amount::new(value).unwrap()
+    }
+
+    fn qty(value: f64) -> Quantity {
+        Quantity::new(value).unwrap()
+    }
+
+    fn input_data() -> InputData {
+        let mut version = FormulaVersion::new(code("P001"), None, None).unwrap();
+        version.add_entry(FormulaEntry::new(
+            code("M001"),
+            ConsumptionRatio::new(0.5).unwrap(),
+        ));
+
+        let pattern = PatternName::new("路線便".to_string()).unwrap();
+        let mut rate_table = FreightRateTable::new(pattern.clone());
+        rate_table.add_weight_break(qty(0.0), amount(20.0));
+        rate_table.add_weight_break(qty(500.0), amount(15.0));
+        rate_table.add_zone_surcharge("九州".to_string(), amount(3.0));
+
+        InputData {
+            formula_versions: vec![version],
+            freight_masters: vec![
+                FreightMaster::new(
+                    "T01".to_string(),
+                    pattern,
+                    amount(10.0),
+                    date("2026-01-01"),
+                    None,
+                )
+                .unwrap()
+                .with_zone("九州".to_string()),
+            ],
+            rate_tables: vec![rate_table],
+            unit_conversions: vec![
+                UnitConversion::new(Some(code("M001")), Unit::Bag, qty(25.0)).unwrap(),
+            ],
+            purchases: vec![
+                Purchase::new(
+                    date("2026-01-05"),
+                    code("M001"),
+                    "原砂A".to_string(),
+                    amount(100.0),
+                    qty(500.0),
+                    FreightCode::Code("T01".to_string()),
+                ),
+                Purchase::new(
+                    date("2026-01-05"),
+                    code("M001"),
+                    "原砂A".to_string(),
+                    amount(110.0),
+                    qty(300.0),
+                    FreightCode::LotFreight(amount(1500.0)),
+                )
+                .with_shipment("S01".to_string()),
+            ],
+            productions: vec![
+                Production::new(
+                    date("2026-01-10"),
+                    code("P001"),
+                    qty(100.0),
+                    YieldRate::new(0.9).unwrap(),
+                    amount(0.0),
+                    amount(0.0),
+                )
+                .with_plant("第2工場".to_string()),
+            ],
+            transactions: vec![InventoryTransaction::new(
+                date("2026-01-20"),
+                InventoryType::Sales,
+                code("P001"),
+                "製品A".to_string(),
+                qty(40.0),
+            )],
+            opening_balances: vec![OpeningBalance::new(
+                code("M001"),
+                "原砂A".to_string(),
+                qty(200.0),
+                Some(amount(19000.0)),
+            )],
+        }
+    }
+
+    fn database() -> SqliteDatabase {
+        let mut database = SqliteDatabase::open(":memory:").unwrap();
+        database
+            .import_input_data(&input_data(), "input.xlsx", &[])
+            .unwrap();
+        database
+    }
+
+    fn count(database: &SqliteDatabase, table: &str) -> i64 {
+        database
+            .conn
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_input_data_round_trip() {
+        let factory = SqliteRepositoryFactory::from_database(&database()).unwrap();
+
+        let entries = factory
+            .formula_repo
+            .find_by_product_code_as_of(&code("P001"), &date("2026-01-10"))
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].consumption_ratio.value(), 0.5);
+
+        // 同日の仕入は登録順（後に登録した便運賃の仕入を採用）
+        let purchase = factory
+            .purchase_repo
+            .find_price_as_of(&code("M001"), &date("2026-01-31"))
+            .unwrap();
+        assert_eq!(purchase.unit_price.value(), 110.0);
+        assert_eq!(purchase.shipment_id.as_deref(), Some("S01"));
+        assert!(matches!(purchase.freight_code, FreightCode::LotFreight(a) if a.value() == 1500.0));
+
+        // 運賃表は運賃パターンで紐付け、地区割増も復元する
+        let master = factory
+            .freight_repo
+            .find_by_code_as_of("T01", &date("2026-01-10"))
+            .unwrap();
+        assert_eq!(master.kg_unit_price_for(&qty(500.0)).unwrap().value(), 18.0);
+
+        let productions = factory.production_repo.find_all().unwrap();
+        assert_eq!(productions[0].plant.as_deref(), Some("第2工場"));
+        assert_eq!(factory.production_repo.source_row(0), None);
+
+        let transactions = factory.transaction_repo.find_all_transactions().unwrap();
+        assert_eq!(transactions[0].inventory_type, InventoryType::Sales);
+
+        let balances = factory.opening_balance_repo.find_all().unwrap();
+        assert_eq!(balances[0].unit_price().unwrap().value(), 95.0);
+    }
+
+    #[test]
+    fn test_decimals_stored_as_text() {
+        let mut data = input_data();
+        data.opening_balances[0].amount =
+            Some(Amount::from_decimal(Decimal::new(123456789, 4)).unwrap());
+        let mut database = SqliteDatabase::open(":memory:").unwrap();
+        database
+            .import_input_data(&data, "input.xlsx", &[])
+            .unwrap();
+
+        let (kind, stored): (String, String) = database
+            .conn
+            .query_row(
+                "SELECT typeof(amount), amount FROM opening_balances",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(kind, "text");
+        assert_eq!(stored, "12345.6789");
+
+        let balances = SqliteOpeningBalanceRepository::load(&database)
+            .unwrap()
+            .find_all()
+            .unwrap();
+        assert_eq!(
+            balances[0].amount.map(|a| a.decimal()),
+            Some(Decimal::new(123456789, 4))
+        );
+    }
+
+    #[test]
+    fn test_purchase_in_bags_normalized_on_load() {
+        let database = database();
+        database
+            .conn
+            .execute(
+                "INSERT INTO purchases
+                 (import_id, period, purchase_date, product_code, product_name, unit_price,
+                  quantity, unit)
+                 VALUES (1, '2026-02', '2026/02/01', 'M001', '原砂A', '2500', '10', '袋')",
+                [],
+            )
+            .unwrap();
+        let conversions = SqliteUnitConversionRepository::load(&database)
+            .unwrap()
+            .find_all()
+            .unwrap();
+
+        let purchase = SqlitePurchaseRepository::load(&database, &conversions)
+            .unwrap()
+            .find_price_as_of(&code("M001"), &date("2026-02-01"))
+            .unwrap();
+
+        assert_eq!(purchase.quantity.value(), 250.0);
+        assert_eq!(purchase.unit_price.value(), 100.0);
+        assert!(matches!(purchase.freight_code, FreightCode::DirectPrice(p) if p == 0.0));
+    }
+
+    #[test]
+    fn test_reimport_keeps_runs() {
+        let mut database = database();
+        let result = MaterialCostResultDto {
+            row_number: 3,
+            production_date: "2026-01-10".to_string(),
+            product_code: "P001".to_string(),
+            raw_material_cost: 5500.0,
+            yield_cost: 4950.0,
+            yield_rate: 0.9,
+            yield_formula: "原砂金額 × 歩留率".to_string(),
+            coagulant_cost: 0.0,
+            clay_treatment_cost: 0.0,
+            freight_cost: 250.0,
+            total_material_cost: 5200.0,
+            intermediate_costs: Vec::new(),
+            explosion: Vec::new(),
+        };
+
+        let first = database
+            .save_run(std::slice::from_ref(&result), &[])
+            .unwrap();
+        let second = database.save_run(&[result], &[]).unwrap();
+        let import_id = database
+            .import_input_data(&input_data(), "input.xlsx", &[])
+            .unwrap();
+
+        assert_eq!((first, second, import_id), (1, 2, 2));
+        assert_eq!(count(&database, "runs"), 2);
+        assert_eq!(count(&database, "run_cost_results"), 2);
+        assert_eq!(count(&database, "purchases"), 2);
+        assert_eq!(count(&database, "formula_entries"), 1);
+    }
+
+    #[test]
+    fn test_import_of_other_month_appends_transactions() {
+        let mut database = database();
+        let mut data = input_data();
+        data.purchases.truncate(1);
+        data.purchases[0].purchase_date = date("2026-02-03");
+        data.purchases[0].unit_price = amount(120.0);
+        data.productions.clear();
+        data.transactions.clear();
+        data.formula_versions.clear();
+
+        database
+            .import_input_data(&data, "2026-02.xlsx", &[])
+            .unwrap();
+
+        assert_eq!(count(&database, "purchases"), 3);
+        assert_eq!(count(&database, "productions"), 1);
+        let purchases = SqlitePurchaseRepository::load(&database, &[]).unwrap();
+        assert_eq!(
+            purchases
+                .find_price_as_of(&code("M001"), &date("2026-01-31"))
+                .unwrap()
+                .unit_price
+                .value(),
+            110.0
+        );
+        assert_eq!(
+            purchases
+                .find_price_as_of(&code("M001"), &date("2026-02-28"))
+                .unwrap()
+                .unit_price
+                .value(),
+            120.0
+        );
+    }
+
+    #[test]
+    fn test_import_of_same_month_replaces_transactions_and_masters() {
+        let mut database = database();
+        let mut data = input_data();
+        data.purchases.truncate(1);
+        data.purchases[0].purchase_date = date("2026/01/20");
+        data.formula_versions[0].add_entry(FormulaEntry::new(
+            code("M002"),
+            ConsumptionRatio::new(0.3).unwrap(),
+        ));
+        data.opening_balances[0].quantity = qty(300.0);
+
+        database
+            .import_input_data(&data, "2026-01.xlsx", &[])
+            .unwrap();
+
+        assert_eq!(count(&database, "purchases"), 1);
+        assert_eq!(count(&database, "productions"), 1);
+        assert_eq!(count(&database, "formula_versions"), 1);
+        assert_eq!(count(&database, "formula_entries"), 2);
+        assert_eq!(count(&database, "freight_weight_breaks"), 2);
+        assert_eq!(count(&database, "unit_conversions"), 1);
+        let balances = SqliteOpeningBalanceRepository::load(&database)
+            .unwrap()
+            .find_all()
+            .unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].quantity.value(), 300.0);
+    }
+
+    #[test]
+    fn test_import_diagnostics_persisted_until_replaced() {
+        let mut database = SqliteDatabase::open(":memory:").unwrap();
+        let skipped = Diagnostic::new("生産", "歩留率が範囲外です")
+            .with_row(5)
+            .with_column("歩留率");
+        database
+            .import_input_data(
+                &input_data(),
+                "2026-01.xlsx",
+                std::slice::from_ref(&skipped),
+            )
+            .unwrap();
+
+        let factory = SqliteRepositoryFactory::from_database(&database).unwrap();
+        assert_eq!(factory.diagnostics, vec![skipped]);
+
+        // すべてのデータを置き換えた取り込みの問題は報告しない
+        database
+            .import_input_data(&input_data(), "2026-01.xlsx", &[])
+            .unwrap();
+        assert!(database.import_diagnostics().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_empty_database_requires_import() {
+        let database = SqliteDatabase::open(":memory:").unwrap();
+
+        let result = SqliteRepositoryFactory::from_database(&database);
+
+        assert!(matches!(
+            result,
+            Err(DomainError::TableUnreadable { ref reason, .. }) if reason.contains("import")
+        ));
+    }
+
+    #[test]
+    fn test_unopenable_database_is_typed_error() {
+        let result = SqliteDatabase::open("tests/存在しないフォルダ/material_cost.db");
+
+        assert!(matches!(result, Err(DomainError::InputUnreadable { .. })));
+    }
+
+    #[test]
+    fn test_invalid_stored_value_is_typed_error() {
+        let database = database();
+        database
+            .conn
+            .execute("UPDATE productions SET yield_rate = 1.5", [])
+            .unwrap();
+
+        let result = SqliteProductionRepository::load(&database);
+
+        assert!(matches!(
+            result,
+            Err(DomainError::TableUnreadable { ref table, .. }) if table == "生産"
+        ));
+    }
+}
@@ -5,10 +5,25 @@ mod infrastructure;
 mod usecase;
 
 use adapter::controller::ExcelController;
+use adapter::file_presenter::{FileFormat, FilePresenter};
+use adapter::multi_presenter::MultiPresenter;
 use adapter::presenter::ExcelPresenter;
-use color_eyre::Result;
+use adapter::run_store_presenter::RunStorePresenter;
+use color_eyre::{Result, eyre};
 use config::Config;
+use domain::diagnostics::Diagnostic;
+use domain::formula_validation::FormulaValidationPolicy;
+use domain::pricing::PricedPurchaseRepository;
+use domain::repositories::*;
+use domain::services::{InventoryHistoryService, RoundingPolicy, SafetyStockPolicy, YieldPolicy};
+use domain::sheet_schema::SchemaMapping;
+use domain::value_objects::{
+    FreightAllocationMethod, PricingMethod, RoundingMode, TransactionDate, YieldMethod,
+};
+use infrastructure::csv_repositories::{CsvEncoding, CsvRepositoryFactory};
 use infrastructure::excel_repositories::ExcelRepositoryFactory;
+use infrastructure::sqlite_repositories::{InputData, SqliteDatabase, SqliteRepositoryFactory};
+use std::collections::HashMap;
 use std::io::{self, Write};
 
 fn main() {
@@ -44,29 +59,231 @@ fn run() -> Result<()> {
         }
     };
 
-    let input_path = &config.paths.input_file;
-    let output_path = &config.paths.output_file;
+    let pricing_method = PricingMethod::new(config.pricing.method.clone())?;
+    let safety_stock = SafetyStockPolicy::new(
+        config.inventory.safety_stock,
+        config.inventory.safety_stock_by_product.clone(),
+    )?;
+    let period_start = config
+        .inventory
+        .period_start
+        .clone()
+        .map(TransactionDate::new)
+        .transpose()?;
+    let formula_validation = FormulaValidationPolicy::new(
+        config.formula_validation.min_total_ratio,
+        config.formula_validation.max_total_ratio,
+        config.formula_validation.strict,
+    )?;
+    let rounding = RoundingPolicy::new(
+        RoundingMode::new(config.rounding.raw_material_cost.clone())?,
+        RoundingMode::new(config.rounding.yield_cost.clone())?,
+        RoundingMode::new(config.rounding.freight_cost.clone())?,
+        RoundingMode::new(config.rounding.material_cost.clone())?,
+        config.rounding.digits,
+    )?;
+    let yield_policy = YieldPolicy::new(
+        YieldMethod::new(config.yield_rate.method.clone())?,
+        yield_methods(&config.yield_rate.method_by_plant)?,
+        yield_methods(&config.yield_rate.method_by_product)?,
+    );
+    let freight_allocation = FreightAllocationMethod::new(config.freight.allocation.clone())?;
+    let mapping = schema_mapping(&config.schema);
+
+    // import コマンド: Excelテンプレートの入力データをデータベースに取り込む
+    if std::env::args().nth(1).as_deref() == Some("import") {
+        return import_excel(&config, freight_allocation, &mapping);
+    }
+
+    let policies = Policies {
+        pricing_method,
+        safety_stock,
+        period_start,
+        formula_validation,
+        rounding,
+        yield_policy,
+    };
+
+    // 入力データを読み取り、リポジトリを初期化
+    match config.input.format.as_str() {
+        "excel" => {
+            println!("便運賃の配賦基準: {}", freight_allocation.as_str());
+            let factory = ExcelRepositoryFactory::from_file(
+                &config.paths.input_file,
+                config.diagnostics.accumulate,
+                freight_allocation,
+                &mapping,
+            )?;
+            calculate(
+                &config,
+                &mapping,
+                policies,
+                Repositories {
+                    formula: &factory.formula_repo,
+                    purchase: &factory.purchase_repo,
+                    freight: &factory.freight_repo,
+                    production: &factory.production_repo,
+                    transaction: &factory.transaction_repo,
+                    opening_balance: &factory.opening_balance_repo,
+                    diagnostics: &factory.diagnostics,
+                },
+            )
+        }
+        "csv" => {
+            println!("便運賃の配賦基準: {}", freight_allocation.as_str());
+            let factory = CsvRepositoryFactory::from_dir(
+                &config.input.csv_dir,
+                CsvEncoding::new(config.input.encoding.clone())?,
+                config.diagnostics.accumulate,
+                freight_allocation,
+                &mapping,
+            )?;
+            calculate(
+                &config,
+                &mapping,
+                policies,
+                Repositories {
+                    formula: &factory.formula_repo,
+                    purchase: &factory.purchase_repo,
+                    freight: &factory.freight_repo,
+                    production: &factory.production_repo,
+                    transaction: &factory.transaction_repo,
+                    opening_balance: &factory.opening_balance_repo,
+                    diagnostics: &factory.diagnostics,
+                },
+            )
+        }
+        // 便運賃は取り込み時に配賦済みのため、配賦基準の設定は使わない
+        "sqlite" => {
+            let database = SqliteDatabase::open(&config.database.path)?;
+            let factory = SqliteRepositoryFactory::from_database(&database)?;
+            calculate(
+                &config,
+                &mapping,
+                policies,
+                Repositories {
+                    formula: &factory.formula_repo,
+                    purchase: &factory.purchase_repo,
+                    freight: &factory.freight_repo,
+                    production: &factory.production_repo,
+                    transaction: &factory.transaction_repo,
+                    opening_balance: &factory.opening_balance_repo,
+                    diagnostics: &factory.diagnostics,
+                },
+            )
+        }
+        other => Err(eyre::eyre!(
+            "入力形式が不正です: '{}'\n  有効な形式: excel, csv, sqlite",
+            other
+        )),
+    }
+}
 
-    // Excelファイルを読み取り、リポジトリを初期化
-    let factory = ExcelRepositoryFactory::from_file(input_path)?;
+/// 設定から組み立てた計算方針
+struct Policies {
+    pricing_method: PricingMethod,
+    safety_stock: SafetyStockPolicy,
+    period_start: Option<TransactionDate>,
+    formula_validation: FormulaValidationPolicy,
+    rounding: RoundingPolicy,
+    yield_policy: YieldPolicy,
+}
+
+/// 入力形式によらないリポジトリ一式
+struct Repositories<'a, F, P, FR, R, T, OB> {
+    formula: &'a F,
+    purchase: &'a P,
+    freight: &'a FR,
+    production: &'a R,
+    transaction: &'a T,
+    opening_balance: &'a OB,
+    diagnostics: &'a [Diagnostic], // 蓄積モードで読み飛ばした行の問題
+}
+
+/// 材料費計算・月次受払表・入出庫履歴を作成し、結果を保存する
+fn calculate<F, P, FR, R, T, OB>(
+    config: &Config,
+    mapping: &SchemaMapping,
+    policies: Policies,
+    repos: Repositories<'_, F, P, FR, R, T, OB>,
+) -> Result<()>
+where
+    F: FormulaRepository,
+    P: PurchaseRepository,
+    FR: FreightMasterRepository,
+    R: ProductionRepository,
+    T: InventoryTransactionRepository,
+    OB: OpeningBalanceRepository,
+{
+    let Policies {
+        pricing_method,
+        safety_stock,
+        period_start,
+        formula_validation,
+        rounding,
+        yield_policy,
+    } = policies;
+
+    // 仕入単価に単価計算方法を適用
+    println!("\n単価計算方法: {}", pricing_method.as_str());
+    println!(
+        "端数処理: 原砂金額={}, 原砂歩留金額={}, 材料運賃={}, 材料費={}（小数点以下{}桁）",
+        rounding.raw_material_cost.as_str(),
+        rounding.yield_cost.as_str(),
+        rounding.freight_cost.as_str(),
+        rounding.total_material_cost.as_str(),
+        rounding.digits
+    );
+    println!(
+        "歩留率の計算方法: {}（{}）、工場別 {} 件、商品別 {} 件",
+        yield_policy.default_method().as_str(),
+        yield_policy.default_method().formula(),
+        config.yield_rate.method_by_plant.len(),
+        config.yield_rate.method_by_product.len()
+    );
+    // 移動平均法の払出は配合マスタから全生産分の材料出庫を求めて渡す
+    let issues = InventoryHistoryService::planned_material_issues(
+        &repos.production.find_all()?,
+        repos.formula,
+    );
+    let purchase_repo = PricedPurchaseRepository::new(repos.purchase, pricing_method)
+        .with_opening_balances(repos.opening_balance.find_all()?)
+        .with_issues(issues);
 
     // プレゼンターを初期化
-    let mut presenter = ExcelPresenter::new(input_path.clone(), output_path.clone())?;
+    let mut presenter = presenters(config, mapping)?;
 
     // コントローラを組み立てる
     let mut controller = ExcelController::new(
-        &factory.formula_repo,
-        &factory.purchase_repo,
-        &factory.freight_repo,
-        &factory.production_repo,
-        &factory.transaction_repo,
+        repos.formula,
+        &purchase_repo,
+        repos.freight,
+        repos.production,
+        repos.transaction,
+        repos.opening_balance,
         &mut presenter,
-    );
+    )
+    .with_safety_stock(safety_stock)
+    .with_period_start(period_start)
+    .with_formula_validation(formula_validation)
+    .with_rounding(rounding)
+    .with_yield_policy(yield_policy)
+    .with_schema(mapping)
+    .with_error_accumulation(config.diagnostics.accumulate);
+
+    // 読み取り時にスキップした行を報告（診断モード）
+    controller.execute_diagnostics_report(repos.diagnostics)?;
+
+    // 配合マスタ検証（材料費計算の前に実行）
+    controller.execute_formula_validation()?;
 
     // ユースケース1: 材料費計算
     controller.execute_material_cost_calculation()?;
 
-    // ユースケース2: 入出庫履歴作成
+    // ユースケース2: 月次受払表作成
+    controller.execute_monthly_inventory_report()?;
+
+    // ユースケース3: 入出庫履歴作成（出力ポートを終了するため最後に実行）
     controller.execute_inventory_history_creation()?;
 
     // 結果を保存
@@ -75,6 +292,119 @@ fn run() -> Result<()> {
     Ok(())
 }
 
+/// 設定した出力形式のプレゼンターを組み立てる
+///
+/// Excelは paths.input_file をテンプレートとして paths.output_file に保存する（CSV・SQLite入力の場合も同じ）。
+/// SQLiteは database.path に実行IDを付けて計算結果を蓄積する。
+fn presenters(config: &Config, mapping: &SchemaMapping) -> Result<MultiPresenter> {
+    if config.output.formats.is_empty() {
+        return Err(eyre::eyre!(
+            "出力形式が指定されていません（[output] formats に excel, csv, json, sqlite のいずれかを指定してください）"
+        ));
+    }
+
+    let mut presenter = MultiPresenter::new();
+    for format in &config.output.formats {
+        presenter = match format.as_str() {
+            "excel" => presenter.with_presenter(Box::new(ExcelPresenter::new(
+                config.paths.input_file.clone(),
+                config.paths.output_file.clone(),
+                mapping.clone(),
+            )?)),
+            "csv" => presenter.with_presenter(Box::new(FilePresenter::new(
+                FileFormat::Csv,
+                config.output.dir.clone(),
+            ))),
+            "json" => presenter.with_presenter(Box::new(FilePresenter::new(
+                FileFormat::Json,
+                config.output.dir.clone(),
+            ))),
+            "sqlite" => presenter.with_presenter(Box::new(RunStorePresenter::new(
+                SqliteDatabase::open(&config.database.path)?,
+            ))),
+            other => {
+                return Err(eyre::eyre!(
+                    "出力形式が不正です: '{}'\n  有効な形式: excel, csv, json, sqlite",
+                    other
+                ));
+            }
+        };
+    }
+    Ok(presenter)
+}
+
+/// Excelテンプレート（paths.input_file）の入力データをデータベース（database.path）に取り込む
+///
+/// マスタは同じキーのデータを、仕入・生産・入出庫は取り込むデータに含まれる月のデータを置き換える。
+/// 読み飛ばした行の問題（蓄積モード）は取り込みIDに紐付けて保存し、実行結果の履歴は残す。
+fn import_excel(
+    config: &Config,
+    freight_allocation: FreightAllocationMethod,
+    mapping: &SchemaMapping,
+) -> Result<()> {
+    println!("便運賃の配賦基準: {}", freight_allocation.as_str());
+    let factory = ExcelRepositoryFactory::from_file(
+        &config.paths.input_file,
+        config.diagnostics.accumulate,
+        freight_allocation,
+        mapping,
+    )?;
+    let data = InputData::from_excel(&factory)?;
+
+    // 読み飛ばした行の問題（件数は読み取り時に表示済み）
+    for diagnostic in &factory.diagnostics {
+        println!("    {}", diagnostic);
+    }
+
+    let mut database = SqliteDatabase::open(&config.database.path)?;
+    let import_id =
+        database.import_input_data(&data, &config.paths.input_file, &factory.diagnostics)?;
+
+    println!(
+        "\n✓ データベースに取り込みました: {}（取り込みID: {}）",
+        database.path(),
+        import_id
+    );
+    println!(
+        "  配合マスタ {} 版、運賃マスタ {} 件、運賃表 {} 件、単位換算 {} 件",
+        data.formula_versions.len(),
+        data.freight_masters.len(),
+        data.rate_tables.len(),
+        data.unit_conversions.len()
+    );
+    println!(
+        "  仕入 {} 件、生産 {} 件、入出庫 {} 件、期首在庫 {} 件",
+        data.purchases.len(),
+        data.productions.len(),
+        data.transactions.len(),
+        data.opening_balances.len()
+    );
+    Ok(())
+}
+
+/// 工場別・商品コード別の歩留率の計算方法を読み取る
+fn yield_methods(methods: &HashMap<String, String>) -> Result<HashMap<String, YieldMethod>> {
+    let mut parsed = HashMap::new();
+    for (key, method) in methods {
+        parsed.insert(key.clone(), YieldMethod::new(method.clone())?);
+    }
+    Ok(parsed)
+}
+
+/// シート名・列名の対応表を組み立てる
+fn schema_mapping(schema: &config::Schema) -> SchemaMapping {
+    let mut mapping = SchemaMapping::new();
+    for (sheet, actual) in &schema.sheets {
+        mapping = mapping.with_sheet(sheet, actual.clone());
+    }
+    for (sheet, columns) in &schema.columns {
+        for (column, aliases) in columns {
+            mapping = mapping.with_column_aliases(sheet, column, aliases.clone());
+        }
+    }
+    mapping
+}
+
 fn wait_for_enter() -> Result<()> {
     println!("\nEnterキーを押して終了...");
     if let Err(e) = io::stdout().flush() {
@@ -1,31 +1,85 @@
+use serde::Serialize;
+
 /// 材料消費結果DTO
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MaterialConsumptionDto {
     pub material_code: String,
     pub material_name: String,
     pub quantity: f64,
     pub unit_price: f64,
+    pub purchase_date: String,
     pub total_cost: f64,
     pub freight_cost: f64,
     pub purchase_quantity: f64,
     pub freight_code_str: String,
     pub freight_kg_price: f64,
+    pub lot_allocations: Vec<LotAllocationDto>,
+    pub lot_shortage: f64,
 }
 
-/// 材料費計算結果DTO
+/// ロット引当DTO
+#[derive(Debug, Clone, Serialize)]
+pub struct LotAllocationDto {
+    pub purchase_date: String,
+    pub quantity: f64,
+    pub unit_price: f64,
+    pub amount: f64,
+    pub freight: Option<f64>, // 引き当てたロットのロット運賃（ロット運賃の仕入のみ）
+}
+
+/// 期末ロット残高DTO
 #[derive(Debug, Clone)]
+pub struct LotBalanceDto {
+    pub product_code: String,
+    pub product_name: String,
+    pub purchase_date: String,
+    pub unit_price: f64,
+    pub purchased_quantity: f64,
+    pub remaining_quantity: f64,
+    pub remaining_amount: f64,
+}
+
+/// 材料費計算結果DTO
+#[derive(Debug, Clone, Serialize)]
 pub struct MaterialCostResultDto {
     pub row_number: usize,
+    pub production_date: String,
+    pub product_code: String,
     pub raw_material_cost: f64,
     pub yield_cost: f64,
+    pub yield_rate: f64,
+    pub yield_formula: String, // 適用した原砂歩留金額の計算式
     pub coagulant_cost: f64,
     pub clay_treatment_cost: f64,
     pub freight_cost: f64,
     pub total_material_cost: f64,
+    pub intermediate_costs: Vec<IntermediateCostDto>,
+    pub explosion: Vec<BomNodeDto>,
+}
+
+/// 中間品積上げ原価DTO
+#[derive(Debug, Clone, Serialize)]
+pub struct IntermediateCostDto {
+    pub material_code: String,
+    pub level: usize,
+    pub quantity: f64,
+    pub material_cost: f64,
+    pub freight_cost: f64,
+}
+
+/// 部品展開ツリーDTO
+#[derive(Debug, Clone, Serialize)]
+pub struct BomNodeDto {
+    pub material_code: String,
+    pub material_name: String,
+    pub quantity: f64,
+    pub material_cost: f64,
+    pub freight_cost: f64,
+    pub children: Vec<BomNodeDto>,
 }
 
 /// 入出庫履歴レコードDTO
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct InventoryHistoryRecordDto {
     pub date: String,
     pub inventory_type: String,
@@ -35,3 +89,74 @@ pub struct InventoryHistoryRecordDto {
     pub change_quantity: f64,
     pub balance: f64,
 }
+
+/// 在庫警告DTO
+#[derive(Debug, Clone)]
+pub struct InventoryWarningDto {
+    pub kind: String,
+    pub date: String,
+    pub inventory_type: String,
+    pub product_code: String,
+    pub product_name: String,
+    pub change_quantity: f64,
+    pub balance: f64,
+    pub threshold: f64,
+}
+
+/// 単価が求められず金額0で評価した受払DTO
+#[derive(Debug, Clone)]
+pub struct MissingPriceDto {
+    pub month: String,
+    pub product_code: String,
+    pub product_name: String,
+    pub quantity: f64,
+}
+
+/// 月次受払表DTO
+#[derive(Debug, Clone)]
+pub struct MonthlyInventoryReportDto {
+    pub month: String,
+    pub product_code: String,
+    pub product_name: String,
+    pub opening_quantity: f64,
+    pub opening_amount: f64,
+    pub production_quantity: f64,
+    pub purchase_quantity: f64,
+    pub receipt_amount: f64,
+    pub sales_quantity: f64,
+    pub material_issue_quantity: f64,
+    pub issue_amount: f64,
+    pub closing_quantity: f64,
+    pub closing_amount: f64,
+    pub unit_price: Option<f64>,
+}
+
+/// 配合マスタ検証結果DTO
+#[derive(Debug, Clone)]
+pub struct FormulaIssueDto {
+    pub kind: String,
+    pub product_code: String,
+    pub period: String,
+    pub material_code: String,
+    pub message: String,
+}
+
+/// 入力データの問題DTO
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticDto {
+    pub sheet: String,
+    pub row: Option<usize>,
+    pub column: String,
+    pub message: String,
+}
+
+/// エラーDTO（位置・コードが特定できない場合は空）
+#[derive(Debug, Clone)]
+pub struct ErrorDto {
+    pub category: String,
+    pub sheet: String,
+    pub row: Option<usize>,
+    pub column: String,
+    pub code: String,
+    pub message: String,
+}
@@ -1,31 +1,152 @@
 use super::dtos::*;
 use super::ports::*;
+use crate::domain::cost_layer::CostLayerLedger;
+use crate::domain::diagnostics::Diagnostic;
+use crate::domain::error::{DomainError, Result as DomainResult};
+use crate::domain::formula_validation::{FormulaValidationPolicy, FormulaValidationService};
 use crate::domain::repositories::*;
 use crate::domain::services::*;
+use crate::domain::sheet_schema::ProductionSheetSchema;
+use crate::domain::valuation::{InventoryValuationService, MonthlyInventoryValuation};
+use crate::domain::value_objects::TransactionDate;
 use color_eyre::Result;
+use rust_decimal::prelude::ToPrimitive;
+
+/// 配合マスタ検証インタラクタ
+pub struct ValidateFormulaInteractor<'a, F, O>
+where
+    F: FormulaRepository,
+    O: ValidateFormulaOutputPort,
+{
+    formula_repo: &'a F,
+    output_port: &'a mut O,
+    policy: FormulaValidationPolicy,
+}
+
+impl<'a, F, O> ValidateFormulaInteractor<'a, F, O>
+where
+    F: FormulaRepository,
+    O: ValidateFormulaOutputPort,
+{
+    pub fn new(formula_repo: &'a F, output_port: &'a mut O) -> Self {
+        Self {
+            formula_repo,
+            output_port,
+            policy: FormulaValidationPolicy::default(),
+        }
+    }
+
+    /// 検証の設定（消費比率合計の範囲・strictモード）
+    pub fn with_policy(mut self, policy: FormulaValidationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+impl<'a, F, O> ValidateFormulaInputPort for ValidateFormulaInteractor<'a, F, O>
+where
+    F: FormulaRepository,
+    O: ValidateFormulaOutputPort,
+{
+    fn execute(&mut self) -> Result<()> {
+        let versions = match self.formula_repo.find_all_versions() {
+            Ok(v) => v,
+            Err(e) => {
+                self.output_port.present_validation_error(&error_dto(&e));
+                return Err(e.into());
+            }
+        };
+        self.output_port.present_validation_start(versions.len());
+
+        let issues = FormulaValidationService::validate(&versions, &self.policy);
+        let issue_dtos: Vec<FormulaIssueDto> = issues
+            .iter()
+            .map(|i| FormulaIssueDto {
+                kind: i.kind.as_str().to_string(),
+                product_code: i.product_code.clone(),
+                period: i.period.clone(),
+                material_code: i.material_code.clone().unwrap_or_default(),
+                message: i.message.clone(),
+            })
+            .collect();
+        self.output_port
+            .present_formula_issues(&issue_dtos, self.policy.strict);
+
+        // strictモードでは問題があれば材料費計算の前に中止
+        if self.policy.strict && !issues.is_empty() {
+            let e = DomainError::FormulaValidationFailed {
+                count: issues.len(),
+            };
+            self.output_port.present_validation_error(&error_dto(&e));
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// 入力データ診断結果の出力インタラクタ
+pub struct ReportDiagnosticsInteractor<'a, O>
+where
+    O: ReportDiagnosticsOutputPort,
+{
+    diagnostics: &'a [Diagnostic],
+    output_port: &'a mut O,
+}
+
+impl<'a, O> ReportDiagnosticsInteractor<'a, O>
+where
+    O: ReportDiagnosticsOutputPort,
+{
+    pub fn new(diagnostics: &'a [Diagnostic], output_port: &'a mut O) -> Self {
+        Self {
+            diagnostics,
+            output_port,
+        }
+    }
+}
+
+impl<'a, O> ReportDiagnosticsInputPort for ReportDiagnosticsInteractor<'a, O>
+where
+    O: ReportDiagnosticsOutputPort,
+{
+    fn execute(&mut self) -> Result<()> {
+        let dtos: Vec<DiagnosticDto> = self.diagnostics.iter().map(diagnostic_dto).collect();
+        self.output_port.present_diagnostics(&dtos);
+        Ok(())
+    }
+}
 
 /// 材料費計算インタラクタ
-pub struct CalculateMaterialCostInteractor<'a, F, P, FR, R, O>
+pub struct CalculateMaterialCostInteractor<'a, F, P, FR, R, OB, O>
 where
     F: FormulaRepository,
     P: PurchaseRepository,
     FR: FreightMasterRepository,
     R: ProductionRepository,
+    OB: OpeningBalanceRepository,
     O: CalculateMaterialCostOutputPort,
 {
     formula_repo: &'a F,
     purchase_repo: &'a P,
     freight_repo: &'a FR,
     production_repo: &'a R,
+    opening_balance_repo: &'a OB,
     output_port: &'a mut O,
+    accumulate_errors: bool,
+    rounding: RoundingPolicy,
+    yield_policy: YieldPolicy,
+    production_sheet: String,
+    costed_productions: Vec<CostedProduction>,
 }
 
-impl<'a, F, P, FR, R, O> CalculateMaterialCostInteractor<'a, F, P, FR, R, O>
+impl<'a, F, P, FR, R, OB, O> CalculateMaterialCostInteractor<'a, F, P, FR, R, OB, O>
 where
     F: FormulaRepository,
     P: PurchaseRepository,
     FR: FreightMasterRepository,
     R: ProductionRepository,
+    OB: OpeningBalanceRepository,
     O: CalculateMaterialCostOutputPort,
 {
     pub fn new(
@@ -33,6 +154,7 @@ where
         purchase_repo: &'a P,
         freight_repo: &'a FR,
         production_repo: &'a R,
+        opening_balance_repo: &'a OB,
         output_port: &'a mut O,
     ) -> Self {
         Self {
@@ -40,18 +162,69 @@ where
             purchase_repo,
             freight_repo,
             production_repo,
+            opening_balance_repo,
             output_port,
+            accumulate_errors: false,
+            rounding: RoundingPolicy::default(),
+            yield_policy: YieldPolicy::default(),
+            production_sheet: ProductionSheetSchema::SHEET.to_string(),
+            costed_productions: Vec::new(),
         }
     }
+
+    /// 診断モード（計算できない行を記録して残りの行の計算を続ける）
+    pub fn with_error_accumulation(mut self, accumulate_errors: bool) -> Self {
+        self.accumulate_errors = accumulate_errors;
+        self
+    }
+
+    /// 材料費の構成要素ごとの端数処理を設定
+    pub fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// 歩留率の計算方法（乗算・除算）を設定
+    pub fn with_yield_policy(mut self, yield_policy: YieldPolicy) -> Self {
+        self.yield_policy = yield_policy;
+        self
+    }
+
+    /// 計算エラーの行を示す生産シート名を設定（テンプレートのシート名）
+    pub fn with_production_sheet(mut self, production_sheet: String) -> Self {
+        self.production_sheet = production_sheet;
+        self
+    }
+
+    /// 材料費を計算できた生産（生産日順、診断モードで計算できなかった行は報告済みのため含まない）
+    pub fn into_costed_productions(self) -> Vec<CostedProduction> {
+        self.costed_productions
+    }
+
+    /// 行単位の計算エラーを処理（診断モードでは記録してスキップ、そうでなければ中止）
+    fn handle_row_error(&mut self, row_number: usize, e: DomainError) -> Result<()> {
+        if !self.accumulate_errors {
+            self.output_port.present_error(&error_dto(&e));
+            return Err(e.into());
+        }
+        let diagnostic = match e {
+            DomainError::InvalidCell(diagnostic) => diagnostic,
+            e => Diagnostic::new(&self.production_sheet, e).with_row(row_number),
+        };
+        self.output_port
+            .present_row_error(&diagnostic_dto(&diagnostic));
+        Ok(())
+    }
 }
 
-impl<'a, F, P, FR, R, O> CalculateMaterialCostInputPort
-    for CalculateMaterialCostInteractor<'a, F, P, FR, R, O>
+impl<'a, F, P, FR, R, OB, O> CalculateMaterialCostInputPort
+    for CalculateMaterialCostInteractor<'a, F, P, FR, R, OB, O>
 where
     F: FormulaRepository,
     P: PurchaseRepository,
     FR: FreightMasterRepository,
     R: ProductionRepository,
+    OB: OpeningBalanceRepository,
     O: CalculateMaterialCostOutputPort,
 {
     fn execute(&mut self) -> Result<()> {
@@ -59,8 +232,8 @@ where
         let productions = match self.production_repo.find_all() {
             Ok(p) => p,
             Err(e) => {
-                self.output_port.present_error(&format!("{:?}", e));
-                return Err(e);
+                self.output_port.present_error(&error_dto(&e));
+                return Err(e.into());
             }
         };
 
@@ -73,14 +246,35 @@ where
         self.output_port
             .present_calculation_start(productions.len());
 
-        for (idx, production) in productions.iter().enumerate() {
+        // 先入先出でロットを引き当てるため、期首在庫と仕入からロット台帳を作成
+        let ledger = self.purchase_repo.find_all().and_then(|purchases| {
+            let opening_balances = self.opening_balance_repo.find_all()?;
+            Ok(CostLayerLedger::new(purchases, opening_balances))
+        });
+        let mut ledger = match ledger {
+            Ok(l) => l,
+            Err(e) => {
+                self.output_port.present_error(&error_dto(&e));
+                return Err(e.into());
+            }
+        };
+
+        // ロットは生産日順に消費する（同日は行順）
+        let mut ordered: Vec<_> = productions.iter().enumerate().collect();
+        ordered.sort_by(|(_, a), (_, b)| a.production_date.cmp(&b.production_date));
+
+        for (idx, production) in ordered {
+            // 読み取り時にスキップした行があっても元の行番号を使う
+            // （行番号を持たないデータソースは並び順から、ヘッダー行を考慮して+2）
+            let row_number = self.production_repo.source_row(idx).unwrap_or(idx + 2);
             self.output_port.present_processing_row(
-                idx + 2, // ヘッダー行を考慮して+2
+                row_number,
+                production.production_date.value(),
                 production.product_code.value(),
             );
 
             // 材料消費を計算
-            let result = match MaterialCostCalculationService::calculate_material_consumption(
+            let mut result = match MaterialCostCalculationService::calculate_material_consumption(
                 production,
                 self.formula_repo,
                 self.purchase_repo,
@@ -88,11 +282,17 @@ where
             ) {
                 Ok(r) => r,
                 Err(e) => {
-                    self.output_port.present_error(&format!("{:?}", e));
-                    return Err(e);
+                    self.handle_row_error(row_number, e)?;
+                    continue;
                 }
             };
 
+            // 仕入ロットを引き当て
+            if let Err(e) = ledger.allocate(&mut result, &production.production_date) {
+                self.handle_row_error(row_number, e)?;
+                continue;
+            }
+
             // DTOに変換
             let consumption_dtos: Vec<MaterialConsumptionDto> = result
                 .consumptions
@@ -102,101 +302,227 @@ where
                     material_name: c.material_name.clone(),
                     quantity: c.quantity.value(),
                     unit_price: c.unit_price.value(),
+                    purchase_date: c.purchase_date.value().to_string(),
                     total_cost: c.total_cost.value(),
                     freight_cost: c.freight_cost.value(),
                     purchase_quantity: c.purchase_quantity.value(),
                     freight_code_str: c.freight_code_str.clone(),
                     freight_kg_price: c.freight_kg_price,
+                    lot_allocations: c
+                        .lot_allocations
+                        .iter()
+                        .map(|a| LotAllocationDto {
+                            purchase_date: lot_date_label(a.purchase_date.as_ref()),
+                            quantity: a.quantity.value(),
+                            unit_price: a.unit_price.value(),
+                            amount: a.amount.value(),
+                            freight: a.freight.map(|f| f.value()),
+                        })
+                        .collect(),
+                    lot_shortage: c.lot_shortage.value(),
                 })
                 .collect();
 
             self.output_port
                 .present_material_consumptions(&consumption_dtos);
 
-            // 各種金額を計算
-            let raw_material_cost =
-                MaterialCostCalculationService::calculate_raw_material_cost(&result.consumptions);
-            let yield_cost = MaterialCostCalculationService::calculate_yield_cost(
+            // 各種金額を計算（構成要素ごとに端数処理）
+            let raw_material_cost = MaterialCostCalculationService::calculate_raw_material_cost(
+                &result.consumptions,
+                &self.rounding,
+            );
+            let yield_method = self.yield_policy.method_for(production);
+            let yield_cost = match MaterialCostCalculationService::calculate_yield_cost(
                 &raw_material_cost,
                 &production.yield_rate,
+                yield_method,
+                &self.rounding,
+            ) {
+                Ok(c) => c,
+                Err(e) => {
+                    self.handle_row_error(row_number, e)?;
+                    continue;
+                }
+            };
+            let freight_cost = MaterialCostCalculationService::calculate_freight_cost(
+                &result.total_freight_cost,
+                &self.rounding,
             );
-            let total_material_cost = MaterialCostCalculationService::calculate_total_material_cost(
-                &yield_cost,
+            let coagulant_cost = MaterialCostCalculationService::calculate_processing_cost(
                 &production.coagulant_cost,
+                &self.rounding,
+            );
+            let clay_treatment_cost = MaterialCostCalculationService::calculate_processing_cost(
                 &production.clay_treatment_cost,
-                &result.total_freight_cost,
+                &self.rounding,
+            );
+            let total_material_cost = MaterialCostCalculationService::calculate_total_material_cost(
+                &yield_cost,
+                &coagulant_cost,
+                &clay_treatment_cost,
+                &freight_cost,
+                &self.rounding,
             );
 
             // 結果をDTOに変換
             let result_dto = MaterialCostResultDto {
-                row_number: idx + 2, // ヘッダー行を考慮して+2
+                row_number,
+                production_date: production.production_date.value().to_string(),
+                product_code: production.product_code.value().to_string(),
                 raw_material_cost: raw_material_cost.value(),
                 yield_cost: yield_cost.value(),
-                coagulant_cost: production.coagulant_cost.value(),
-                clay_treatment_cost: production.clay_treatment_cost.value(),
-                freight_cost: result.total_freight_cost.value(),
+                yield_rate: production.yield_rate.value(),
+                yield_formula: yield_method.formula().to_string(),
+                coagulant_cost: coagulant_cost.value(),
+                clay_treatment_cost: clay_treatment_cost.value(),
+                freight_cost: freight_cost.value(),
                 total_material_cost: total_material_cost.value(),
+                intermediate_costs: result
+                    .intermediates
+                    .iter()
+                    .map(|i| IntermediateCostDto {
+                        material_code: i.material_code.value().to_string(),
+                        level: i.level,
+                        quantity: i.quantity.value(),
+                        material_cost: i.material_cost.value(),
+                        freight_cost: i.freight_cost.value(),
+                    })
+                    .collect(),
+                explosion: result.explosion.iter().map(bom_node_dto).collect(),
             };
 
             self.output_port.present_calculation_result(&result_dto);
+
+            self.costed_productions.push(CostedProduction {
+                production: production.clone(),
+                result,
+            });
         }
 
+        // 期末ロット残高
+        let balance_dtos: Vec<LotBalanceDto> = ledger
+            .remaining_layers()
+            .iter()
+            .map(|l| LotBalanceDto {
+                product_code: l.product_code.value().to_string(),
+                product_name: l.product_name.clone(),
+                purchase_date: lot_date_label(l.purchase_date.as_ref()),
+                unit_price: l.unit_price.value(),
+                purchased_quantity: l.purchased_quantity.value(),
+                remaining_quantity: l.remaining_quantity.value(),
+                remaining_amount: l.remaining_amount().value(),
+            })
+            .collect();
+        self.output_port.present_lot_balances(&balance_dtos);
+
         self.output_port.present_completion();
         Ok(())
     }
 }
 
 /// 入出庫履歴作成インタラクタ
-pub struct CreateInventoryHistoryInteractor<'a, R, O>
+///
+/// 材料出庫は材料費計算の結果から作成する（材料費計算で報告済みの計算できなかった行は含まない）。
+pub struct CreateInventoryHistoryInteractor<'a, T, OB, O>
 where
-    R: InventoryTransactionRepository,
+    T: InventoryTransactionRepository,
+    OB: OpeningBalanceRepository,
     O: CreateInventoryHistoryOutputPort,
 {
-    transaction_repo: &'a R,
+    transaction_repo: &'a T,
+    opening_balance_repo: &'a OB,
+    costed_productions: &'a [CostedProduction],
     output_port: &'a mut O,
+    safety_stock: SafetyStockPolicy,
+    period_start: Option<TransactionDate>,
 }
 
-impl<'a, R, O> CreateInventoryHistoryInteractor<'a, R, O>
+impl<'a, T, OB, O> CreateInventoryHistoryInteractor<'a, T, OB, O>
 where
-    R: InventoryTransactionRepository,
+    T: InventoryTransactionRepository,
+    OB: OpeningBalanceRepository,
     O: CreateInventoryHistoryOutputPort,
 {
-    pub fn new(transaction_repo: &'a R, output_port: &'a mut O) -> Self {
+    pub fn new(
+        transaction_repo: &'a T,
+        opening_balance_repo: &'a OB,
+        costed_productions: &'a [CostedProduction],
+        output_port: &'a mut O,
+    ) -> Self {
         Self {
             transaction_repo,
+            opening_balance_repo,
+            costed_productions,
             output_port,
+            safety_stock: SafetyStockPolicy::default(),
+            period_start: None,
         }
     }
+
+    /// 安全在庫を設定（未設定の場合はマイナス在庫のみ警告）
+    pub fn with_safety_stock(mut self, safety_stock: SafetyStockPolicy) -> Self {
+        self.safety_stock = safety_stock;
+        self
+    }
+
+    /// 繰越行の期首日付を設定（未設定の場合は最初のトランザクションの月初）
+    pub fn with_period_start(mut self, period_start: Option<TransactionDate>) -> Self {
+        self.period_start = period_start;
+        self
+    }
 }
 
-impl<'a, R, O> CreateInventoryHistoryInputPort for CreateInventoryHistoryInteractor<'a, R, O>
+impl<'a, T, OB, O> CreateInventoryHistoryInputPort
+    for CreateInventoryHistoryInteractor<'a, T, OB, O>
 where
-    R: InventoryTransactionRepository,
+    T: InventoryTransactionRepository,
+    OB: OpeningBalanceRepository,
     O: CreateInventoryHistoryOutputPort,
 {
     fn execute(&mut self) -> Result<()> {
         self.output_port.present_history_start();
 
         // 全トランザクションを取得
-        let transactions = match self.transaction_repo.find_all_transactions() {
+        let mut transactions = match self.transaction_repo.find_all_transactions() {
             Ok(t) => t,
             Err(e) => {
-                self.output_port.present_history_error(&format!("{:?}", e));
-                return Err(e);
+                self.output_port.present_history_error(&error_dto(&e));
+                return Err(e.into());
+            }
+        };
+
+        // 生産で消費した材料の出庫を追加
+        transactions.extend(
+            self.costed_productions
+                .iter()
+                .flat_map(CostedProduction::material_issues),
+        );
+
+        // 期首在庫を取得
+        let opening_balances = match self.opening_balance_repo.find_all() {
+            Ok(b) => b,
+            Err(e) => {
+                self.output_port.present_history_error(&error_dto(&e));
+                return Err(e.into());
             }
         };
 
         // 入出庫履歴を作成
-        let records = match InventoryHistoryService::create_history(transactions) {
-            Ok(r) => r,
+        let history = match InventoryHistoryService::create_history(
+            transactions,
+            opening_balances,
+            self.period_start.as_ref(),
+            &self.safety_stock,
+        ) {
+            Ok(h) => h,
             Err(e) => {
-                self.output_port.present_history_error(&format!("{:?}", e));
-                return Err(e);
+                self.output_port.present_history_error(&error_dto(&e));
+                return Err(e.into());
             }
         };
 
         // 各レコードを出力
-        for record in &records {
+        for record in &history.records {
             let dto = InventoryHistoryRecordDto {
                 date: record.date.value().to_string(),
                 inventory_type: record.inventory_type.as_str().to_string(),
@@ -209,7 +535,25 @@ where
             self.output_port.present_history_record(&dto);
         }
 
-        self.output_port.present_history_completion(records.len());
+        // 在庫警告を出力
+        let warning_dtos: Vec<InventoryWarningDto> = history
+            .warnings
+            .iter()
+            .map(|w| InventoryWarningDto {
+                kind: w.kind.as_str().to_string(),
+                date: w.date.value().to_string(),
+                inventory_type: w.inventory_type.as_str().to_string(),
+                product_code: w.product_code.value().to_string(),
+                product_name: w.product_name.clone(),
+                change_quantity: w.change_quantity.value(),
+                balance: w.balance.value(),
+                threshold: w.threshold,
+            })
+            .collect();
+        self.output_port.present_inventory_warnings(&warning_dtos);
+
+        self.output_port
+            .present_history_completion(history.records.len());
 
         // インタラクタがアウトプットポートを終了
         self.output_port.finalize()?;
@@ -217,3 +561,177 @@ where
         Ok(())
     }
 }
+
+/// 月次受払表作成インタラクタ
+///
+/// 材料出庫は材料費計算の結果から作成する（材料費計算で報告済みの計算できなかった行は含まない）。
+pub struct CreateMonthlyInventoryReportInteractor<'a, T, OB, P, O>
+where
+    T: InventoryTransactionRepository,
+    OB: OpeningBalanceRepository,
+    P: PurchaseRepository,
+    O: CreateMonthlyInventoryReportOutputPort,
+{
+    transaction_repo: &'a T,
+    opening_balance_repo: &'a OB,
+    purchase_repo: &'a P,
+    costed_productions: &'a [CostedProduction],
+    output_port: &'a mut O,
+    period_start: Option<TransactionDate>,
+    rounding: RoundingPolicy,
+}
+
+impl<'a, T, OB, P, O> CreateMonthlyInventoryReportInteractor<'a, T, OB, P, O>
+where
+    T: InventoryTransactionRepository,
+    OB: OpeningBalanceRepository,
+    P: PurchaseRepository,
+    O: CreateMonthlyInventoryReportOutputPort,
+{
+    pub fn new(
+        transaction_repo: &'a T,
+        opening_balance_repo: &'a OB,
+        purchase_repo: &'a P,
+        costed_productions: &'a [CostedProduction],
+        output_port: &'a mut O,
+    ) -> Self {
+        Self {
+            transaction_repo,
+            opening_balance_repo,
+            purchase_repo,
+            costed_productions,
+            output_port,
+            period_start: None,
+            rounding: RoundingPolicy::default(),
+        }
+    }
+
+    /// 最初の月を設定（未設定の場合は最初の受払の月）
+    pub fn with_period_start(mut self, period_start: Option<TransactionDate>) -> Self {
+        self.period_start = period_start;
+        self
+    }
+
+    /// 金額の端数処理を設定（材料費の端数処理を使う）
+    pub fn with_rounding(mut self, rounding: RoundingPolicy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// 入出庫トランザクション・材料出庫・期首在庫から月次受払表を作成
+    fn create_report(&self) -> DomainResult<MonthlyInventoryValuation> {
+        let transactions = self.transaction_repo.find_all_transactions()?;
+        let opening_balances = self.opening_balance_repo.find_all()?;
+
+        InventoryValuationService::create_monthly_report(
+            &transactions,
+            self.costed_productions,
+            &opening_balances,
+            self.period_start.as_ref(),
+            self.purchase_repo,
+            &self.rounding,
+        )
+    }
+}
+
+impl<'a, T, OB, P, O> CreateMonthlyInventoryReportInputPort
+    for CreateMonthlyInventoryReportInteractor<'a, T, OB, P, O>
+where
+    T: InventoryTransactionRepository,
+    OB: OpeningBalanceRepository,
+    P: PurchaseRepository,
+    O: CreateMonthlyInventoryReportOutputPort,
+{
+    fn execute(&mut self) -> Result<()> {
+        self.output_port.present_report_start();
+
+        let valuation = match self.create_report() {
+            Ok(v) => v,
+            Err(e) => {
+                self.output_port.present_report_error(&error_dto(&e));
+                return Err(e.into());
+            }
+        };
+
+        let dtos: Vec<MonthlyInventoryReportDto> = valuation
+            .reports
+            .iter()
+            .map(|r| MonthlyInventoryReportDto {
+                month: r.month.clone(),
+                product_code: r.product_code.value().to_string(),
+                product_name: r.product_name.clone(),
+                opening_quantity: r.opening_quantity.value(),
+                opening_amount: r.opening_amount.to_f64().unwrap_or_default(),
+                production_quantity: r.production_quantity.value(),
+                purchase_quantity: r.purchase_quantity.value(),
+                receipt_amount: r.receipt_amount.to_f64().unwrap_or_default(),
+                sales_quantity: r.sales_quantity.value(),
+                material_issue_quantity: r.material_issue_quantity.value(),
+                issue_amount: r.issue_amount.to_f64().unwrap_or_default(),
+                closing_quantity: r.closing_quantity.value(),
+                closing_amount: r.closing_amount.to_f64().unwrap_or_default(),
+                unit_price: r.unit_price.map(|p| p.value()),
+            })
+            .collect();
+        self.output_port.present_monthly_report(&dtos);
+
+        let missing_dtos: Vec<MissingPriceDto> = valuation
+            .missing_prices
+            .iter()
+            .map(|m| MissingPriceDto {
+                month: m.month.clone(),
+                product_code: m.product_code.value().to_string(),
+                product_name: m.product_name.clone(),
+                quantity: m.quantity.to_f64().unwrap_or_default(),
+            })
+            .collect();
+        self.output_port.present_missing_prices(&missing_dtos);
+
+        Ok(())
+    }
+}
+
+/// ドメインエラーをDTOに変換
+fn error_dto(e: &DomainError) -> ErrorDto {
+    ErrorDto {
+        category: e.category().as_str().to_string(),
+        sheet: e.sheet().unwrap_or_default().to_string(),
+        row: e.row(),
+        column: e.column().unwrap_or_default(),
+        code: e.code().unwrap_or_default().to_string(),
+        // セルの問題は位置を別項目に持つため、内容のみ
+        message: match e {
+            DomainError::InvalidCell(diagnostic) => diagnostic.message.clone(),
+            e => e.to_string(),
+        },
+    }
+}
+
+/// 入力データの問題をDTOに変換
+fn diagnostic_dto(diagnostic: &Diagnostic) -> DiagnosticDto {
+    DiagnosticDto {
+        sheet: diagnostic.sheet.clone(),
+        row: diagnostic.row,
+        column: diagnostic.column.clone().unwrap_or_default(),
+        message: diagnostic.message.clone(),
+    }
+}
+
+/// 部品展開ツリーをDTOに変換
+fn bom_node_dto(node: &BomNode) -> BomNodeDto {
+    BomNodeDto {
+        material_code: node.material_code.value().to_string(),
+        material_name: node.material_name.clone(),
+        quantity: node.quantity.value(),
+        material_cost: node.material_cost.value(),
+        freight_cost: node.freight_cost.value(),
+        children: node.children.iter().map(bom_node_dto).collect(),
+    }
+}
+
+/// ロットの仕入日表示（期首在庫は「期首」）
+fn lot_date_label(purchase_date: Option<&TransactionDate>) -> String {
+    purchase_date
+        .map(|d| d.value().to_string())
+        .unwrap_or_else(|| "期首".to_string())
+}
@@ -1,6 +1,28 @@
 use super::dtos::*;
 use color_eyre::Result;
 
+/// 配合マスタ検証インプットポート
+pub trait ValidateFormulaInputPort {
+    fn execute(&mut self) -> Result<()>;
+}
+
+/// 配合マスタ検証アウトプットポート
+pub trait ValidateFormulaOutputPort {
+    fn present_validation_start(&mut self, total_versions: usize);
+    fn present_formula_issues(&mut self, issues: &[FormulaIssueDto], strict: bool);
+    fn present_validation_error(&mut self, error: &ErrorDto);
+}
+
+/// 入力データ診断結果出力インプットポート
+pub trait ReportDiagnosticsInputPort {
+    fn execute(&mut self) -> Result<()>;
+}
+
+/// 入力データ診断結果出力アウトプットポート
+pub trait ReportDiagnosticsOutputPort {
+    fn present_diagnostics(&mut self, diagnostics: &[DiagnosticDto]);
+}
+
 /// インプットポート（ユースケースの抽象）
 pub trait CalculateMaterialCostInputPort {
     fn execute(&mut self) -> Result<()>;
@@ -10,11 +32,18 @@ pub trait CalculateMaterialCostInputPort {
 pub trait CalculateMaterialCostOutputPort {
     fn present_no_data(&mut self);
     fn present_calculation_start(&mut self, total_rows: usize);
-    fn present_processing_row(&mut self, row_number: usize, product_code: &str);
+    fn present_processing_row(
+        &mut self,
+        row_number: usize,
+        production_date: &str,
+        product_code: &str,
+    );
     fn present_material_consumptions(&mut self, consumptions: &[MaterialConsumptionDto]);
     fn present_calculation_result(&mut self, result: &MaterialCostResultDto);
+    fn present_lot_balances(&mut self, balances: &[LotBalanceDto]);
+    fn present_row_error(&mut self, diagnostic: &DiagnosticDto);
     fn present_completion(&mut self);
-    fn present_error(&mut self, message: &str);
+    fn present_error(&mut self, error: &ErrorDto);
 }
 
 /// 入出庫履歴作成インプットポート
@@ -26,7 +55,31 @@ pub trait CreateInventoryHistoryInputPort {
 pub trait CreateInventoryHistoryOutputPort {
     fn present_history_start(&mut self);
     fn present_history_record(&mut self, record: &InventoryHistoryRecordDto);
+    fn present_inventory_warnings(&mut self, warnings: &[InventoryWarningDto]);
     fn present_history_completion(&mut self, total_records: usize);
-    fn present_history_error(&mut self, message: &str);
+    fn present_history_error(&mut self, error: &ErrorDto);
     fn finalize(&mut self) -> Result<()>;
 }
+
+/// 月次受払表作成インプットポート
+pub trait CreateMonthlyInventoryReportInputPort {
+    fn execute(&mut self) -> Result<()>;
+}
+
+/// 月次受払表作成アウトプットポート
+pub trait CreateMonthlyInventoryReportOutputPort {
+    fn present_report_start(&mut self);
+    fn present_monthly_report(&mut self, reports: &[MonthlyInventoryReportDto]);
+    fn present_missing_prices(&mut self, missing: &[MissingPriceDto]);
+    fn present_report_error(&mut self, error: &ErrorDto);
+}
+
+/// 実行結果保存アウトプットポート
+pub trait SaveRunResultsOutputPort {
+    /// 材料費計算結果と入出庫履歴を新しい実行IDで保存し、実行IDを返す
+    fn save_run(
+        &mut self,
+        results: &[MaterialCostResultDto],
+        history_records: &[InventoryHistoryRecordDto],
+    ) -> Result<i64>;
+}